@@ -269,6 +269,8 @@ enum ByToken {
     Name(Literal),
     ClassName(Literal),
     Testid(Literal),
+    Locator(Literal),
+    Js(Literal),
     Multi,
     /// NotEmpty is the default but can be specified to be explicit.
     NotEmpty,
@@ -296,7 +298,9 @@ impl ByToken {
             | ByToken::XPath(_)
             | ByToken::Name(_)
             | ByToken::ClassName(_)
-            | ByToken::Testid(_) => "selector",
+            | ByToken::Testid(_)
+            | ByToken::Locator(_)
+            | ByToken::Js(_) => "selector",
             ByToken::Multi => "multi",
             ByToken::NotEmpty => "not_empty",
             ByToken::AllowEmpty => "allow_empty",
@@ -467,6 +471,20 @@ impl TryFrom<Meta> for ByToken {
                         ..
                     }),
                 ) if k.is_ident("testid") => Ok(ByToken::Testid(v.token())),
+                (
+                    k,
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(v),
+                        ..
+                    }),
+                ) if k.is_ident("locator") => Ok(ByToken::Locator(v.token())),
+                (
+                    k,
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(v),
+                        ..
+                    }),
+                ) if k.is_ident("js") => Ok(ByToken::Js(v.token())),
                 (
                     k,
                     Expr::Lit(ExprLit {
@@ -535,6 +553,8 @@ impl ByTokens {
                 ByToken::Name(name) => ret.push(quote! { By::Name(#name) }),
                 ByToken::ClassName(class_name) => ret.push(quote! { By::ClassName(#class_name) }),
                 ByToken::Testid(id) => ret.push(quote! { By::Testid(#id) }),
+                ByToken::Locator(key) => ret.push(quote! { By::Locator(#key)? }),
+                ByToken::Js(script) => ret.push(quote! { By::Js(#script, Vec::new()) }),
                 t => self.tokens.push(t),
             }
         }
@@ -550,6 +570,10 @@ impl ByTokens {
         self.tokens.iter().any(|x| matches!(&x, ByToken::Multi))
     }
 
+    pub fn is_locator(&self) -> bool {
+        self.tokens.iter().any(|x| matches!(&x, ByToken::Locator(_)))
+    }
+
     pub fn take_one<F, T>(&mut self, f: F) -> Option<T>
     where
         F: Fn(&ByToken) -> Option<T>,
@@ -690,6 +714,8 @@ impl ToTokens for DebugByTokens {
                 | ByToken::Name(lit)
                 | ByToken::ClassName(lit)
                 | ByToken::Testid(lit)
+                | ByToken::Locator(lit)
+                | ByToken::Js(lit)
                 | ByToken::Description(lit) => lit.to_tokens(tokens),
                 // idents
                 ByToken::Multi
@@ -761,6 +787,7 @@ enum SingleResolverOptions {
     CustomFn(Expr),
     Opts {
         by: TokenStream,
+        locator: bool,
         first: Option<bool>,
         ignore_errors: Option<bool>,
         description: Option<Literal>,
@@ -777,6 +804,7 @@ impl TryFrom<ByTokens> for SingleResolverOptions {
         let s = match t.take_custom() {
             Some(f) => Self::CustomFn(f),
             None => Self::Opts {
+                locator: t.is_locator(),
                 by: t.take_by(),
                 first: t.take_first(),
                 ignore_errors: t.take_ignore_errors(),
@@ -818,6 +846,7 @@ impl ToTokens for SingleResolverArgs {
             }
             SingleResolverOptions::Opts {
                 by,
+                locator,
                 first,
                 ignore_errors,
                 description,
@@ -852,18 +881,11 @@ impl ToTokens for SingleResolverArgs {
                         .set_wait(#wait_ident)
                 );
 
-                match first {
-                    Some(true) => {
-                        tokens.append_all(quote!(
-                            #ty::new_first_opts(base.clone(), #by, #opts_ident)
-                        ));
-                    }
-                    _ => {
-                        tokens.append_all(quote!(
-                            #ty::new_single_opts(base.clone(), #by, #opts_ident)
-                        ));
-                    }
-                }
+                let resolver = match first {
+                    Some(true) => quote!(#ty::new_first_opts(base.clone(), #by, #opts_ident)),
+                    _ => quote!(#ty::new_single_opts(base.clone(), #by, #opts_ident)),
+                };
+                tokens.append_all(wrap_locator(ty, *locator, resolver));
             }
         }
     }
@@ -874,6 +896,7 @@ enum MultiResolverOptions {
     CustomFn(Expr),
     Opts {
         by: TokenStream,
+        locator: bool,
         allow_empty: Option<bool>,
         ignore_errors: Option<bool>,
         description: Option<Literal>,
@@ -891,6 +914,7 @@ impl TryFrom<ByTokens> for MultiResolverOptions {
         let s = match t.take_custom() {
             Some(f) => Self::CustomFn(f),
             None => Self::Opts {
+                locator: t.is_locator(),
                 by: t.take_by(),
                 allow_empty: t.take_allow_empty(),
                 ignore_errors: t.take_ignore_errors(),
@@ -932,6 +956,7 @@ impl ToTokens for MultiResolverArgs {
             }
             MultiResolverOptions::Opts {
                 by,
+                locator,
                 allow_empty,
                 ignore_errors,
                 description,
@@ -966,23 +991,30 @@ impl ToTokens for MultiResolverArgs {
                         .set_wait(#wait_ident)
                 );
 
-                match allow_empty {
-                    Some(true) => {
-                        tokens.append_all(
-                            quote!(#ty::new_allow_empty_opts(base.clone(), #by, #opts_ident)),
-                        );
-                    }
-                    _ => {
-                        tokens.append_all(quote!(
-                            #ty::new_not_empty_opts(base.clone(), #by, #opts_ident)
-                        ));
-                    }
-                }
+                let resolver = match allow_empty {
+                    Some(true) => quote!(#ty::new_allow_empty_opts(base.clone(), #by, #opts_ident)),
+                    _ => quote!(#ty::new_not_empty_opts(base.clone(), #by, #opts_ident)),
+                };
+                tokens.append_all(wrap_locator(ty, *locator, resolver));
             }
         }
     }
 }
 
+/// Wraps a resolver whose selector is a named locator, so that the locator is looked up
+/// (and a missing locator is reported) each time the element is resolved, rather than when
+/// the component is created.
+fn wrap_locator(ty: &TokenStream, locator: bool, resolver: TokenStream) -> TokenStream {
+    if !locator {
+        return resolver;
+    }
+    quote!(
+        #ty::new_custom(base.clone(), |base: ::thirtyfour::WebElement| async move {
+            #resolver.resolve().await
+        })
+    )
+}
+
 /// Converts GenericType<Args> to GenericType::<Args> to call ::new_*() on it.
 ///
 /// Non-generic types will be returned as is.
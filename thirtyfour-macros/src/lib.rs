@@ -50,6 +50,12 @@ pub(crate) use bail;
 /// - `xpath = "..."`: Select element by XPath.
 /// - `name = "..."`: Select element by name.
 /// - `class = "..."`: Select element by class name.
+/// - `testid = "..."`: Select element by `data-testid` attribute.
+/// - `locator = "..."`: Select element using the named locator from the global
+///   `LocatorRepository` (see `LocatorRepository::set_global()`). The locator is looked up
+///   when the element is resolved.
+/// - `js = "..."`: Select elements using custom javascript, which returns an element or an
+///   array of elements (see `By::Js()`).
 ///
 /// Optional attributes available within `#[by(..)]` include:
 /// - `single`: (default, single element only) Return `NoSuchElement` if the number of elements
@@ -576,7 +576,7 @@ impl ActionChain {
     /// # }
     /// ```
     pub fn move_to_element_center(mut self, element: &WebElement) -> Self {
-        self.pointer_actions.move_to_element_center(element.element_id.clone());
+        self.pointer_actions.move_to_element_center(element.element_id());
         self.key_actions.pause();
         self
     }
@@ -623,7 +623,7 @@ impl ActionChain {
         x_offset: i64,
         y_offset: i64,
     ) -> Self {
-        self.pointer_actions.move_to_element(element.element_id.clone(), x_offset, y_offset);
+        self.pointer_actions.move_to_element(element.element_id(), x_offset, y_offset);
         self.key_actions.pause();
         self
     }
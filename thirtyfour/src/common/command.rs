@@ -1,6 +1,9 @@
 use http::Method;
 use serde_json::{json, Value};
 
+use crate::common::css::quote_string;
+use crate::common::locators::LocatorRepository;
+use crate::common::validate::{validate_css, validate_xpath};
 use crate::common::{
     capabilities::desiredcapabilities::make_w3c_caps,
     cookie::Cookie,
@@ -8,10 +11,15 @@ use crate::common::{
     print::PrintParameters,
     types::{ElementId, OptionRect, SessionId, TimeoutConfiguration, WindowHandle},
 };
+use crate::error::{invalid_selector, WebDriverError, WebDriverResult};
+use crate::js::{find_by_script, FIND_ACCESSIBLE, FIND_DEEP_CSS};
 use crate::IntoArcStr;
 use crate::RequestData;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 use std::fmt;
 use std::fmt::Debug;
+use std::str::FromStr;
 use std::sync::Arc;
 
 /// The W3C element identifier key.
@@ -46,6 +54,12 @@ impl Selector {
     }
 }
 
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.query)
+    }
+}
+
 /// Element Selector representation.
 #[derive(Debug, Clone)]
 pub enum BySelector {
@@ -67,6 +81,20 @@ pub enum BySelector {
     Css(Arc<str>),
     /// Select an element by data-testid.
     Testid(Arc<str>),
+    /// Select an element by ARIA role and accessible name.
+    Role(Arc<str>, Arc<str>),
+    /// Select a form control by the text of its label.
+    Label(Arc<str>),
+    /// Select an element by its placeholder text.
+    Placeholder(Arc<str>),
+    /// Select an element by its text, either exactly or as a case-insensitive substring.
+    Text(Arc<str>, bool),
+    /// Select an element by its alt text.
+    AltText(Arc<str>),
+    /// Select an element by CSS, descending into shadow roots at each `>>>`.
+    DeepCss(Arc<str>),
+    /// Select elements using a custom javascript function and its arguments.
+    Js(Arc<str>, Arc<[Value]>),
 }
 
 /// Element Selector struct providing a convenient way to specify selectors.
@@ -79,8 +107,12 @@ pub struct By {
 impl By {
     /// Select element by id.
     pub fn Id(id: impl IntoArcStr) -> Self {
+        let id = id.into();
+        if id.starts_with('#') {
+            tracing::warn!("By::Id({id:?}) starts with '#', which will be matched literally");
+        }
         Self {
-            selector: BySelector::Id(id.into()),
+            selector: BySelector::Id(id),
         }
     }
 
@@ -115,7 +147,7 @@ impl By {
     /// Select element by name.
     pub fn Name(name: impl IntoArcStr) -> Self {
         Self {
-            selector: BySelector::Css(format!(r#"[name="{}"]"#, name.into()).into()),
+            selector: BySelector::Css(format!("[name={}]", quote_string(&name.into())).into()),
         }
     }
 
@@ -128,17 +160,336 @@ impl By {
 
     /// Select element by class.
     pub fn ClassName(name: impl IntoArcStr) -> Self {
+        let name = name.into();
+        if name.starts_with('.') {
+            tracing::warn!(
+                "By::ClassName({name:?}) should be a single class name; \
+                 use By::Css() to combine classes"
+            );
+        }
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            // Keep the name as given, so that `By::validate()` can reject it.
+            return Self {
+                selector: BySelector::ClassName(name),
+            };
+        }
         Self {
-            selector: BySelector::Css(format!(".{}", name.into()).into()),
+            selector: BySelector::Css(format!(".{}", name).into()),
         }
     }
 
     /// Select element by testid.
     pub fn Testid(id: impl IntoArcStr) -> Self {
         Self {
-            selector: BySelector::Css(format!("[data-testid=\"{}\"]", id.into()).into()),
+            selector: BySelector::Css(format!("[data-testid={}]", quote_string(&id.into())).into()),
+        }
+    }
+
+    /// Select element by ARIA role and accessible name, e.g. `By::Role("button", "Save")`.
+    ///
+    /// The role may be explicit (the `role` attribute) or implicit (e.g. `<button>` has the
+    /// `button` role). The name is computed from `aria-labelledby`, `aria-label`, associated
+    /// labels, alt text or the element content, and must match exactly (ignoring extra
+    /// whitespace). Pass an empty name to match any name. Hidden elements are never matched.
+    pub fn Role(role: impl IntoArcStr, name: impl IntoArcStr) -> Self {
+        Self {
+            selector: BySelector::Role(role.into(), name.into()),
+        }
+    }
+
+    /// Select element by the text of its label, including `aria-label` and `aria-labelledby`.
+    pub fn Label(text: impl IntoArcStr) -> Self {
+        Self {
+            selector: BySelector::Label(text.into()),
+        }
+    }
+
+    /// Select element by placeholder text.
+    pub fn Placeholder(text: impl IntoArcStr) -> Self {
+        Self {
+            selector: BySelector::Placeholder(text.into()),
+        }
+    }
+
+    /// Select element by text.
+    ///
+    /// If `exact` is true, the text must match exactly (ignoring extra whitespace),
+    /// otherwise it is a case-insensitive substring match.
+    /// Only the innermost elements containing the text are matched, and text that is
+    /// hidden (including scripts and styles) is ignored.
+    pub fn Text(text: impl IntoArcStr, exact: bool) -> Self {
+        Self {
+            selector: BySelector::Text(text.into(), exact),
+        }
+    }
+
+    /// Select element by alt text.
+    pub fn AltText(text: impl IntoArcStr) -> Self {
+        Self {
+            selector: BySelector::AltText(text.into()),
         }
     }
+
+    /// Select element by CSS, piercing shadow roots.
+    ///
+    /// The selector is split on `>>>`. Each part is a CSS selector that is matched within
+    /// the open shadow roots of the elements matched by the previous part, e.g.
+    /// `By::DeepCss("my-app >>> settings-panel >>> button.save")`.
+    /// The first part is matched normally, from the document or element being searched.
+    ///
+    /// Closed shadow roots cannot be accessed.
+    pub fn DeepCss(css: impl IntoArcStr) -> Self {
+        Self {
+            selector: BySelector::DeepCss(css.into()),
+        }
+    }
+
+    /// Select element using the locator with the specified key in the global
+    /// `LocatorRepository` (see `LocatorRepository::set_global()`).
+    ///
+    /// Returns an `InvalidSelector` error if there is no such locator.
+    pub fn Locator(key: impl IntoArcStr) -> WebDriverResult<Self> {
+        let key = key.into();
+        LocatorRepository::global().get(&key).cloned().ok_or_else(|| {
+            invalid_selector(format!(
+                "invalid locator {key:?}: no such key in the global locator repository"
+            ))
+        })
+    }
+
+    /// Select elements using custom javascript.
+    ///
+    /// The script is the body of a function that returns an element, an array (or
+    /// `NodeList`) of elements, or null if there are no matching elements. The specified
+    /// arguments are available via `arguments`, as they are for `WebDriver::execute()`, and
+    /// `this` is the element being searched from (or the document).
+    ///
+    /// This can be used anywhere a `By` is accepted, including `WebDriver::query()` and the
+    /// `#[by(js = "...")]` component attribute, so custom lookup logic can be shared
+    /// between searches and waits.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// let by = By::Js(
+    ///     r#"return Array.from(this.querySelectorAll("tr")).filter(
+    ///         (row) => row.cells.length > arguments[0]
+    ///     );"#,
+    ///     vec![serde_json::json!(2)],
+    /// );
+    /// let rows = driver.query(by).all_from_selector_required().await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn Js(script: impl IntoArcStr, args: impl Into<Arc<[Value]>>) -> Self {
+        Self {
+            selector: BySelector::Js(script.into(), args.into()),
+        }
+    }
+
+    /// Check the syntax of this selector locally, without sending it to WebDriver.
+    ///
+    /// CSS selectors (including those built by `By::Tag()` and `By::ClassName()`, and each
+    /// part of `By::DeepCss()`) and XPath expressions are parsed, and an `InvalidSelector`
+    /// error is returned describing the first syntax error and its position. Class names and
+    /// ids must not be empty or contain whitespace. Other selectors (including `By::Js()`) are
+    /// always valid.
+    ///
+    /// This is checked before every element search (unless disabled via
+    /// `WebDriverConfigBuilder::validate_selectors()`), so a malformed selector fails without a
+    /// round trip to the browser. It can also be called directly to check selectors (for
+    /// example, those used by page objects) in unit tests, without starting a browser.
+    /// See `thirtyfour::common::validate` for details.
+    ///
+    /// # Example:
+    /// ```
+    /// # use thirtyfour::prelude::*;
+    /// assert!(By::Css("form > input[type='text']").validate().is_ok());
+    ///
+    /// let err = By::XPath("//button[@id='save'").validate().unwrap_err();
+    /// assert!(err.to_string().contains("at position 19"));
+    /// ```
+    pub fn validate(&self) -> WebDriverResult<()> {
+        let result = match &self.selector {
+            BySelector::Css(css) | BySelector::Tag(css) => validate_css(css),
+            BySelector::ClassName(name) | BySelector::Id(name)
+                if name.is_empty() || name.contains(char::is_whitespace) =>
+            {
+                return Err(invalid_selector(format!(
+                    "invalid selector {self}: class names and ids must not be empty or \
+                     contain whitespace"
+                )))
+            }
+            BySelector::ClassName(name) => validate_css(&format!(".{name}")),
+            BySelector::XPath(xpath) => validate_xpath(xpath),
+            BySelector::DeepCss(css) => {
+                let mut offset = 0;
+                css.split(">>>").try_for_each(|part| {
+                    let result = validate_css(part).map_err(|mut e| {
+                        e.position += offset;
+                        e
+                    });
+                    offset += part.chars().count() + 3;
+                    result
+                })
+            }
+            _ => Ok(()),
+        };
+        result.map_err(|e| invalid_selector(format!("invalid selector {self}: {e}")))
+    }
+
+    /// Get this selector in the string syntax accepted by `By::from_str()`,
+    /// e.g. `css=button.primary`. This is also how a `By` is serialized.
+    ///
+    /// Selectors built by `By::Name()`, `By::Tag()`, `By::ClassName()` and `By::Testid()` are
+    /// converted to CSS, so they are written with the `css=` prefix.
+    /// The arguments of a `By::Js()` selector are not included.
+    pub fn to_locator_string(&self) -> String {
+        let (prefix, value): (&str, &str) = match &self.selector {
+            BySelector::Id(x) => ("id", x),
+            BySelector::XPath(x) => ("xpath", x),
+            BySelector::LinkText(x) => ("link-text", x),
+            BySelector::PartialLinkText(x) => ("partial-link-text", x),
+            BySelector::Name(x) => ("name", x),
+            BySelector::Tag(x) => ("tag", x),
+            BySelector::ClassName(x) => ("class", x),
+            BySelector::Css(x) => ("css", x),
+            BySelector::Testid(x) => ("testid", x),
+            BySelector::Role(role, name) if name.is_empty() => ("role", role),
+            BySelector::Role(role, name) => {
+                let name = serde_json::to_string(name).unwrap_or_default();
+                return format!("role={role}[name={name}]");
+            }
+            BySelector::Label(x) => ("label", x),
+            BySelector::Placeholder(x) => ("placeholder", x),
+            BySelector::Text(x, true) => ("text", x),
+            BySelector::Text(x, false) => ("partial-text", x),
+            BySelector::AltText(x) => ("alt-text", x),
+            BySelector::DeepCss(x) => ("deep-css", x),
+            BySelector::Js(x, _) => ("js", x),
+        };
+        format!("{prefix}={value}")
+    }
+
+    /// Get the script used to find elements with this selector, along with its query
+    /// argument, if the selector is not supported natively by WebDriver.
+    ///
+    /// The script is called with the element to search from (or null) and the query,
+    /// and must return an array of elements.
+    pub(crate) fn script(&self) -> Option<(Cow<'static, str>, Value)> {
+        let query = match &self.selector {
+            BySelector::Role(role, name) => json!({"kind": "role", "role": role, "name": name}),
+            BySelector::Label(text) => json!({"kind": "label", "text": text}),
+            BySelector::Placeholder(text) => json!({"kind": "placeholder", "text": text}),
+            BySelector::Text(text, exact) => json!({"kind": "text", "text": text, "exact": exact}),
+            BySelector::AltText(text) => json!({"kind": "alt_text", "text": text}),
+            BySelector::DeepCss(css) => {
+                let parts: Vec<&str> = css.split(">>>").map(str::trim).collect();
+                return Some((Cow::Borrowed(FIND_DEEP_CSS), json!(parts)));
+            }
+            BySelector::Js(script, args) => {
+                return Some((Cow::Owned(find_by_script(script)), json!(args)));
+            }
+            _ => return None,
+        };
+        Some((Cow::Borrowed(FIND_ACCESSIBLE), query))
+    }
+}
+
+impl FromStr for By {
+    type Err = WebDriverError;
+
+    /// Parse a selector written as a prefix and a value, such as `css=button.primary`,
+    /// `xpath=//button`, `id=save`, `testid=save-button` or `text=Save`.
+    ///
+    /// The supported prefixes are `css`, `xpath`, `id`, `name`, `tag`, `class`, `testid`,
+    /// `link-text`, `partial-link-text`, `text`, `partial-text`, `label`, `placeholder`,
+    /// `alt-text`, `deep-css`, `role`, `locator` and `js`. A role may be followed by an accessible
+    /// name in JSON string syntax, as in `role=button[name="Save"]`.
+    ///
+    /// Whitespace around the prefix and the value is ignored, and an empty value is an
+    /// `InvalidSelector` error. The selector is also checked via `By::validate()`, so syntax
+    /// errors are reported when the selector is parsed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((prefix, value)) = s.split_once('=') else {
+            return Err(invalid_selector(format!(
+                "invalid locator {s:?}: expected a prefix such as \"css=\" or \"xpath=\""
+            )));
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(invalid_selector(format!("invalid locator {s:?}: empty selector")));
+        }
+        let by = match prefix.trim() {
+            "css" => By::Css(value),
+            "xpath" => By::XPath(value),
+            "id" => By::Id(value),
+            "name" => By::Name(value),
+            "tag" => By::Tag(value),
+            "class" => By::ClassName(value),
+            "testid" => By::Testid(value),
+            "link-text" => By::LinkText(value),
+            "partial-link-text" => By::PartialLinkText(value),
+            "text" => By::Text(value, true),
+            "partial-text" => By::Text(value, false),
+            "label" => By::Label(value),
+            "placeholder" => By::Placeholder(value),
+            "alt-text" => By::AltText(value),
+            "deep-css" => By::DeepCss(value),
+            "locator" => By::Locator(value)?,
+            "js" => By::Js(value, Vec::new()),
+            "role" => match value.split_once('[') {
+                None => By::Role(value, ""),
+                Some((role, rest)) => {
+                    let name = rest
+                        .strip_prefix("name=")
+                        .and_then(|x| x.strip_suffix(']'))
+                        .and_then(|x| serde_json::from_str::<String>(x).ok())
+                        .ok_or_else(|| {
+                            invalid_selector(format!(
+                                "invalid locator {s:?}: expected role=ROLE[name=\"NAME\"]"
+                            ))
+                        })?;
+                    By::Role(role, name)
+                }
+            },
+            prefix => {
+                return Err(invalid_selector(format!(
+                    "invalid locator {s:?}: unknown prefix {prefix:?}"
+                )))
+            }
+        };
+        by.validate()?;
+        Ok(by)
+    }
+}
+
+impl Serialize for By {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let BySelector::Js(_, args) = &self.selector {
+            if !args.is_empty() {
+                return Err(serde::ser::Error::custom(
+                    "cannot serialize a By::Js() selector with arguments",
+                ));
+            }
+        }
+        serializer.serialize_str(&self.to_locator_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for By {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 impl fmt::Display for BySelector {
@@ -153,6 +504,14 @@ impl fmt::Display for BySelector {
             BySelector::ClassName(cname) => write!(f, "Class({})", cname),
             BySelector::Css(css) => write!(f, "CSS({})", css),
             BySelector::Testid(id) => write!(f, "Testid({})", id),
+            BySelector::Role(role, name) => write!(f, "Role({}, {:?})", role, name),
+            BySelector::Label(text) => write!(f, "Label({})", text),
+            BySelector::Placeholder(text) => write!(f, "Placeholder({})", text),
+            BySelector::Text(text, true) => write!(f, "Text({})", text),
+            BySelector::Text(text, false) => write!(f, "Partial Text({})", text),
+            BySelector::AltText(text) => write!(f, "Alt Text({})", text),
+            BySelector::DeepCss(css) => write!(f, "Deep CSS({})", css),
+            BySelector::Js(script, _) => write!(f, "JS({})", script.trim()),
         }
     }
 }
@@ -166,17 +525,31 @@ impl fmt::Display for By {
 impl From<BySelector> for Selector {
     fn from(by: BySelector) -> Self {
         match by {
-            BySelector::Id(x) => Selector::new("css selector", format!("[id=\"{}\"]", x)),
+            BySelector::Id(x) => {
+                Selector::new("css selector", format!("[id={}]", quote_string(&x)))
+            }
             BySelector::XPath(x) => Selector::new("xpath", x),
             BySelector::LinkText(x) => Selector::new("link text", x),
             BySelector::PartialLinkText(x) => Selector::new("partial link text", x),
-            BySelector::Name(x) => Selector::new("css selector", format!("[name=\"{}\"]", x)),
+            BySelector::Name(x) => {
+                Selector::new("css selector", format!("[name={}]", quote_string(&x)))
+            }
             BySelector::Tag(x) => Selector::new("css selector", x),
             BySelector::ClassName(x) => Selector::new("css selector", format!(".{}", x)),
             BySelector::Css(x) => Selector::new("css selector", x),
             BySelector::Testid(x) => {
-                Selector::new("testid selector", format!("[data-testid=\"{}\"]", x))
+                Selector::new("testid selector", format!("[data-testid={}]", quote_string(&x)))
             }
+            // These are resolved via script (see `By::script()`) rather than sent to
+            // WebDriver, so the selector is only used for descriptions.
+            BySelector::Role(role, name) => Selector::new("role", format!("{role} {name:?}")),
+            BySelector::Label(x) => Selector::new("label", x),
+            BySelector::Placeholder(x) => Selector::new("placeholder", x),
+            BySelector::Text(x, true) => Selector::new("text", x),
+            BySelector::Text(x, false) => Selector::new("partial text", x),
+            BySelector::AltText(x) => Selector::new("alt text", x),
+            BySelector::DeepCss(x) => Selector::new("deep css selector", x),
+            BySelector::Js(x, _) => Selector::new("javascript", x),
         }
     }
 }
@@ -272,6 +645,33 @@ pub enum Command {
 pub trait FormatRequestData: Debug {
     /// Format the command into a `RequestData` struct.
     fn format_request(&self, session_id: &SessionId) -> RequestData;
+
+    /// The name of this command, e.g. `FindElement`.
+    ///
+    /// By default, this is the name of the enum variant (or struct) as shown by its
+    /// `Debug` implementation.
+    fn command_name(&self) -> Cow<'static, str> {
+        let debug = format!("{self:?}");
+        match debug.find(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            Some(end) => Cow::Owned(debug[..end].to_string()),
+            None => Cow::Owned(debug),
+        }
+    }
+
+    /// The id of the element this command operates on, if any.
+    fn element_id(&self) -> Option<&ElementId> {
+        None
+    }
+
+    /// The selector used to search for elements, if any.
+    fn selector(&self) -> Option<&Selector> {
+        None
+    }
+
+    /// This command as a `Command`, if it is one.
+    fn command(&self) -> Option<&Command> {
+        None
+    }
 }
 
 impl FormatRequestData for Command {
@@ -528,4 +928,147 @@ impl FormatRequestData for Command {
             }
         }
     }
+
+    fn command_name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self {
+            Command::NewSession(..) => "NewSession",
+            Command::DeleteSession => "DeleteSession",
+            Command::Status => "Status",
+            Command::GetTimeouts => "GetTimeouts",
+            Command::SetTimeouts(..) => "SetTimeouts",
+            Command::NavigateTo(..) => "NavigateTo",
+            Command::GetCurrentUrl => "GetCurrentUrl",
+            Command::Back => "Back",
+            Command::Forward => "Forward",
+            Command::Refresh => "Refresh",
+            Command::GetTitle => "GetTitle",
+            Command::GetWindowHandle => "GetWindowHandle",
+            Command::CloseWindow => "CloseWindow",
+            Command::SwitchToWindow(..) => "SwitchToWindow",
+            Command::GetWindowHandles => "GetWindowHandles",
+            Command::NewWindow => "NewWindow",
+            Command::NewTab => "NewTab",
+            Command::SwitchToFrameDefault => "SwitchToFrameDefault",
+            Command::SwitchToFrameNumber(..) => "SwitchToFrameNumber",
+            Command::SwitchToFrameElement(..) => "SwitchToFrameElement",
+            Command::SwitchToParentFrame => "SwitchToParentFrame",
+            Command::GetWindowRect => "GetWindowRect",
+            Command::SetWindowRect(..) => "SetWindowRect",
+            Command::MaximizeWindow => "MaximizeWindow",
+            Command::MinimizeWindow => "MinimizeWindow",
+            Command::FullscreenWindow => "FullscreenWindow",
+            Command::GetActiveElement => "GetActiveElement",
+            Command::FindElement(..) => "FindElement",
+            Command::FindElements(..) => "FindElements",
+            Command::FindElementFromElement(..) => "FindElementFromElement",
+            Command::FindElementsFromElement(..) => "FindElementsFromElement",
+            Command::IsElementSelected(..) => "IsElementSelected",
+            Command::IsElementDisplayed(..) => "IsElementDisplayed",
+            Command::GetElementAttribute(..) => "GetElementAttribute",
+            Command::GetElementProperty(..) => "GetElementProperty",
+            Command::GetElementCssValue(..) => "GetElementCssValue",
+            Command::GetElementText(..) => "GetElementText",
+            Command::GetElementTagName(..) => "GetElementTagName",
+            Command::GetElementRect(..) => "GetElementRect",
+            Command::IsElementEnabled(..) => "IsElementEnabled",
+            Command::ElementClick(..) => "ElementClick",
+            Command::ElementClear(..) => "ElementClear",
+            Command::ElementSendKeys(..) => "ElementSendKeys",
+            Command::GetPageSource => "GetPageSource",
+            Command::ExecuteScript(..) => "ExecuteScript",
+            Command::ExecuteAsyncScript(..) => "ExecuteAsyncScript",
+            Command::GetAllCookies => "GetAllCookies",
+            Command::GetNamedCookie(..) => "GetNamedCookie",
+            Command::AddCookie(..) => "AddCookie",
+            Command::DeleteCookie(..) => "DeleteCookie",
+            Command::DeleteAllCookies => "DeleteAllCookies",
+            Command::PerformActions(..) => "PerformActions",
+            Command::ReleaseActions => "ReleaseActions",
+            Command::DismissAlert => "DismissAlert",
+            Command::AcceptAlert => "AcceptAlert",
+            Command::GetAlertText => "GetAlertText",
+            Command::SendAlertText(..) => "SendAlertText",
+            Command::PrintPage(..) => "PrintPage",
+            Command::TakeScreenshot => "TakeScreenshot",
+            Command::TakeElementScreenshot(..) => "TakeElementScreenshot",
+            Command::ExtensionCommand(..) => "ExtensionCommand",
+        })
+    }
+
+    fn command(&self) -> Option<&Command> {
+        Some(self)
+    }
+
+    fn element_id(&self) -> Option<&ElementId> {
+        match self {
+            Command::SwitchToFrameElement(element_id)
+            | Command::FindElementFromElement(element_id, _)
+            | Command::FindElementsFromElement(element_id, _)
+            | Command::IsElementSelected(element_id)
+            | Command::IsElementDisplayed(element_id)
+            | Command::GetElementAttribute(element_id, _)
+            | Command::GetElementProperty(element_id, _)
+            | Command::GetElementCssValue(element_id, _)
+            | Command::GetElementText(element_id)
+            | Command::GetElementTagName(element_id)
+            | Command::GetElementRect(element_id)
+            | Command::IsElementEnabled(element_id)
+            | Command::ElementClick(element_id)
+            | Command::ElementClear(element_id)
+            | Command::ElementSendKeys(element_id, _)
+            | Command::TakeElementScreenshot(element_id) => Some(element_id),
+            _ => None,
+        }
+    }
+
+    fn selector(&self) -> Option<&Selector> {
+        match self {
+            Command::FindElement(selector)
+            | Command::FindElements(selector)
+            | Command::FindElementFromElement(_, selector)
+            | Command::FindElementsFromElement(_, selector) => Some(selector),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_metadata() {
+        let id = ElementId::from("abc");
+        let cmd = Command::FindElementFromElement(id.clone(), By::Id("button1").into());
+        assert_eq!(cmd.command_name(), "FindElementFromElement");
+        assert_eq!(cmd.element_id(), Some(&id));
+        assert_eq!(cmd.selector().unwrap().to_string(), "css selector: [id=\"button1\"]");
+
+        assert_eq!(Command::GetTitle.command_name(), "GetTitle");
+        assert!(Command::GetTitle.element_id().is_none());
+        assert!(Command::GetTitle.selector().is_none());
+    }
+
+    #[test]
+    fn test_deep_css_script() {
+        let (script, query) =
+            By::DeepCss("my-app >>> settings-panel>>>button.save").script().unwrap();
+        assert_eq!(script, FIND_DEEP_CSS);
+        assert_eq!(query, json!(["my-app", "settings-panel", "button.save"]));
+        assert!(By::Css("my-app").script().is_none());
+    }
+
+    #[test]
+    fn test_js_selector() {
+        let by = By::Js("return this.querySelector(arguments[0]);", vec![json!("button")]);
+        let (script, query) = by.script().unwrap();
+        assert!(script.contains("\nreturn this.querySelector(arguments[0]);\n"));
+        assert_eq!(query, json!(["button"]));
+        assert!(by.validate().is_ok());
+        assert_eq!(by.to_string(), "JS(return this.querySelector(arguments[0]);)");
+        assert!(serde_json::to_string(&by).is_err());
+
+        let by: By = "js=return document.body;".parse().unwrap();
+        assert_eq!(serde_json::to_string(&by).unwrap(), r#""js=return document.body;""#);
+    }
 }
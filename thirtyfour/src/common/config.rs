@@ -1,12 +1,16 @@
+use crate::common::events::EventListener;
 use crate::error::WebDriverError;
 use crate::{
     extensions::query::{ElementPollerWithTimeout, IntoElementPoller},
     prelude::WebDriverResult,
+    session::http::TracePropagator,
 };
 use const_format::formatcp;
 use http::HeaderValue;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 /// Configuration options used by a `WebDriver` instance and the related `SessionHandle`.
 ///
@@ -22,6 +26,45 @@ pub struct WebDriverConfig {
     pub user_agent: HeaderValue,
     /// The timeout duration for reqwest client requests.
     pub reqwest_timeout: Duration,
+    /// The maximum time to wait for each individual command to complete.
+    ///
+    /// If a command takes longer than this, the HTTP request is aborted and
+    /// `WebDriverError::CommandTimeout` is returned. If `None`, only `reqwest_timeout` applies.
+    pub command_timeout: Option<Duration>,
+    /// Token used to abort any in-flight commands.
+    ///
+    /// Once the token is cancelled, every pending and future command sent with this config
+    /// is aborted and `WebDriverError::CommandCancelled` is returned.
+    pub cancellation_token: Option<CancellationToken>,
+    /// Used to add trace context headers (e.g. `traceparent`) to every request.
+    pub trace_propagator: Option<Arc<dyn TracePropagator>>,
+    /// If true, record the count, latency and errors of every command sent by the session.
+    ///
+    /// See `SessionHandle::metrics_report()` and `WebDriver::quit_with_report()`.
+    pub collect_metrics: bool,
+    /// Listeners notified of every command, navigation, element search and interaction.
+    pub event_listeners: Vec<Arc<dyn EventListener>>,
+    /// If true, the context of errors from failed commands includes the URL of the page.
+    ///
+    /// This sends an extra `GetCurrentUrl` command for every failed command, including
+    /// those that are routinely retried while polling.
+    pub error_url_context: bool,
+    /// If set, save a screenshot, the page source, the current URL and the recent command
+    /// history into this directory whenever a command fails.
+    ///
+    /// See `SessionHandle::failure_artifacts_guard()` for more details.
+    pub failure_artifacts_dir: Option<PathBuf>,
+    /// What to do when an element becomes stale.
+    pub stale_element_policy: StaleElementPolicy,
+    /// If true, element queries check their built-in filters for all candidate elements
+    /// with a single script, unless overridden via `ElementQuery::batch_filters()`.
+    pub batch_query_filters: bool,
+    /// If true, element queries that find nothing attach a `QueryDiagnostics` report to
+    /// the error, unless overridden via `ElementQuery::diagnostics()`. Defaults to false.
+    pub query_diagnostics: bool,
+    /// If true, selectors are checked via `By::validate()` before each element search.
+    /// Defaults to false.
+    pub validate_selectors: bool,
 }
 
 impl Default for WebDriverConfig {
@@ -65,6 +108,23 @@ impl WebDriverConfig {
     }
 }
 
+/// What to do when a command fails because an element is no longer attached to the DOM.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StaleElementPolicy {
+    /// Return the `StaleElementReference` error.
+    #[default]
+    Fail,
+    /// Find the element again using the selector (and parent element) it was originally found
+    /// with, then retry the command once.
+    ///
+    /// This only applies to elements returned by `find()`, and by `ElementQuery::first()`,
+    /// `first_opt()` or `single()`, where the whole query (including any relations and
+    /// filters) is run again, without waiting. Elements returned with other elements (e.g. by
+    /// `find_all()`) have no reliable identity once they are stale, so they are never found
+    /// again.
+    Refind,
+}
+
 /// Builder for `WebDriverConfig`.
 #[derive(Debug)]
 pub struct WebDriverConfigBuilder {
@@ -72,6 +132,17 @@ pub struct WebDriverConfigBuilder {
     poller: Option<Arc<dyn IntoElementPoller + Send + Sync>>,
     user_agent: Option<WebDriverResult<HeaderValue>>,
     reqwest_timeout: Duration,
+    command_timeout: Option<Duration>,
+    cancellation_token: Option<CancellationToken>,
+    trace_propagator: Option<Arc<dyn TracePropagator>>,
+    collect_metrics: bool,
+    event_listeners: Vec<Arc<dyn EventListener>>,
+    error_url_context: bool,
+    failure_artifacts_dir: Option<PathBuf>,
+    stale_element_policy: StaleElementPolicy,
+    batch_query_filters: bool,
+    query_diagnostics: bool,
+    validate_selectors: bool,
 }
 
 impl Default for WebDriverConfigBuilder {
@@ -88,6 +159,17 @@ impl WebDriverConfigBuilder {
             poller: None,
             user_agent: None,
             reqwest_timeout: Duration::from_secs(120),
+            command_timeout: None,
+            cancellation_token: None,
+            trace_propagator: None,
+            collect_metrics: false,
+            event_listeners: Vec::new(),
+            error_url_context: false,
+            failure_artifacts_dir: None,
+            stale_element_policy: StaleElementPolicy::default(),
+            batch_query_filters: false,
+            query_diagnostics: false,
+            validate_selectors: false,
         }
     }
 
@@ -119,6 +201,91 @@ impl WebDriverConfigBuilder {
         self
     }
 
+    /// Set the timeout for each individual command.
+    pub fn command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the token used to cancel in-flight commands.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Set the propagator used to add trace context headers to every request.
+    pub fn trace_propagator(mut self, propagator: Arc<dyn TracePropagator>) -> Self {
+        self.trace_propagator = Some(propagator);
+        self
+    }
+
+    /// Set whether to record metrics for every command sent by the session.
+    pub fn collect_metrics(mut self, collect_metrics: bool) -> Self {
+        self.collect_metrics = collect_metrics;
+        self
+    }
+
+    /// Add a listener to be notified of commands and element interactions.
+    ///
+    /// Listeners are called in the order they were added.
+    pub fn event_listener(mut self, listener: Arc<dyn EventListener>) -> Self {
+        self.event_listeners.push(listener);
+        self
+    }
+
+    /// Set whether the context of errors includes the URL of the page when the command
+    /// failed (the default is false).
+    ///
+    /// This sends an extra `GetCurrentUrl` command for every failed command.
+    pub fn error_url_context(mut self, error_url_context: bool) -> Self {
+        self.error_url_context = error_url_context;
+        self
+    }
+
+    /// Set the directory used to save artifacts whenever a command fails.
+    ///
+    /// Errors that are routinely retried (such as `NoSuchElement`, `ElementNotInteractable`
+    /// or `JavascriptError`) and timeouts do not save artifacts, unless the test then panics
+    /// while a `FailureArtifactsGuard` is alive.
+    ///
+    /// Artifacts for each test are saved in a subdirectory named after the test.
+    pub fn failure_artifacts_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.failure_artifacts_dir = Some(dir.into());
+        self
+    }
+
+    /// Set what to do when an element becomes stale.
+    pub fn stale_element_policy(mut self, policy: StaleElementPolicy) -> Self {
+        self.stale_element_policy = policy;
+        self
+    }
+
+    /// Set whether element queries check their built-in filters with a single script.
+    ///
+    /// See `ElementQuery::batch_filters()` for details.
+    pub fn batch_query_filters(mut self, batch: bool) -> Self {
+        self.batch_query_filters = batch;
+        self
+    }
+
+    /// Set whether element queries that find nothing collect a `QueryDiagnostics` report.
+    ///
+    /// See `ElementQuery::diagnostics()` for details.
+    pub fn query_diagnostics(mut self, diagnostics: bool) -> Self {
+        self.query_diagnostics = diagnostics;
+        self
+    }
+
+    /// Set whether selectors are checked via `By::validate()` before each element search
+    /// (the default is false).
+    ///
+    /// Disable this to send selectors to the browser unchanged, for example if the local
+    /// check rejects a selector that the browser supports.
+    pub fn validate_selectors(mut self, validate: bool) -> Self {
+        self.validate_selectors = validate;
+        self
+    }
+
     /// Build `WebDriverConfig` using builder options.
     pub fn build(self) -> WebDriverResult<WebDriverConfig> {
         Ok(WebDriverConfig {
@@ -126,6 +293,17 @@ impl WebDriverConfigBuilder {
             poller: self.poller.unwrap_or_else(|| Arc::new(ElementPollerWithTimeout::default())),
             user_agent: self.user_agent.transpose()?.unwrap_or(WebDriverConfig::DEFAULT_USER_AGENT),
             reqwest_timeout: self.reqwest_timeout,
+            command_timeout: self.command_timeout,
+            cancellation_token: self.cancellation_token,
+            trace_propagator: self.trace_propagator,
+            collect_metrics: self.collect_metrics,
+            event_listeners: self.event_listeners,
+            error_url_context: self.error_url_context,
+            failure_artifacts_dir: self.failure_artifacts_dir,
+            stale_element_policy: self.stale_element_policy,
+            batch_query_filters: self.batch_query_filters,
+            query_diagnostics: self.query_diagnostics,
+            validate_selectors: self.validate_selectors,
         })
     }
 }
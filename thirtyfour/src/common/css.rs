@@ -0,0 +1,265 @@
+use crate::By;
+use std::fmt::{Display, Formatter, Write};
+
+/// Escape the specified value for use as a CSS identifier, such as a tag, class or id.
+///
+/// This follows the same rules as `CSS.escape()` in the browser.
+pub fn escape_ident(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    if value == "-" {
+        return r"\-".to_string();
+    }
+    for (i, c) in value.chars().enumerate() {
+        let leading_digit = c.is_ascii_digit() && (i == 0 || (i == 1 && value.starts_with('-')));
+        match c {
+            '\0' => escaped.push('\u{FFFD}'),
+            c if c.is_control() || leading_digit => {
+                let _ = write!(escaped, "\\{:x} ", c as u32);
+            }
+            c if !c.is_ascii() || c.is_ascii_alphanumeric() || c == '-' || c == '_' => {
+                escaped.push(c)
+            }
+            c => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+        }
+    }
+    escaped
+}
+
+/// Quote the specified value as a CSS string, escaping any quotes and backslashes.
+pub fn quote_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\{:x} ", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Start a selector matching elements with the specified tag name.
+pub fn tag(name: &str) -> CssSelector {
+    CssSelector::new(escape_ident(name))
+}
+
+/// Start a selector matching any element.
+pub fn any() -> CssSelector {
+    CssSelector::new(String::new())
+}
+
+/// Start a selector matching the element with the specified id.
+pub fn id(id: &str) -> CssSelector {
+    any().id(id)
+}
+
+/// Start a selector matching elements with the specified class.
+pub fn class(name: &str) -> CssSelector {
+    any().class(name)
+}
+
+/// A CSS selector built from escaped parts, so that values containing quotes or other
+/// special characters cannot break the selector.
+///
+/// Each method adds a condition to the rightmost element in the selector, and the
+/// combinator methods (such as `descendant()`) add another element to the right.
+/// Convert it into a `By` via `By::from()` or `.into()`.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use thirtyfour::css;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let selector = css::tag("form")
+///     .attr_eq("name", r#"say "hi""#)
+///     .descendant(css::tag("button").class("primary").attr_eq("type", "submit"));
+/// assert_eq!(
+///     selector.to_string(),
+///     r#"form[name="say \"hi\""] button.primary[type="submit"]"#
+/// );
+/// let elem = driver.find(selector.into()).await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssSelector {
+    /// Each compound selector, along with the combinator before it.
+    /// An empty compound selector matches any element.
+    parts: Vec<(&'static str, String)>,
+}
+
+impl CssSelector {
+    fn new(compound: String) -> Self {
+        Self {
+            parts: vec![("", compound)],
+        }
+    }
+
+    fn push(mut self, part: impl Display) -> Self {
+        if let Some((_, compound)) = self.parts.last_mut() {
+            let _ = write!(compound, "{part}");
+        }
+        self
+    }
+
+    fn combine(mut self, combinator: &'static str, other: CssSelector) -> Self {
+        let mut parts = other.parts.into_iter();
+        if let Some((_, compound)) = parts.next() {
+            self.parts.push((combinator, compound));
+        }
+        self.parts.extend(parts);
+        self
+    }
+
+    /// Require the element to have the specified id.
+    pub fn id(self, id: &str) -> Self {
+        self.push(format_args!("#{}", escape_ident(id)))
+    }
+
+    /// Require the element to have the specified class.
+    pub fn class(self, name: &str) -> Self {
+        self.push(format_args!(".{}", escape_ident(name)))
+    }
+
+    /// Require the element to have the specified attribute.
+    pub fn attr(self, name: &str) -> Self {
+        self.push(format_args!("[{}]", escape_ident(name)))
+    }
+
+    /// Require the specified attribute to equal the specified value.
+    pub fn attr_eq(self, name: &str, value: &str) -> Self {
+        self.attr_op(name, "=", value)
+    }
+
+    /// Require the specified attribute to contain the specified value.
+    pub fn attr_contains(self, name: &str, value: &str) -> Self {
+        self.attr_op(name, "*=", value)
+    }
+
+    /// Require the specified attribute to start with the specified value.
+    pub fn attr_starts_with(self, name: &str, value: &str) -> Self {
+        self.attr_op(name, "^=", value)
+    }
+
+    /// Require the specified attribute to end with the specified value.
+    pub fn attr_ends_with(self, name: &str, value: &str) -> Self {
+        self.attr_op(name, "$=", value)
+    }
+
+    /// Require the specified attribute to contain the specified value as a
+    /// whitespace-separated word.
+    pub fn attr_word(self, name: &str, value: &str) -> Self {
+        self.attr_op(name, "~=", value)
+    }
+
+    fn attr_op(self, name: &str, op: &str, value: &str) -> Self {
+        self.push(format_args!("[{}{op}{}]", escape_ident(name), quote_string(value)))
+    }
+
+    /// Require the element to be the nth child of its parent, starting from 1.
+    pub fn nth_child(self, n: usize) -> Self {
+        self.push(format_args!(":nth-child({n})"))
+    }
+
+    /// Require the element to be the nth element of its type within its parent,
+    /// starting from 1.
+    pub fn nth_of_type(self, n: usize) -> Self {
+        self.push(format_args!(":nth-of-type({n})"))
+    }
+
+    /// Require the element not to match the specified selector.
+    pub fn not(self, other: CssSelector) -> Self {
+        self.push(format_args!(":not({other})"))
+    }
+
+    /// Require the element to contain an element matching the specified selector.
+    pub fn has(self, other: CssSelector) -> Self {
+        self.push(format_args!(":has({other})"))
+    }
+
+    /// Add a pseudo-class, such as `checked` or `first-child`.
+    ///
+    /// The name is not escaped. It is checked when the selector is used, along with the
+    /// rest of the selector (see `By::validate()`).
+    pub fn pseudo(self, name: &str) -> Self {
+        self.push(format_args!(":{name}"))
+    }
+
+    /// Match elements matching `other` that are descendants of this element.
+    pub fn descendant(self, other: CssSelector) -> Self {
+        self.combine(" ", other)
+    }
+
+    /// Match elements matching `other` that are direct children of this element.
+    pub fn child(self, other: CssSelector) -> Self {
+        self.combine(" > ", other)
+    }
+
+    /// Match elements matching `other` that immediately follow this element.
+    pub fn next_sibling(self, other: CssSelector) -> Self {
+        self.combine(" + ", other)
+    }
+
+    /// Match elements matching `other` that follow this element.
+    pub fn subsequent_sibling(self, other: CssSelector) -> Self {
+        self.combine(" ~ ", other)
+    }
+}
+
+impl Display for CssSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (combinator, compound) in &self.parts {
+            f.write_str(combinator)?;
+            f.write_str(if compound.is_empty() {
+                "*"
+            } else {
+                compound
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl From<CssSelector> for By {
+    fn from(selector: CssSelector) -> Self {
+        By::Css(selector.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css() {
+        let selector = tag("form")
+            .attr_eq("name", r#"a "quoted" \ value"#)
+            .child(any().class("btn primary").id("1st"))
+            .next_sibling(id("x").not(class("hidden")).pseudo("checked"));
+        assert_eq!(
+            selector.to_string(),
+            r#"form[name="a \"quoted\" \\ value"] > .btn\ primary#\31 st + #x:not(.hidden):checked"#
+        );
+        assert!(By::from(selector).validate().is_ok());
+        assert_eq!(any().descendant(any()).to_string(), "* *");
+        assert_eq!(escape_ident("-"), r"\-");
+        assert_eq!(escape_ident("-2x"), r"-\32 x");
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::Debug;
+
+use crate::error::WebDriverError;
+use crate::session::handle::SessionHandle;
+use crate::session::http::CmdResponse;
+use crate::{By, RequestData, TypingData, WebElement};
+
+/// Trait used to observe commands sent by a `WebDriver` and the elements found via it.
+///
+/// This is similar to the `EventFiringDecorator` in Selenium for Java, and can be used
+/// for things like step logging, reporting or highlighting elements before they are clicked.
+///
+/// Register a listener via `WebDriverConfigBuilder::event_listener()`. All methods do
+/// nothing by default, so you only need to implement the ones you are interested in.
+///
+/// **NOTE:** Commands sent from within `before_command()`, `after_command()` or `on_error()`
+///           will trigger those methods again. The other methods can send commands freely.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use thirtyfour::common::config::WebDriverConfig;
+/// use thirtyfour::common::events::EventListener;
+/// use std::sync::Arc;
+///
+/// #[derive(Debug)]
+/// struct Highlighter;
+///
+/// #[async_trait::async_trait]
+/// impl EventListener for Highlighter {
+///     async fn before_click(&self, elem: &WebElement) {
+///         let _ = elem
+///             .handle
+///             .execute(r#"arguments[0].style.outline = "2px solid red";"#, vec![elem.to_json().unwrap()])
+///             .await;
+///     }
+/// }
+///
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// let caps = DesiredCapabilities::chrome();
+/// let config = WebDriverConfig::builder().event_listener(Arc::new(Highlighter)).build()?;
+/// let driver = WebDriver::new_with_config("http://localhost:4444", caps, config).await?;
+/// driver.goto("https://www.rust-lang.org").await?;
+/// driver.find(By::Css("a")).await?.click().await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+#[async_trait::async_trait]
+pub trait EventListener: Debug + Send + Sync {
+    /// Called before every command is sent to the webdriver server.
+    async fn before_command(&self, _command: &str, _request: &RequestData) {}
+
+    /// Called after every command that completed successfully.
+    async fn after_command(&self, _command: &str, _request: &RequestData, _response: &CmdResponse) {
+    }
+
+    /// Called after every command that returned an error.
+    async fn on_error(&self, _command: &str, _request: &RequestData, _error: &WebDriverError) {}
+
+    /// Called before navigating to the specified URL.
+    async fn before_navigate(&self, _handle: &SessionHandle, _url: &str) {}
+
+    /// Called after navigating to the specified URL.
+    async fn after_navigate(&self, _handle: &SessionHandle, _url: &str) {}
+
+    /// Called after searching for elements, with the elements that were found.
+    ///
+    /// Element queries call this once per poll iteration.
+    async fn on_find(&self, _by: &By, _elements: &[WebElement]) {}
+
+    /// Called before clicking the specified element.
+    async fn before_click(&self, _elem: &WebElement) {}
+
+    /// Called after clicking the specified element.
+    async fn after_click(&self, _elem: &WebElement) {}
+
+    /// Called before sending keys to the specified element.
+    async fn before_send_keys(&self, _elem: &WebElement, _keys: &TypingData) {}
+
+    /// Called after sending keys to the specified element.
+    async fn after_send_keys(&self, _elem: &WebElement, _keys: &TypingData) {}
+
+    /// Called before clearing the specified element.
+    async fn before_clear(&self, _elem: &WebElement) {}
+
+    /// Called after clearing the specified element.
+    async fn after_clear(&self, _elem: &WebElement) {}
+}
@@ -0,0 +1,256 @@
+use crate::error::{invalid_selector, WebDriverError, WebDriverErrorInner, WebDriverResult};
+use crate::By;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+/// The repository used by `By::Locator()`.
+static GLOBAL_REPOSITORY: RwLock<Option<Arc<LocatorRepository>>> = RwLock::new(None);
+
+/// A set of named locators, usually loaded from a file that is maintained separately from
+/// the code, so that the selectors can be changed without recompiling.
+///
+/// Locator files are JSON (or TOML, with the `toml` feature) objects whose values are
+/// locator strings in the syntax accepted by `By::from_str()`, such as `css=button.primary`.
+/// Nested objects are flattened, with their keys joined by `.`:
+///
+/// ```json
+/// {
+///     "login": {
+///         "username": "id=username",
+///         "submit": "xpath=//form//button[@type='submit']"
+///     }
+/// }
+/// ```
+///
+/// Every locator is parsed and checked via `By::validate()` when the file is loaded.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use thirtyfour::LocatorRepository;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let locators = LocatorRepository::load("locators.json")?;
+/// let elem = driver.find(locators.by("login.username")?).await?;
+///
+/// // Make the locators available to `By::Locator()` and `#[by(locator = "...")]`.
+/// LocatorRepository::set_global(locators);
+/// let elem = driver.find(By::Locator("login.submit")?).await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LocatorRepository {
+    locators: HashMap<String, By>,
+}
+
+impl LocatorRepository {
+    /// Create a new, empty `LocatorRepository`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load locators from the specified file.
+    ///
+    /// Files ending in `.toml` are parsed as TOML (which requires the `toml` feature), and
+    /// all other files are parsed as JSON.
+    pub fn load(path: impl AsRef<Path>) -> WebDriverResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|x| x.to_str()) {
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&contents),
+            #[cfg(not(feature = "toml"))]
+            Some("toml") => Err(WebDriverError::ParseError(format!(
+                "cannot load {}: TOML locator files require the `toml` feature",
+                path.display()
+            ))),
+            _ => Self::from_json(&contents),
+        }
+    }
+
+    /// Parse locators from the specified JSON.
+    pub fn from_json(json: &str) -> WebDriverResult<Self> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| WebDriverError::Json(format!("invalid locator file: {e}")))?;
+        Self::from_value(value)
+    }
+
+    /// Parse locators from the specified TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> WebDriverResult<Self> {
+        let value: Value = toml::from_str(toml)
+            .map_err(|e| WebDriverError::ParseError(format!("invalid locator file: {e}")))?;
+        Self::from_value(value)
+    }
+
+    fn from_value(value: Value) -> WebDriverResult<Self> {
+        let mut repository = Self::new();
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    repository.add_value(key, value)?;
+                }
+                Ok(repository)
+            }
+            _ => Err(WebDriverError::Json("a locator file must contain an object".to_string())),
+        }
+    }
+
+    fn add_value(&mut self, key: String, value: Value) -> WebDriverResult<()> {
+        match value {
+            Value::String(s) => {
+                let by: By = s.parse().map_err(|e: WebDriverError| match e.into_inner() {
+                    WebDriverErrorInner::InvalidSelector(info) => {
+                        invalid_selector(format!("locator {key:?}: {}", info.value.message))
+                    }
+                    e => e.into(),
+                })?;
+                self.insert(key, by);
+            }
+            Value::Object(map) => {
+                for (child, value) in map {
+                    self.add_value(format!("{key}.{child}"), value)?;
+                }
+            }
+            _ => {
+                return Err(WebDriverError::Json(format!(
+                    "locator {key:?} must be a string or an object"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the specified locator, replacing any existing locator with the same key.
+    pub fn insert(&mut self, key: impl Into<String>, by: By) {
+        self.locators.insert(key.into(), by);
+    }
+
+    /// Add all locators from the specified repository, replacing any existing locators with
+    /// the same keys.
+    pub fn extend(&mut self, other: LocatorRepository) {
+        self.locators.extend(other.locators);
+    }
+
+    /// Get the locator with the specified key, if any.
+    pub fn get(&self, key: &str) -> Option<&By> {
+        self.locators.get(key)
+    }
+
+    /// Get the locator with the specified key.
+    ///
+    /// Returns `WebDriverError::NotFound` if there is no such locator.
+    pub fn by(&self, key: &str) -> WebDriverResult<By> {
+        self.get(key).cloned().ok_or_else(|| {
+            WebDriverError::NotFound(
+                format!("locator {key}"),
+                "no such key in the locator repository".to_string(),
+            )
+        })
+    }
+
+    /// The number of locators.
+    pub fn len(&self) -> usize {
+        self.locators.len()
+    }
+
+    /// Returns true if there are no locators.
+    pub fn is_empty(&self) -> bool {
+        self.locators.is_empty()
+    }
+
+    /// Set the repository used by `By::Locator()` (and therefore by the
+    /// `#[by(locator = "...")]` component attribute).
+    ///
+    /// Components look up their locators each time an element is resolved, so a missing
+    /// locator is reported as an `InvalidSelector` error by `ElementResolver::resolve()`.
+    pub fn set_global(repository: LocatorRepository) {
+        let mut global = GLOBAL_REPOSITORY.write().unwrap_or_else(|e| e.into_inner());
+        *global = Some(Arc::new(repository));
+    }
+
+    /// Get the repository used by `By::Locator()`. This is empty unless it was set via
+    /// `LocatorRepository::set_global()`.
+    pub fn global() -> Arc<LocatorRepository> {
+        let global = GLOBAL_REPOSITORY.read().unwrap_or_else(|e| e.into_inner());
+        global.clone().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locators() {
+        for s in [
+            "css=button.primary",
+            "xpath=//a[@href='x=y']",
+            "id=save",
+            "text=Save changes",
+            "partial-text=save",
+            "role=button",
+            r#"role=button[name="Say \"hi\""]"#,
+            "deep-css=my-app >>> button",
+        ] {
+            let by: By = s.parse().unwrap();
+            assert_eq!(by.to_locator_string(), s);
+        }
+        let by: By = "testid=save".parse().unwrap();
+        assert_eq!(by.to_locator_string(), r#"css=[data-testid="save"]"#);
+
+        assert!("button".parse::<By>().is_err());
+        assert!("foo=button".parse::<By>().is_err());
+        assert!("css=button[".parse::<By>().is_err());
+        assert!("role=button[label=\"x\"]".parse::<By>().is_err());
+        assert!("id=".parse::<By>().is_err());
+        assert!("css= ".parse::<By>().is_err());
+
+        let by: By = " css = a ".parse().unwrap();
+        assert_eq!(by.to_locator_string(), "css=a");
+    }
+
+    #[test]
+    fn test_serde() {
+        let by: Vec<By> = serde_json::from_str(r#"["id=a", "xpath=//b"]"#).unwrap();
+        assert_eq!(serde_json::to_string(&by).unwrap(), r#"["id=a","xpath=//b"]"#);
+        assert!(serde_json::from_str::<By>(r#""xpath=//b[""#).is_err());
+    }
+
+    #[test]
+    fn test_repository() {
+        let repository = LocatorRepository::from_json(
+            r#"{"title": "css=h1", "login": {"username": "id=user", "form": {"submit": "css=button"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(repository.len(), 3);
+        assert_eq!(repository.by("login.username").unwrap().to_locator_string(), "id=user");
+        assert!(repository.get("login.form.submit").is_some());
+        assert!(repository.by("login").is_err());
+
+        let err = LocatorRepository::from_json(r#"{"login": {"username": "css=a["}}"#).unwrap_err();
+        assert!(err.to_string().contains("login.username"), "{err}");
+        assert!(LocatorRepository::from_json(r#"{"a": 1}"#).is_err());
+
+        let err = By::Locator("missing.key").unwrap_err();
+        assert!(matches!(*err, WebDriverErrorInner::InvalidSelector(_)), "{err}");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml() {
+        let repository =
+            LocatorRepository::from_toml("title = \"css=h1\"\n[login]\nusername = \"id=user\"\n")
+                .unwrap();
+        assert_eq!(repository.by("login.username").unwrap().to_locator_string(), "id=user");
+    }
+}
@@ -8,11 +8,21 @@ pub mod command;
 pub mod config;
 /// Cookie type.
 pub mod cookie;
+/// Builder for CSS selectors.
+pub mod css;
+/// Event listeners for observing commands.
+pub mod events;
 /// Types for working with keyboard input.
 pub mod keys;
+/// Named locators, loaded from files.
+pub mod locators;
 /// Types used with print commands.
 pub mod print;
 /// Type for request method and body.
 pub mod requestdata;
 /// Common types used within thirtyfour.
 pub mod types;
+/// Offline validation of CSS and XPath selectors.
+pub mod validate;
+/// Builder for XPath selectors.
+pub mod xpath;
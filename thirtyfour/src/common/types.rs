@@ -287,6 +287,13 @@ pub trait ElementQueryFn<T>: Send + Sync {
 
     /// the implementation of the query function
     fn call(&self, arg: WebElement) -> Self::Fut;
+
+    /// a human-readable description of the query function, used in error messages
+    ///
+    /// Closures have no description. Use `conditions::describe()` to add one.
+    fn description(&self) -> Option<String> {
+        None
+    }
 }
 
 impl<T, Fut, Fun> ElementQueryFn<T> for Fun
@@ -312,11 +319,26 @@ pub type DynElementQueryFn<T> = dyn ElementQueryFn<T, Fut = BoxFuture<'static, W
 /// a dynamically dispatched element predicate
 pub type DynElementPredicate = DynElementQueryFn<bool>;
 
+/// an ElementQueryFn that boxes the future returned by the wrapped function
+struct BoxedQueryFn<F>(F);
+
+impl<T, F: ElementQueryFn<T, Fut: 'static>> ElementQueryFn<T> for BoxedQueryFn<F> {
+    type Fut = BoxFuture<'static, WebDriverResult<T>>;
+
+    fn call(&self, arg: WebElement) -> Self::Fut {
+        self.0.call(arg).boxed()
+    }
+
+    fn description(&self) -> Option<String> {
+        self.0.description()
+    }
+}
+
 impl<T: 'static> DynElementQueryFn<T> {
     fn wrap<F: ElementQueryFn<T, Fut: 'static>>(
         fun: F,
     ) -> impl ElementQueryFn<T, Fut = BoxFuture<'static, WebDriverResult<T>>> {
-        move |arg: WebElement| fun.call(arg).boxed()
+        BoxedQueryFn(fun)
     }
 
     /// erases the type of ElementQueryFn, and dynamically dispatches it using a Box smart pointer
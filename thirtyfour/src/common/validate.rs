@@ -0,0 +1,1029 @@
+use std::fmt::{Display, Formatter};
+
+/// A syntax error found while validating a selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorSyntaxError {
+    /// The position of the error, in characters from the start of the selector.
+    pub position: usize,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl Display for SelectorSyntaxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SelectorSyntaxError {}
+
+type SyntaxResult<T = ()> = Result<T, SelectorSyntaxError>;
+
+/// Pseudo-classes that are only supported by jQuery, and are rejected by browsers.
+///
+/// These are not treated as errors, because pseudo-classes are not checked against a list
+/// of supported names, but a warning is logged when a selector uses one.
+const JQUERY_PSEUDO_CLASSES: &[&str] = &[
+    "animated", "button", "checkbox", "contains", "eq", "even", "file", "first", "gt", "header",
+    "hidden", "image", "input", "last", "lt", "odd", "parent", "password", "radio", "reset",
+    "selected", "submit", "text", "visible",
+];
+
+/// The axes supported by XPath 1.0.
+const XPATH_AXES: &[&str] = &[
+    "ancestor",
+    "ancestor-or-self",
+    "attribute",
+    "child",
+    "descendant",
+    "descendant-or-self",
+    "following",
+    "following-sibling",
+    "namespace",
+    "parent",
+    "preceding",
+    "preceding-sibling",
+    "self",
+];
+
+/// The node types supported by XPath 1.0.
+const XPATH_NODE_TYPES: &[&str] = &["comment", "node", "processing-instruction", "text"];
+
+/// The functions in the XPath 1.0 core function library.
+const XPATH_FUNCTIONS: &[&str] = &[
+    "boolean",
+    "ceiling",
+    "concat",
+    "contains",
+    "count",
+    "false",
+    "floor",
+    "id",
+    "lang",
+    "last",
+    "local-name",
+    "name",
+    "namespace-uri",
+    "normalize-space",
+    "not",
+    "number",
+    "position",
+    "round",
+    "starts-with",
+    "string",
+    "string-length",
+    "substring",
+    "substring-after",
+    "substring-before",
+    "sum",
+    "translate",
+    "true",
+];
+
+/// Check the syntax of a CSS selector list, as accepted by `document.querySelectorAll()`.
+///
+/// Only syntax errors are rejected. Pseudo-classes and pseudo-elements are not checked
+/// against a list of known names, because browsers add new ones regularly, but a warning is
+/// logged for pseudo-classes that are only supported by jQuery, such as `:contains()`.
+///
+/// # Example:
+/// ```
+/// use thirtyfour::common::validate::validate_css;
+///
+/// assert!(validate_css("form > input[type='text']:not(:disabled)").is_ok());
+///
+/// let err = validate_css("div[data-id=").unwrap_err();
+/// assert_eq!(err.position, 12);
+/// ```
+pub fn validate_css(css: &str) -> Result<(), SelectorSyntaxError> {
+    let mut parser = CssParser {
+        cursor: Cursor::new(css),
+    };
+    parser.selector_list(false)?;
+    match parser.cursor.peek() {
+        None => Ok(()),
+        Some(c) => Err(parser.cursor.error(format!("unexpected '{c}'"))),
+    }
+}
+
+/// Check the syntax of an XPath 1.0 expression, as accepted by `document.evaluate()`.
+///
+/// Functions that are not in the XPath 1.0 core function library, such as `matches()` or
+/// `lower-case()` from XPath 2.0, are accepted with a warning, because browsers do not support
+/// them but other drivers (such as Appium) may.
+///
+/// # Example:
+/// ```
+/// use thirtyfour::common::validate::validate_xpath;
+///
+/// assert!(validate_xpath("//button[normalize-space() = 'Save']").is_ok());
+///
+/// let err = validate_xpath("//button[@id='save'").unwrap_err();
+/// assert_eq!(err.position, 19);
+/// ```
+pub fn validate_xpath(xpath: &str) -> Result<(), SelectorSyntaxError> {
+    let tokens = xpath_tokens(xpath)?;
+    let mut parser = XPathParser {
+        tokens,
+        index: 0,
+        source: xpath.chars().collect(),
+    };
+    parser.expr()?;
+    match parser.tokens.get(parser.index) {
+        None => Ok(()),
+        Some(_) => Err(parser.error(format!("unexpected {}", parser.describe_next()))),
+    }
+}
+
+/// A position within a selector.
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(s: &str) -> Self {
+        Self {
+            chars: s.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Skip any whitespace and comments, returning true if there was any whitespace.
+    fn skip_ws(&mut self) -> bool {
+        let mut whitespace = false;
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\n' | '\r' | '\x0C') => {
+                    self.pos += 1;
+                    whitespace = true;
+                }
+                Some('/') if self.peek_at(1) == Some('*') => self.skip_comment(),
+                _ => return whitespace,
+            }
+        }
+    }
+
+    /// Skip a `/* comment */`. An unterminated comment runs to the end of the selector.
+    fn skip_comment(&mut self) {
+        self.pos += 2;
+        while self.pos < self.chars.len() {
+            if self.peek() == Some('*') && self.peek_at(1) == Some('/') {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn text(&self, start: usize) -> String {
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn error(&self, message: impl Into<String>) -> SelectorSyntaxError {
+        error_at(self.pos, message)
+    }
+
+    fn expected(&self, what: &str) -> SelectorSyntaxError {
+        let found = match self.peek() {
+            Some(c) => format!("'{c}'"),
+            None => "end of selector".to_string(),
+        };
+        self.error(format!("expected {what}, found {found}"))
+    }
+}
+
+fn error_at(position: usize, message: impl Into<String>) -> SelectorSyntaxError {
+    SelectorSyntaxError {
+        position,
+        message: message.into(),
+    }
+}
+
+fn is_css_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_css_name_char(c: char) -> bool {
+    is_css_name_start(c) || c.is_ascii_digit() || c == '-'
+}
+
+/// A recursive descent parser for CSS selectors.
+struct CssParser {
+    cursor: Cursor,
+}
+
+impl CssParser {
+    /// Parse a comma-separated list of selectors. A relative selector may start with a
+    /// combinator, as in `:has(> img)`.
+    fn selector_list(&mut self, relative: bool) -> SyntaxResult {
+        loop {
+            self.complex(relative)?;
+            if !self.cursor.eat(',') {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parse compound selectors separated by combinators.
+    fn complex(&mut self, relative: bool) -> SyntaxResult {
+        self.cursor.skip_ws();
+        if relative && matches!(self.cursor.peek(), Some('>' | '+' | '~')) {
+            self.cursor.bump();
+            self.cursor.skip_ws();
+        }
+        self.compound()?;
+        loop {
+            let whitespace = self.cursor.skip_ws();
+            match self.cursor.peek() {
+                None | Some(',' | ')') => return Ok(()),
+                Some('>' | '+' | '~') => {
+                    self.cursor.bump();
+                    self.cursor.skip_ws();
+                    self.compound()?;
+                }
+                Some(_) if whitespace => self.compound()?,
+                Some(c) => return Err(self.cursor.error(format!("unexpected '{c}'"))),
+            }
+        }
+    }
+
+    /// Parse a sequence of simple selectors that are not separated by combinators.
+    fn compound(&mut self) -> SyntaxResult {
+        let start = self.cursor.pos;
+        self.type_selector()?;
+        loop {
+            match self.cursor.peek() {
+                Some('#') => {
+                    self.cursor.bump();
+                    if !self.name()? {
+                        return Err(self.cursor.expected("an id after '#'"));
+                    }
+                }
+                Some('.') => {
+                    self.cursor.bump();
+                    if !self.ident()? {
+                        return Err(self.cursor.expected("a class name after '.'"));
+                    }
+                }
+                Some('[') => self.attribute()?,
+                Some(':') => self.pseudo()?,
+                // The nesting selector, which matches the scoping root outside of nested
+                // style rules.
+                Some('&') => {
+                    self.cursor.bump();
+                }
+                Some('/') if self.cursor.peek_at(1) == Some('*') => self.cursor.skip_comment(),
+                _ => break,
+            }
+        }
+        if self.cursor.pos == start {
+            return Err(self.cursor.expected("a selector"));
+        }
+        Ok(())
+    }
+
+    /// Parse an optional element name or `*`, with an optional namespace prefix.
+    fn type_selector(&mut self) -> SyntaxResult {
+        // The name is optional, e.g. `|a` matches `a` elements without a namespace.
+        let _ = self.cursor.eat('*') || self.ident()?;
+        if self.cursor.peek() == Some('|') && self.cursor.peek_at(1) != Some('=') {
+            self.cursor.bump();
+            if !(self.cursor.eat('*') || self.ident()?) {
+                return Err(self.cursor.expected("an element name after '|'"));
+            }
+        }
+        Ok(())
+    }
+
+    fn attribute(&mut self) -> SyntaxResult {
+        self.cursor.bump();
+        self.cursor.skip_ws();
+        if self.cursor.peek() == Some('*') && self.cursor.peek_at(1) == Some('|') {
+            self.cursor.pos += 2;
+        } else if self.cursor.peek() == Some('|') {
+            self.cursor.bump();
+        }
+        if !self.ident()? {
+            return Err(self.cursor.expected("an attribute name"));
+        }
+        if self.cursor.peek() == Some('|') && self.cursor.peek_at(1) != Some('=') {
+            self.cursor.bump();
+            if !self.ident()? {
+                return Err(self.cursor.expected("an attribute name after '|'"));
+            }
+        }
+        self.cursor.skip_ws();
+        if self.cursor.eat(']') {
+            return Ok(());
+        }
+
+        match (self.cursor.peek(), self.cursor.peek_at(1)) {
+            (Some('='), _) => self.cursor.pos += 1,
+            (Some('~' | '|' | '^' | '$' | '*'), Some('=')) => self.cursor.pos += 2,
+            _ => return Err(self.cursor.expected("an attribute operator or ']'")),
+        }
+        self.cursor.skip_ws();
+        match self.cursor.peek() {
+            Some('"' | '\'') => self.string()?,
+            _ => {
+                if !self.ident()? {
+                    return Err(self.cursor.expected("an identifier or quoted attribute value"));
+                }
+            }
+        }
+        self.cursor.skip_ws();
+
+        let flag_start = self.cursor.pos;
+        if self.ident()? {
+            let flag = self.cursor.text(flag_start);
+            if !flag.eq_ignore_ascii_case("i") && !flag.eq_ignore_ascii_case("s") {
+                return Err(error_at(flag_start, format!("invalid attribute flag '{flag}'")));
+            }
+            self.cursor.skip_ws();
+        }
+        if !self.cursor.eat(']') {
+            return Err(self.cursor.expected("']'"));
+        }
+        Ok(())
+    }
+
+    fn pseudo(&mut self) -> SyntaxResult {
+        self.cursor.bump();
+        let element = self.cursor.eat(':');
+        let kind = match element {
+            true => "pseudo-element",
+            false => "pseudo-class",
+        };
+
+        let name_start = self.cursor.pos;
+        if !self.ident()? {
+            return Err(self.cursor.expected(&format!("a {kind} name")));
+        }
+        let name = self.cursor.text(name_start).to_ascii_lowercase();
+        let function = self.cursor.peek() == Some('(');
+        if !element && JQUERY_PSEUDO_CLASSES.contains(&name.as_str()) {
+            tracing::warn!(
+                "selector uses ':{name}', which is only supported by jQuery and will be \
+                 rejected by the browser"
+            );
+        }
+
+        if function {
+            self.cursor.bump();
+            match name.as_str() {
+                "not" => self.selector_list(false)?,
+                "has" => self.selector_list(true)?,
+                "host" | "host-context" | "slotted" => {
+                    self.cursor.skip_ws();
+                    self.compound()?;
+                    self.cursor.skip_ws();
+                }
+                // `:is()` and `:where()` take a forgiving selector list, so any invalid
+                // selectors within them are ignored rather than rejected.
+                _ => self.arguments()?,
+            }
+            if !self.cursor.eat(')') {
+                return Err(self.cursor.expected("')'"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Skip balanced function arguments, up to (but not including) the closing `)`.
+    fn arguments(&mut self) -> SyntaxResult {
+        let mut depth = 0;
+        loop {
+            match self.cursor.peek() {
+                None => return Err(self.cursor.expected("')'")),
+                Some(')') if depth == 0 => return Ok(()),
+                Some('(' | '[') => {
+                    depth += 1;
+                    self.cursor.bump();
+                }
+                Some(')' | ']') => {
+                    depth -= 1;
+                    self.cursor.bump();
+                }
+                Some('"' | '\'') => self.string()?,
+                Some('\\') => self.escape()?,
+                Some('/') if self.cursor.peek_at(1) == Some('*') => self.cursor.skip_comment(),
+                Some(_) => {
+                    self.cursor.bump();
+                }
+            }
+        }
+    }
+
+    /// Parse an identifier, returning false if there isn't one at the current position.
+    fn ident(&mut self) -> SyntaxResult<bool> {
+        match (self.cursor.peek(), self.cursor.peek_at(1)) {
+            (Some('-'), Some(c)) if c == '-' || c == '\\' || is_css_name_start(c) => {
+                self.cursor.bump();
+            }
+            (Some(c), _) if c == '\\' || is_css_name_start(c) => {}
+            _ => return Ok(false),
+        }
+        self.name()
+    }
+
+    /// Parse a sequence of name characters, returning false if there were none.
+    fn name(&mut self) -> SyntaxResult<bool> {
+        let start = self.cursor.pos;
+        loop {
+            match self.cursor.peek() {
+                Some('\\') => self.escape()?,
+                Some(c) if is_css_name_char(c) => {
+                    self.cursor.bump();
+                }
+                _ => return Ok(self.cursor.pos > start),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> SyntaxResult {
+        let start = self.cursor.pos;
+        self.cursor.bump();
+        match self.cursor.peek() {
+            None | Some('\n' | '\r' | '\x0C') => Err(error_at(start, "invalid escape")),
+            Some(c) if c.is_ascii_hexdigit() => {
+                let mut digits = 0;
+                while digits < 6 && self.cursor.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                    self.cursor.bump();
+                    digits += 1;
+                }
+                if matches!(self.cursor.peek(), Some(' ' | '\t' | '\n')) {
+                    self.cursor.bump();
+                }
+                Ok(())
+            }
+            Some(_) => {
+                self.cursor.bump();
+                Ok(())
+            }
+        }
+    }
+
+    fn string(&mut self) -> SyntaxResult {
+        let start = self.cursor.pos;
+        let quote = self.cursor.bump();
+        loop {
+            match self.cursor.bump() {
+                None => return Err(error_at(start, "unterminated string")),
+                Some('\\') => {
+                    if self.cursor.bump().is_none() {
+                        return Err(error_at(start, "unterminated string"));
+                    }
+                }
+                Some('\n' | '\r' | '\x0C') => {
+                    return Err(error_at(self.cursor.pos - 1, "unescaped newline in string"))
+                }
+                c if c == quote => return Ok(()),
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+/// An XPath token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum XPathToken {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    Literal,
+    Number,
+    Variable,
+    NameTest,
+    NodeType(String),
+    Function,
+    Axis,
+}
+
+impl XPathToken {
+    /// Whether a `*` or name after this token is an operator rather than a name test.
+    fn precedes_operator(&self) -> bool {
+        use XPathToken::*;
+        !matches!(
+            self,
+            At | ColonColon
+                | LParen
+                | LBracket
+                | Comma
+                | Slash
+                | DoubleSlash
+                | Pipe
+                | Plus
+                | Minus
+                | Eq
+                | Neq
+                | Lt
+                | Le
+                | Gt
+                | Ge
+                | Multiply
+                | And
+                | Or
+                | Mod
+                | Div
+        )
+    }
+}
+
+fn is_xml_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_xml_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '\u{B7}')
+}
+
+/// Split an XPath expression into tokens, along with their start and end positions.
+fn xpath_tokens(xpath: &str) -> SyntaxResult<Vec<(XPathToken, usize, usize)>> {
+    use XPathToken::*;
+
+    fn ncname(cursor: &mut Cursor) -> bool {
+        if !cursor.peek().is_some_and(is_xml_name_start) {
+            return false;
+        }
+        while cursor.peek().is_some_and(is_xml_name_char) {
+            cursor.bump();
+        }
+        true
+    }
+
+    let mut cursor = Cursor::new(xpath);
+    let mut tokens: Vec<(XPathToken, usize, usize)> = Vec::new();
+    loop {
+        while matches!(cursor.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            cursor.bump();
+        }
+        let start = cursor.pos;
+        let Some(c) = cursor.bump() else {
+            break;
+        };
+        let operator = tokens.last().is_some_and(|(t, _, _)| t.precedes_operator());
+        let token = match c {
+            '(' => LParen,
+            ')' => RParen,
+            '[' => LBracket,
+            ']' => RBracket,
+            '@' => At,
+            ',' => Comma,
+            '|' => Pipe,
+            '+' => Plus,
+            '-' => Minus,
+            '=' => Eq,
+            '!' if cursor.eat('=') => Neq,
+            '!' => return Err(error_at(start, "expected '=' after '!'")),
+            '<' if cursor.eat('=') => Le,
+            '<' => Lt,
+            '>' if cursor.eat('=') => Ge,
+            '>' => Gt,
+            '/' if cursor.eat('/') => DoubleSlash,
+            '/' => Slash,
+            ':' if cursor.eat(':') => ColonColon,
+            '.' if cursor.eat('.') => DotDot,
+            '.' if !cursor.peek().is_some_and(|c| c.is_ascii_digit()) => Dot,
+            '.' | '0'..='9' => {
+                while cursor.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    cursor.bump();
+                }
+                if c != '.' && cursor.eat('.') {
+                    while cursor.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        cursor.bump();
+                    }
+                }
+                Number
+            }
+            '"' | '\'' => {
+                while cursor.peek().is_some_and(|q| q != c) {
+                    cursor.bump();
+                }
+                if !cursor.eat(c) {
+                    return Err(error_at(start, "unterminated string"));
+                }
+                Literal
+            }
+            '$' => {
+                if !ncname(&mut cursor) {
+                    return Err(cursor.expected("a variable name after '$'"));
+                }
+                if cursor.peek() == Some(':') && cursor.peek_at(1) != Some(':') {
+                    cursor.bump();
+                    if !ncname(&mut cursor) {
+                        return Err(cursor.expected("a variable name after ':'"));
+                    }
+                }
+                Variable
+            }
+            '*' if operator => Multiply,
+            '*' => NameTest,
+            c if is_xml_name_start(c) => {
+                cursor.pos = start;
+                ncname(&mut cursor);
+                let name = cursor.text(start);
+                if operator {
+                    match name.as_str() {
+                        "and" => And,
+                        "or" => Or,
+                        "mod" => Mod,
+                        "div" => Div,
+                        _ => {
+                            return Err(error_at(
+                                start,
+                                format!("expected an operator, found '{name}'"),
+                            ))
+                        }
+                    }
+                } else if cursor.peek() == Some(':') && cursor.peek_at(1) == Some(':') {
+                    if !XPATH_AXES.contains(&name.as_str()) {
+                        return Err(error_at(start, format!("unknown axis '{name}'")));
+                    }
+                    Axis
+                } else {
+                    let prefixed = cursor.peek() == Some(':');
+                    if prefixed {
+                        cursor.bump();
+                        if cursor.eat('*') {
+                            tokens.push((NameTest, start, cursor.pos));
+                            continue;
+                        }
+                        if !ncname(&mut cursor) {
+                            return Err(cursor.expected("a local name after ':'"));
+                        }
+                    }
+
+                    let end = cursor.pos;
+                    while matches!(cursor.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+                        cursor.bump();
+                    }
+                    let call = cursor.peek() == Some('(');
+                    cursor.pos = end;
+                    match (call, prefixed) {
+                        (true, false) if XPATH_NODE_TYPES.contains(&name.as_str()) => {
+                            NodeType(name)
+                        }
+                        (true, false) if !XPATH_FUNCTIONS.contains(&name.as_str()) => {
+                            tracing::warn!(
+                                "XPath uses '{name}()', which is not an XPath 1.0 function and \
+                                 will be rejected by the browser"
+                            );
+                            Function
+                        }
+                        (true, _) => Function,
+                        (false, _) => NameTest,
+                    }
+                }
+            }
+            c => return Err(error_at(start, format!("unexpected '{c}'"))),
+        };
+        tokens.push((token, start, cursor.pos));
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser for XPath 1.0 expressions.
+struct XPathParser {
+    tokens: Vec<(XPathToken, usize, usize)>,
+    index: usize,
+    source: Vec<char>,
+}
+
+impl XPathParser {
+    fn peek(&self) -> Option<&XPathToken> {
+        self.tokens.get(self.index).map(|(t, _, _)| t)
+    }
+
+    fn eat(&mut self, token: XPathToken) -> bool {
+        let found = self.peek() == Some(&token);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn eat_any(&mut self, tokens: &[XPathToken]) -> bool {
+        tokens.iter().any(|t| self.eat(t.clone()))
+    }
+
+    fn describe_next(&self) -> String {
+        match self.tokens.get(self.index) {
+            Some((_, start, end)) => {
+                format!("'{}'", self.source[*start..*end].iter().collect::<String>())
+            }
+            None => "end of expression".to_string(),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> SelectorSyntaxError {
+        let position = match self.tokens.get(self.index) {
+            Some((_, start, _)) => *start,
+            None => self.source.len(),
+        };
+        error_at(position, message)
+    }
+
+    fn expect(&mut self, token: XPathToken, what: &str) -> SyntaxResult {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected {what}, found {}", self.describe_next()))),
+        }
+    }
+
+    fn expr(&mut self) -> SyntaxResult {
+        use XPathToken::*;
+
+        // Binary operators, from the lowest precedence to the highest.
+        const LEVELS: &[&[XPathToken]] =
+            &[&[Or], &[And], &[Eq, Neq], &[Lt, Le, Gt, Ge], &[Plus, Minus], &[Multiply, Mod, Div]];
+
+        fn binary(parser: &mut XPathParser, level: usize) -> SyntaxResult {
+            let Some(operators) = LEVELS.get(level) else {
+                return parser.unary();
+            };
+            binary(parser, level + 1)?;
+            while parser.eat_any(operators) {
+                binary(parser, level + 1)?;
+            }
+            Ok(())
+        }
+
+        binary(self, 0)
+    }
+
+    fn unary(&mut self) -> SyntaxResult {
+        while self.eat(XPathToken::Minus) {}
+        self.path()?;
+        while self.eat(XPathToken::Pipe) {
+            self.path()?;
+        }
+        Ok(())
+    }
+
+    fn path(&mut self) -> SyntaxResult {
+        use XPathToken::*;
+        match self.peek() {
+            Some(Variable | LParen | Literal | Number | Function) => {
+                self.primary()?;
+                self.predicates()?;
+                if self.eat_any(&[Slash, DoubleSlash]) {
+                    self.relative_path()?;
+                }
+                Ok(())
+            }
+            Some(Slash) => {
+                self.index += 1;
+                match self.peek() {
+                    Some(Dot | DotDot | At | Axis | NameTest | NodeType(_)) => self.relative_path(),
+                    _ => Ok(()),
+                }
+            }
+            Some(DoubleSlash) => {
+                self.index += 1;
+                self.relative_path()
+            }
+            _ => self.relative_path(),
+        }
+    }
+
+    fn primary(&mut self) -> SyntaxResult {
+        use XPathToken::*;
+        if self.eat(LParen) {
+            self.expr()?;
+            return self.expect(RParen, "')'");
+        }
+        if self.eat(Function) {
+            self.expect(LParen, "'('")?;
+            if !self.eat(RParen) {
+                self.expr()?;
+                while self.eat(Comma) {
+                    self.expr()?;
+                }
+                self.expect(RParen, "',' or ')'")?;
+            }
+            return Ok(());
+        }
+        self.index += 1;
+        Ok(())
+    }
+
+    fn relative_path(&mut self) -> SyntaxResult {
+        self.step()?;
+        while self.eat_any(&[XPathToken::Slash, XPathToken::DoubleSlash]) {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    fn step(&mut self) -> SyntaxResult {
+        use XPathToken::*;
+        if self.eat_any(&[Dot, DotDot]) {
+            return Ok(());
+        }
+        if !self.eat(At) && self.eat(Axis) {
+            self.expect(ColonColon, "'::'")?;
+        }
+        match self.peek().cloned() {
+            Some(NameTest) => self.index += 1,
+            Some(NodeType(name)) => {
+                self.index += 1;
+                self.expect(LParen, "'('")?;
+                if name == "processing-instruction" {
+                    self.eat(Literal);
+                }
+                self.expect(RParen, "')'")?;
+            }
+            _ => {
+                return Err(
+                    self.error(format!("expected a location step, found {}", self.describe_next()))
+                )
+            }
+        }
+        self.predicates()
+    }
+
+    fn predicates(&mut self) -> SyntaxResult {
+        while self.eat(XPathToken::LBracket) {
+            self.expr()?;
+            self.expect(XPathToken::RBracket, "']'")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::WebDriverErrorInner;
+    use crate::By;
+
+    #[test]
+    fn test_valid_css() {
+        for css in [
+            "div",
+            "*",
+            "#main .item > a[href^='https'] + span ~ p",
+            "ul li:nth-child(2n + 1):not(.hidden, [aria-hidden=true])",
+            "svg|circle, *|*, |a",
+            "input[type=\"checkbox\" i]:checked::after",
+            "a:is(:unknown, b) section:has(> img, + p)",
+            ":host(.dark) ::slotted(span)",
+            "div:-webkit-autofill::-moz-selection",
+            r"#\31 23 .a\:b [data-x\=y]",
+            "  div  ,  span  ",
+            "a:contains('x'), a:visible, a::unknown, a:popover-open",
+            ":state(checked) ::part(label):focus-visible",
+            "div /* the list */ > li/* items */.item:not(/* ) */ .x)",
+            "& > li, .parent &.active, :is(&) span",
+            "div /* unterminated",
+        ] {
+            assert_eq!(validate_css(css), Ok(()), "{css}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_css() {
+        for (css, position, message) in [
+            ("", 0, "expected a selector, found end of selector"),
+            ("div,", 4, "expected a selector, found end of selector"),
+            ("div >", 5, "expected a selector, found end of selector"),
+            ("a > > b", 4, "expected a selector, found '>'"),
+            ("div[data-id=", 12, "expected an identifier or quoted attribute value"),
+            ("div[data-id=1]", 12, "expected an identifier or quoted attribute value"),
+            ("div[title='x]", 10, "unterminated string"),
+            ("div[title='x' q]", 14, "invalid attribute flag 'q'"),
+            ("a:not(.b", 8, "expected ')'"),
+            ("a:", 2, "expected a pseudo-class name"),
+            ("a::1", 3, "expected a pseudo-element name"),
+            ("a/**/b", 5, "unexpected 'b'"),
+            ("a:not(.b))", 9, "unexpected ')'"),
+            ("div$", 3, "unexpected '$'"),
+            ("#", 1, "expected an id after '#'"),
+            (".1a", 1, "expected a class name after '.'"),
+        ] {
+            let err = validate_css(css).unwrap_err();
+            assert_eq!(err.position, position, "{css}: {err}");
+            assert!(err.message.starts_with(message), "{css}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_valid_xpath() {
+        for xpath in [
+            "//div",
+            "/",
+            ".",
+            "..",
+            "/html/body//a[@href][1]",
+            "//button[normalize-space(.) = 'Save' and not(@disabled)]",
+            "(//li)[last()]",
+            "//*[contains(concat(' ', @class, ' '), ' item ')]",
+            "//div/following-sibling::*[position() mod 2 = 0]",
+            "count(//a) div 2 - -1",
+            "//a | //b",
+            "//text()[. != \"\"]",
+            "//svg:circle/@svg:*",
+            "x:foo(1)",
+            "//processing-instruction('xml-stylesheet')",
+            "//div[@id = $id]",
+            "//div/div",
+            "//*[.5 < 1.5]",
+            "//a[matches(., 'x')]",
+            "//a[ends-with(@href,'x')]",
+        ] {
+            assert_eq!(validate_xpath(xpath), Ok(()), "{xpath}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_xpath() {
+        for (xpath, position, message) in [
+            ("", 0, "expected a location step, found end of expression"),
+            ("//button[@id='save'", 19, "expected ']', found end of expression"),
+            ("//div[", 6, "expected a location step, found end of expression"),
+            ("//a[text()='x]", 11, "unterminated string"),
+            ("//a/sibling::b", 4, "unknown axis 'sibling'"),
+            ("//a[@id 'x']", 8, "expected ']', found ''x''"),
+            ("//a b", 4, "expected an operator, found 'b'"),
+            ("//a[1]]", 6, "unexpected ']'"),
+            ("//a[@id ! 'x']", 8, "expected '=' after '!'"),
+            ("//a[#]", 4, "unexpected '#'"),
+            ("count(//a", 9, "expected ',' or ')'"),
+            ("//", 2, "expected a location step"),
+        ] {
+            let err = validate_xpath(xpath).unwrap_err();
+            assert_eq!(err.position, position, "{xpath}: {err}");
+            assert!(err.message.starts_with(message), "{xpath}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_by_validate() {
+        assert!(By::Css("div > a").validate().is_ok());
+        assert!(By::XPath("//a").validate().is_ok());
+        assert!(By::Id(r#"say-"hi""#).validate().is_ok());
+        assert!(By::Name("a'b\"c\\").validate().is_ok());
+        assert!(By::DeepCss("my-app >>> button.save").validate().is_ok());
+        assert!(By::LinkText("[").validate().is_ok());
+
+        let err = By::XPath("//a[").validate().unwrap_err();
+        assert!(matches!(*err, WebDriverErrorInner::InvalidSelector(_)), "{err}");
+        assert!(err.to_string().contains("at position 4"), "{err}");
+
+        let err = By::DeepCss("my-app >>> button[").validate().unwrap_err();
+        assert!(err.to_string().contains("at position 18"), "{err}");
+        assert!(By::ClassName("w-1/2").validate().is_err());
+
+        for by in [By::ClassName("foo bar"), By::ClassName(""), By::Id("foo bar")] {
+            let err = by.validate().unwrap_err();
+            assert!(matches!(*err, WebDriverErrorInner::InvalidSelector(_)), "{err}");
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::components::escape_string;
+use crate::By;
+use std::fmt::{Display, Formatter, Write};
+
+/// Quote the specified value as an XPath string literal.
+///
+/// XPath 1.0 has no escape sequences, so values containing both single and double quotes
+/// are built with `concat()`.
+pub fn literal(value: &str) -> String {
+    escape_string(value)
+}
+
+/// Start a selector matching descendants with the specified tag name.
+///
+/// The name is not escaped, because XPath has no way to escape names.
+pub fn tag(name: &str) -> XPathSelector {
+    XPathSelector::new(name.to_string())
+}
+
+/// Start a selector matching any descendant element.
+pub fn any() -> XPathSelector {
+    tag("*")
+}
+
+/// An XPath expression built from escaped parts, so that values containing quotes cannot
+/// break the expression.
+///
+/// The expression starts with `.//`, so it matches descendants of the element it is used
+/// from (or of the document, for `WebDriver::find()` and `WebDriver::query()`).
+/// Each method adds a predicate to the last step in the path, and the axis methods (such as
+/// `descendant()`) add another step. Convert it into a `By` via `By::from()` or `.into()`.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use thirtyfour::xpath;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let selector = xpath::tag("tr")
+///     .has(xpath::tag("td").text_eq("O'Brien"))
+///     .descendant(xpath::tag("button").text_contains("Edit"));
+/// assert_eq!(
+///     selector.to_string(),
+///     r#".//tr[.//td[normalize-space(.) = "O'Brien"]]//button[contains(., "Edit")]"#
+/// );
+/// let elem = driver.find(selector.into()).await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XPathSelector {
+    /// Each step, along with the separator and axis before it.
+    steps: Vec<(&'static str, String)>,
+}
+
+impl XPathSelector {
+    fn new(name: String) -> Self {
+        Self {
+            steps: vec![(".//", name)],
+        }
+    }
+
+    fn push(mut self, predicate: impl Display) -> Self {
+        if let Some((_, step)) = self.steps.last_mut() {
+            let _ = write!(step, "[{predicate}]");
+        }
+        self
+    }
+
+    fn combine(mut self, axis: &'static str, other: XPathSelector) -> Self {
+        let mut steps = other.steps.into_iter();
+        if let Some((_, step)) = steps.next() {
+            self.steps.push((axis, step));
+        }
+        self.steps.extend(steps);
+        self
+    }
+
+    /// Require the element to have the specified id.
+    pub fn id(self, id: &str) -> Self {
+        self.attr_eq("id", id)
+    }
+
+    /// Require the element to have the specified class.
+    pub fn class(self, name: &str) -> Self {
+        self.push(format_args!(
+            "contains(concat(' ', normalize-space(@class), ' '), {})",
+            literal(&format!(" {name} "))
+        ))
+    }
+
+    /// Require the element to have the specified attribute.
+    pub fn attr(self, name: &str) -> Self {
+        self.push(format_args!("@{name}"))
+    }
+
+    /// Require the specified attribute to equal the specified value.
+    pub fn attr_eq(self, name: &str, value: &str) -> Self {
+        self.push(format_args!("@{name} = {}", literal(value)))
+    }
+
+    /// Require the specified attribute to contain the specified value.
+    pub fn attr_contains(self, name: &str, value: &str) -> Self {
+        self.push(format_args!("contains(@{name}, {})", literal(value)))
+    }
+
+    /// Require the specified attribute to start with the specified value.
+    pub fn attr_starts_with(self, name: &str, value: &str) -> Self {
+        self.push(format_args!("starts-with(@{name}, {})", literal(value)))
+    }
+
+    /// Require the text of the element to equal the specified text, ignoring leading,
+    /// trailing and repeated whitespace.
+    pub fn text_eq(self, text: &str) -> Self {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.push(format_args!("normalize-space(.) = {}", literal(&text)))
+    }
+
+    /// Require the text of the element to contain the specified text.
+    pub fn text_contains(self, text: &str) -> Self {
+        self.push(format_args!("contains(., {})", literal(text)))
+    }
+
+    /// Require the text of the element to start with the specified text, ignoring leading
+    /// whitespace.
+    pub fn text_starts_with(self, text: &str) -> Self {
+        self.push(format_args!("starts-with(normalize-space(.), {})", literal(text.trim_start())))
+    }
+
+    /// Require the element to contain an element matching the specified selector.
+    pub fn has(self, other: XPathSelector) -> Self {
+        self.push(other)
+    }
+
+    /// Require the element not to contain an element matching the specified selector.
+    pub fn has_not(self, other: XPathSelector) -> Self {
+        self.push(format_args!("not({other})"))
+    }
+
+    /// Select only the nth of the elements matched by the last step (for each parent),
+    /// starting from 1.
+    pub fn nth(self, n: usize) -> Self {
+        self.push(n)
+    }
+
+    /// Select only the last of the elements matched by the last step (for each parent).
+    pub fn last(self) -> Self {
+        self.push("last()")
+    }
+
+    /// Add the specified predicate expression, such as `@value > 3`.
+    ///
+    /// The expression is not escaped. It is checked when the selector is used, along with
+    /// the rest of the expression (see `By::validate()`).
+    pub fn predicate(self, expr: &str) -> Self {
+        self.push(expr)
+    }
+
+    /// Match elements matching `other` that are descendants of this element.
+    pub fn descendant(self, other: XPathSelector) -> Self {
+        self.combine("//", other)
+    }
+
+    /// Match elements matching `other` that are direct children of this element.
+    pub fn child(self, other: XPathSelector) -> Self {
+        self.combine("/", other)
+    }
+
+    /// Match elements matching `other` that are ancestors of this element.
+    pub fn ancestor(self, other: XPathSelector) -> Self {
+        self.combine("/ancestor::", other)
+    }
+
+    /// Match the parent of this element.
+    pub fn parent(self) -> Self {
+        self.combine("/", tag(".."))
+    }
+
+    /// Match elements matching `other` that follow this element and have the same parent.
+    pub fn following_sibling(self, other: XPathSelector) -> Self {
+        self.combine("/following-sibling::", other)
+    }
+
+    /// Match elements matching `other` that precede this element and have the same parent.
+    pub fn preceding_sibling(self, other: XPathSelector) -> Self {
+        self.combine("/preceding-sibling::", other)
+    }
+}
+
+impl Display for XPathSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (axis, step) in &self.steps {
+            f.write_str(axis)?;
+            f.write_str(step)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<XPathSelector> for By {
+    fn from(selector: XPathSelector) -> Self {
+        By::XPath(selector.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xpath() {
+        let selector = tag("div")
+            .class("card")
+            .attr_eq("title", r#"it's "quoted""#)
+            .child(any().text_eq("  Hello   world "))
+            .following_sibling(tag("span").nth(2))
+            .parent();
+        assert_eq!(
+            selector.to_string(),
+            r#".//div[contains(concat(' ', normalize-space(@class), ' '), " card ")][@title = concat("it's ", '"', "quoted", '"')]/*[normalize-space(.) = "Hello world"]/following-sibling::span[2]/.."#
+        );
+        assert!(By::from(selector).validate().is_ok());
+
+        let selector = tag("li").has_not(tag("a")).last().ancestor(tag("ul").id("menu"));
+        assert_eq!(selector.to_string(), r#".//li[not(.//a)][last()]/ancestor::ul[@id = "menu"]"#);
+        assert!(By::from(selector).validate().is_ok());
+    }
+}
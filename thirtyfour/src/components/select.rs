@@ -19,6 +19,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::common::css::quote_string;
 use crate::error::{no_such_element, WebDriverErrorInner, WebDriverResult};
 use crate::{By, WebElement};
 use std::fmt::{Display, Formatter};
@@ -35,20 +36,21 @@ struct Escaped<'a>(&'a str);
 
 impl Display for Escaped<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Double quotes are written as '"', and everything else within double quotes.
+        let mut parts = Vec::new();
         for (i, substring) in self.0.split('\"').enumerate() {
             if i != 0 {
-                f.write_str(", '\"', ")?
+                parts.push("'\"'".to_string());
+            }
+            if !substring.is_empty() {
+                parts.push(format!("\"{substring}\""));
             }
-            write!(f, "\"{}\"", substring)?;
-        }
-        if self.0.ends_with('\"') {
-            f.write_str(", '\"'")?;
         }
-        Ok(())
+        f.write_str(&parts.join(", "))
     }
 }
 
-/// Escape the specified string for use in Css or XPath selector.
+/// Escape the specified string for use in an XPath selector.
 pub fn escape_string(value: &str) -> String {
     let contains_single = value.contains('\'');
     let contains_double = value.contains('\"');
@@ -120,7 +122,7 @@ impl SelectElement {
 
     /// Set the selection state of options matching the specified value.
     async fn set_selection_by_value(&self, value: &str, select: bool) -> WebDriverResult<()> {
-        let selector = format!("option[value={}]", escape_string(value));
+        let selector = format!("option[value={}]", quote_string(value));
         let options = self.element.find_all(By::Css(&*selector)).await?;
         for option in options {
             set_selected(&option, select).await?;
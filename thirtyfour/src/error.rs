@@ -1,8 +1,13 @@
 use base64::DecodeError;
 use serde::Deserialize;
-use std::fmt::{Display, Formatter, Write};
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter, Write};
 use std::ops::{Deref, DerefMut};
 
+use crate::common::command::{FormatRequestData, Selector};
+use crate::extensions::query::QueryDiagnostics;
+use crate::{ElementId, SessionId};
+
 /// Type def for Result<T, WebDriverError>.
 pub type WebDriverResult<T> = Result<T, WebDriverError>;
 
@@ -112,9 +117,124 @@ impl Display for WebDriverErrorInfo {
 }
 
 /// WebDriverError is the main error type for thirtyfour
-#[derive(Debug, thiserror::Error)]
-#[error(transparent)]
-pub struct WebDriverError(Box<WebDriverErrorInner>);
+///
+/// Errors returned by a command also carry an [`ErrorContext`] describing the command
+/// that failed. See [`WebDriverError::context()`].
+pub struct WebDriverError(Box<ErrorRepr>);
+
+struct ErrorRepr {
+    inner: WebDriverErrorInner,
+    context: Option<ErrorContext>,
+    diagnostics: Option<QueryDiagnostics>,
+}
+
+impl Debug for WebDriverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.0.context, &self.0.diagnostics) {
+            (None, None) => f.debug_tuple("WebDriverError").field(&self.0.inner).finish(),
+            (context, diagnostics) => f
+                .debug_struct("WebDriverError")
+                .field("inner", &self.0.inner)
+                .field("context", context)
+                .field("diagnostics", diagnostics)
+                .finish(),
+        }
+    }
+}
+
+impl Display for WebDriverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.inner)?;
+        if let Some(context) = &self.0.context {
+            write!(f, "\nCommand: {context}")?;
+        }
+        if let Some(diagnostics) = &self.0.diagnostics {
+            write!(f, "\n{diagnostics}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for WebDriverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.inner.source()
+    }
+}
+
+/// Details about the command that caused an error.
+///
+/// The `Display` implementation prints something like
+/// `FindElement(css selector: .btn) from element 1234 on https://example.com (session abcd)`.
+#[derive(Debug, Clone)]
+pub struct ErrorContext {
+    command: Cow<'static, str>,
+    selector: Option<Selector>,
+    element_id: Option<ElementId>,
+    url: Option<String>,
+    session_id: SessionId,
+}
+
+impl ErrorContext {
+    /// Create a new ErrorContext for the specified command.
+    pub(crate) fn new(command: &impl FormatRequestData, session_id: &SessionId) -> Self {
+        Self {
+            command: command.command_name(),
+            selector: command.selector().cloned(),
+            element_id: command.element_id().cloned(),
+            url: None,
+            session_id: session_id.clone(),
+        }
+    }
+
+    /// Set the URL of the page at the time of the error.
+    pub(crate) fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// The name of the command that failed, e.g. `FindElement`.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// The selector used by the command, if any.
+    pub fn selector(&self) -> Option<&Selector> {
+        self.selector.as_ref()
+    }
+
+    /// The id of the element the command operated on, if any.
+    pub fn element_id(&self) -> Option<&ElementId> {
+        self.element_id.as_ref()
+    }
+
+    /// The URL of the page at the time of the error, if it could be determined.
+    ///
+    /// This is only set if `WebDriverConfigBuilder::error_url_context()` was enabled.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// The id of the session that sent the command.
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.command)?;
+        if let Some(selector) = &self.selector {
+            write!(f, "({selector})")?;
+        }
+        if let Some(element_id) = &self.element_id {
+            write!(f, " from element {element_id}")?;
+        }
+        if let Some(url) = &self.url {
+            write!(f, " on {url}")?;
+        }
+        write!(f, " (session {})", self.session_id)
+    }
+}
 
 macro_rules! make_enum_variant_func {
     ($enum_name: ident $variant_name: ident()) => {
@@ -189,6 +309,15 @@ macro_rules! webdriver_err {
             )+
         }
 
+        impl $enum_name {
+            /// The name of this error variant, e.g. `NoSuchElement`.
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $(Self::$variant_name(..) => stringify!($variant_name)),+
+                }
+            }
+        }
+
         $(
             impl_from_for_variant! {
                 $($(#[$($ty_attr)*])* $variant_tys),*
@@ -285,6 +414,12 @@ webdriver_err! {
         CommandSendError(String),
         #[error("Could not create session: {0}")]
         SessionCreateError(String),
+        #[error("The command did not complete within the command timeout: {0}")]
+        CommandTimeout(String),
+        #[error("The command was cancelled: {0}")]
+        CommandCancelled(String),
+        #[error("expectation failed: {0}")]
+        ExpectationFailed(String),
     }
 }
 
@@ -350,12 +485,61 @@ impl WebDriverError {
 
     /// converts the underlying representation to the main representation
     pub fn from_inner(err: WebDriverErrorInner) -> Self {
-        Self(Box::new(err))
+        Self(Box::new(ErrorRepr {
+            inner: err,
+            context: None,
+            diagnostics: None,
+        }))
     }
 
     /// converts this error to its underlying representation
+    ///
+    /// NOTE: This discards the `ErrorContext` and `QueryDiagnostics`, if any.
     pub fn into_inner(self) -> WebDriverErrorInner {
-        *self.0
+        self.0.inner
+    }
+
+    /// Details about the command that caused this error, if known.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// if let Err(e) = driver.find(By::Css(".btn")).await {
+    ///     let context = e.context().expect("errors from commands have context");
+    ///     assert_eq!(context.command(), "FindElement");
+    ///     println!("{} failed on {:?}", context.command(), context.url());
+    /// }
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn context(&self) -> Option<&ErrorContext> {
+        self.0.context.as_ref()
+    }
+
+    /// Attach the specified context to this error, replacing any existing context.
+    pub fn with_context(mut self, context: ErrorContext) -> Self {
+        self.0.context = Some(context);
+        self
+    }
+
+    /// The report explaining why an `ElementQuery` found nothing, if this error was
+    /// returned by an `ElementQuery`. See [`QueryDiagnostics`].
+    pub fn query_diagnostics(&self) -> Option<&QueryDiagnostics> {
+        self.0.diagnostics.as_ref()
+    }
+
+    /// Attach the specified query diagnostics to this error.
+    pub(crate) fn with_query_diagnostics(mut self, diagnostics: QueryDiagnostics) -> Self {
+        self.0.diagnostics = Some(diagnostics);
+        self
     }
 }
 
@@ -375,13 +559,13 @@ impl Deref for WebDriverError {
     type Target = WebDriverErrorInner;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.0.inner
     }
 }
 
 impl DerefMut for WebDriverError {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.0.inner
     }
 }
 
@@ -399,6 +583,20 @@ pub fn no_such_element(message: String) -> WebDriverError {
     }))
 }
 
+/// Convenience function to construct a simulated InvalidSelector error.
+pub fn invalid_selector(message: String) -> WebDriverError {
+    WebDriverError::from_inner(WebDriverErrorInner::InvalidSelector(WebDriverErrorInfo {
+        status: 400,
+        error: message.clone(),
+        value: WebDriverErrorValue {
+            message,
+            error: None,
+            stacktrace: None,
+            data: None,
+        },
+    }))
+}
+
 #[cfg(feature = "reqwest")]
 impl From<reqwest::Error> for WebDriverError {
     fn from(err: reqwest::Error) -> Self {
@@ -411,3 +609,27 @@ impl From<serde_json::Error> for WebDriverError {
         WebDriverError::Json(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::command::Command;
+    use crate::By;
+
+    #[test]
+    fn test_error_context() {
+        let command = Command::FindElementFromElement("1234".into(), By::Css(".btn").into());
+        let context = ErrorContext::new(&command, &SessionId::from("abcd"))
+            .with_url(Some("https://example.com/".to_string()));
+        let error = no_such_element("not found".to_string()).with_context(context);
+
+        let context = error.context().unwrap();
+        assert_eq!(context.command(), "FindElementFromElement");
+        assert_eq!(context.element_id().map(|id| id.to_string()).as_deref(), Some("1234"));
+        assert_eq!(context.url(), Some("https://example.com/"));
+        assert!(error.to_string().ends_with(
+            "\nCommand: FindElementFromElement(css selector: .btn) from element 1234 \
+             on https://example.com/ (session abcd)"
+        ));
+    }
+}
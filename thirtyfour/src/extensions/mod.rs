@@ -4,3 +4,6 @@ pub mod addons;
 pub mod cdp;
 // ElementQuery and ElementWaiter interfaces.
 pub mod query;
+/// Local snapshots of the DOM, for fast read-only queries.
+#[cfg(feature = "dom-snapshot")]
+pub mod snapshot;
@@ -0,0 +1,288 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use super::filter_elements;
+use crate::error::{WebDriverError, WebDriverResult};
+use crate::js::READ_ELEMENT_VALUES;
+use crate::session::handle::SessionHandle;
+use futures_util::future::BoxFuture;
+
+use crate::{DynElementPredicate, ElementId, ElementPredicate, ElementQueryFn, WebElement};
+
+/// A value read from an element by a built-in filter.
+#[derive(Debug, Clone)]
+pub(crate) enum Probe {
+    Text,
+    TagName,
+    Attribute(Arc<str>),
+    Property(Arc<str>),
+    CssValue(Arc<str>),
+    Displayed,
+    Enabled,
+    Selected,
+}
+
+impl Probe {
+    fn to_json(&self) -> Value {
+        match self {
+            Probe::Text => json!({"kind": "text"}),
+            Probe::TagName => json!({"kind": "tag_name"}),
+            Probe::Attribute(name) => json!({"kind": "attribute", "name": name}),
+            Probe::Property(name) => json!({"kind": "property", "name": name}),
+            Probe::CssValue(name) => json!({"kind": "css_value", "name": name}),
+            Probe::Displayed => json!({"kind": "displayed"}),
+            Probe::Enabled => json!({"kind": "enabled"}),
+            Probe::Selected => json!({"kind": "selected"}),
+        }
+    }
+
+    /// Convert the value returned by the script, in the same way as the corresponding
+    /// `WebElement` method converts the WebDriver response.
+    fn parse(&self, value: Value) -> WebDriverResult<ProbeValue> {
+        let unexpected = |v: &Value| WebDriverError::Json(format!("Unexpected value: {v:?}"));
+        Ok(match (self, value) {
+            (Probe::Text | Probe::TagName | Probe::CssValue(_), Value::String(s)) => {
+                ProbeValue::Text(s)
+            }
+            (Probe::Attribute(_), Value::String(s)) => ProbeValue::Opt(Some(s)),
+            (Probe::Attribute(_) | Probe::Property(_), Value::Null) => ProbeValue::Opt(None),
+            (Probe::Property(_), Value::String(s)) => ProbeValue::Opt(Some(s)),
+            (Probe::Property(_), Value::Bool(b)) => ProbeValue::Opt(Some(b.to_string())),
+            (Probe::Property(_), Value::Number(n)) => ProbeValue::Opt(Some(n.to_string())),
+            (Probe::Displayed | Probe::Enabled | Probe::Selected, Value::Bool(b)) => {
+                ProbeValue::Flag(b)
+            }
+            (_, v) => return Err(unexpected(&v)),
+        })
+    }
+}
+
+/// A value read by a `Probe`.
+#[derive(Debug, Clone)]
+pub(crate) enum ProbeValue {
+    Text(String),
+    Opt(Option<String>),
+    Flag(bool),
+}
+
+impl Display for ProbeValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProbeValue::Text(s) | ProbeValue::Opt(Some(s)) => write!(f, "{s:?}"),
+            ProbeValue::Opt(None) => write!(f, "none"),
+            ProbeValue::Flag(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl ProbeValue {
+    pub(crate) fn text(&self) -> &str {
+        match self {
+            ProbeValue::Text(s) => s,
+            _ => "",
+        }
+    }
+
+    pub(crate) fn opt(&self) -> Option<&str> {
+        match self {
+            ProbeValue::Opt(s) => s.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn flag(&self) -> bool {
+        matches!(self, ProbeValue::Flag(true))
+    }
+}
+
+type BatchCheck = dyn Fn(&[ProbeValue]) -> bool + Send + Sync;
+
+/// A built-in `ElementQuery` filter.
+///
+/// This can either be run as a normal `ElementPredicate` (sending WebDriver commands for
+/// each element), or checked for all elements at once by reading the values it needs via
+/// a single script. See `ElementQuery::batch_filters()` for details.
+#[derive(Clone)]
+pub struct BatchFilter {
+    predicate: Arc<DynElementPredicate>,
+    probes: Vec<Probe>,
+    check: Arc<BatchCheck>,
+}
+
+impl Debug for BatchFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchFilter").field("probes", &self.probes).finish()
+    }
+}
+
+impl BatchFilter {
+    /// Create a new `BatchFilter`.
+    ///
+    /// The `check` function is called with the values read by each of the `probes`, and
+    /// must return the same result as `predicate` would.
+    pub(crate) fn new(
+        predicate: impl ElementPredicate + 'static,
+        probes: Vec<Probe>,
+        check: impl Fn(&[ProbeValue]) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            predicate: DynElementPredicate::arc(predicate),
+            probes,
+            check: Arc::new(check),
+        }
+    }
+
+    /// The filter as a normal `ElementPredicate`.
+    pub fn predicate(&self) -> &DynElementPredicate {
+        &*self.predicate
+    }
+
+    /// Check the values read by the probes for a single element.
+    pub(crate) fn check(&self, values: &[ProbeValue]) -> bool {
+        (self.check)(values)
+    }
+}
+
+impl ElementQueryFn<bool> for BatchFilter {
+    type Fut = BoxFuture<'static, WebDriverResult<bool>>;
+
+    fn call(&self, arg: WebElement) -> Self::Fut {
+        self.predicate.call(arg)
+    }
+
+    fn description(&self) -> Option<String> {
+        self.predicate.description()
+    }
+}
+
+/// Filter the specified elements using the specified built-in filters.
+///
+/// If `batch` is true, the values needed by all filters are read with a single script
+/// execution (per frame). If that fails, for example because an element became stale,
+/// the filters are run as normal `ElementPredicate`s instead.
+pub async fn filter_elements_batched(
+    handle: &Arc<SessionHandle>,
+    elements: Vec<WebElement>,
+    filters: &[BatchFilter],
+    batch: bool,
+) -> WebDriverResult<Vec<WebElement>> {
+    if elements.is_empty() || filters.is_empty() {
+        return Ok(elements);
+    }
+    if !batch {
+        let predicates: Vec<_> = filters.iter().map(|f| &f.predicate).collect();
+        return filter_elements(elements, predicates).await;
+    }
+
+    // Each script execution can only access the elements in a single frame.
+    let mut groups: IndexMap<Vec<ElementId>, Vec<WebElement>> = IndexMap::new();
+    for element in elements {
+        groups.entry(element.frame_path().to_vec()).or_default().push(element);
+    }
+
+    let mut matched = Vec::new();
+    for (frames, group) in groups {
+        match check_group(handle, &frames, &group, filters).await {
+            Ok(keep) => {
+                matched.extend(group.into_iter().zip(keep).filter(|(_, keep)| *keep).map(|x| x.0))
+            }
+            Err(e) => {
+                tracing::debug!("batched filters failed, running them individually: {e}");
+                let predicates: Vec<_> = filters.iter().map(|f| &f.predicate).collect();
+                matched.extend(filter_elements(group, predicates).await?);
+            }
+        }
+    }
+    Ok(matched)
+}
+
+/// Check all filters for the specified elements, which must all be in the specified frame.
+///
+/// Returns whether each element matched all filters.
+async fn check_group(
+    handle: &Arc<SessionHandle>,
+    frames: &[ElementId],
+    elements: &[WebElement],
+    filters: &[BatchFilter],
+) -> WebDriverResult<Vec<bool>> {
+    let probes: Vec<&Probe> = filters.iter().flat_map(|f| &f.probes).collect();
+    let rows = read_values(handle, frames, elements, &probes).await?;
+    Ok(rows
+        .into_iter()
+        .map(|values| {
+            let mut offset = 0;
+            filters.iter().all(|filter| {
+                let values = &values[offset..offset + filter.probes.len()];
+                offset += filter.probes.len();
+                filter.check(values)
+            })
+        })
+        .collect())
+}
+
+/// Read the values needed by the specified filter for each of the specified elements,
+/// which can be in any frame.
+pub(crate) async fn read_filter_values(
+    handle: &Arc<SessionHandle>,
+    elements: &[WebElement],
+    filter: &BatchFilter,
+) -> WebDriverResult<Vec<Vec<ProbeValue>>> {
+    let probes: Vec<&Probe> = filter.probes.iter().collect();
+    let mut groups: IndexMap<&[ElementId], Vec<usize>> = IndexMap::new();
+    for (i, element) in elements.iter().enumerate() {
+        groups.entry(element.frame_path()).or_default().push(i);
+    }
+
+    let mut values = vec![Vec::new(); elements.len()];
+    for (frames, indices) in groups {
+        let group: Vec<WebElement> = indices.iter().map(|&i| elements[i].clone()).collect();
+        let rows = read_values(handle, frames, &group, &probes).await?;
+        for (i, row) in indices.into_iter().zip(rows) {
+            values[i] = row;
+        }
+    }
+    Ok(values)
+}
+
+/// Read the values for the specified probes from each of the specified elements, which
+/// must all be in the specified frame.
+async fn read_values(
+    handle: &Arc<SessionHandle>,
+    frames: &[ElementId],
+    elements: &[WebElement],
+    probes: &[&Probe],
+) -> WebDriverResult<Vec<Vec<ProbeValue>>> {
+    let args = vec![
+        Value::Array(elements.iter().map(|e| e.to_json()).collect::<WebDriverResult<_>>()?),
+        Value::Array(probes.iter().map(|p| p.to_json()).collect()),
+    ];
+    let ret = if frames.is_empty() {
+        handle.execute(READ_ELEMENT_VALUES, args).await?
+    } else {
+        handle.in_frame_path(frames, handle.execute(READ_ELEMENT_VALUES, args)).await?
+    };
+
+    let rows: Vec<Vec<Value>> = ret.convert()?;
+    if rows.len() != elements.len() {
+        return Err(WebDriverError::Json(format!(
+            "expected values for {} elements, got {}",
+            elements.len(),
+            rows.len()
+        )));
+    }
+    rows.into_iter()
+        .map(|row| {
+            if row.len() != probes.len() {
+                return Err(WebDriverError::Json(format!(
+                    "expected {} values, got {}",
+                    probes.len(),
+                    row.len()
+                )));
+            }
+            probes.iter().zip(row).map(|(probe, value)| probe.parse(value)).collect()
+        })
+        .collect()
+}
@@ -1,8 +1,9 @@
 use crate::error::WebDriverResult;
 use crate::IntoArcStr;
-use crate::{ElementPredicate, WebElement};
+use crate::{DynElementPredicate, ElementPredicate, ElementQueryFn, WebElement};
+use std::any::Any;
 use std::sync::Arc;
-use stringmatch::Needle;
+use stringmatch::{Needle, StringMatch};
 
 pub(crate) fn handle_errors(
     result: WebDriverResult<bool>,
@@ -19,44 +20,293 @@ pub(crate) fn negate(result: WebDriverResult<bool>, ignore_errors: bool) -> WebD
     handle_errors(result.map(|x| !x), ignore_errors)
 }
 
+/// The description used for predicates that do not have one.
+fn description_of<P: ElementPredicate + ?Sized>(predicate: &P) -> String {
+    predicate.description().unwrap_or_else(|| "custom predicate".to_string())
+}
+
+/// A predicate with a human-readable description.
+struct Described<P> {
+    predicate: P,
+    description: String,
+}
+
+impl<P: ElementPredicate> ElementQueryFn<bool> for Described<P> {
+    type Fut = P::Fut;
+
+    fn call(&self, arg: WebElement) -> Self::Fut {
+        self.predicate.call(arg)
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(self.description.clone())
+    }
+}
+
+/// Add a human-readable description to the specified predicate (usually a closure), which
+/// is shown in error messages when a query or wait fails.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use thirtyfour::extensions::query::conditions;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let elem = driver.find(By::Id("counter")).await?;
+/// elem.wait_until()
+///     .condition(conditions::describe("count is even", |elem: WebElement| async move {
+///         Ok(elem.text().await?.parse::<u32>().is_ok_and(|n| n % 2 == 0))
+///     }))
+///     .await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+pub fn describe<P>(description: impl Into<String>, predicate: P) -> impl ElementPredicate
+where
+    P: ElementPredicate,
+{
+    Described {
+        predicate,
+        description: description.into(),
+    }
+}
+
+/// Predicate that returns true for elements that match both of the specified predicates.
+///
+/// The second predicate is only checked if the first one matches.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use thirtyfour::extensions::query::conditions::{self, and, or};
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let elem = driver
+///     .query(By::Css("li"))
+///     .with_filter(and(
+///         conditions::element_is_displayed(true),
+///         or(
+///             conditions::element_has_class("active", true),
+///             conditions::element_has_text("Home", true),
+///         ),
+///     ))
+///     .first()
+///     .await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+pub fn and<A, B>(a: A, b: B) -> impl ElementPredicate
+where
+    A: ElementPredicate,
+    B: ElementPredicate,
+{
+    let description = format!("({} and {})", description_of(&a), description_of(&b));
+    let predicates = Arc::new((a, b));
+    describe(description, move |elem: WebElement| {
+        let predicates = predicates.clone();
+        async move { Ok(predicates.0.call(elem.clone()).await? && predicates.1.call(elem).await?) }
+    })
+}
+
+/// Predicate that returns true for elements that match either of the specified predicates.
+///
+/// The second predicate is only checked if the first one does not match.
+pub fn or<A, B>(a: A, b: B) -> impl ElementPredicate
+where
+    A: ElementPredicate,
+    B: ElementPredicate,
+{
+    let description = format!("({} or {})", description_of(&a), description_of(&b));
+    let predicates = Arc::new((a, b));
+    describe(description, move |elem: WebElement| {
+        let predicates = predicates.clone();
+        async move { Ok(predicates.0.call(elem.clone()).await? || predicates.1.call(elem).await?) }
+    })
+}
+
+/// Predicate that returns true for elements that do not match the specified predicate.
+///
+/// Errors are returned as-is, so if the predicate ignores errors (treating them as not
+/// matching), the negated predicate will match when an error occurs.
+pub fn not<P>(predicate: P) -> impl ElementPredicate
+where
+    P: ElementPredicate,
+{
+    let description = format!("not {}", description_of(&predicate));
+    let predicate = Arc::new(predicate);
+    describe(description, move |elem: WebElement| {
+        let predicate = predicate.clone();
+        async move { Ok(!predicate.call(elem).await?) }
+    })
+}
+
+/// Predicate that returns true for elements that match all of the specified predicates.
+///
+/// The predicates are checked in order, stopping at the first one that does not match.
+/// An empty list matches all elements.
+pub fn all_of(
+    predicates: impl IntoIterator<Item = Box<DynElementPredicate>>,
+) -> impl ElementPredicate {
+    let predicates: Arc<[Box<DynElementPredicate>]> = predicates.into_iter().collect();
+    let description = join_descriptions(&predicates, "all of");
+    describe(description, move |elem: WebElement| {
+        let predicates = predicates.clone();
+        async move {
+            for predicate in predicates.iter() {
+                if !predicate.call(elem.clone()).await? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+    })
+}
+
+/// Predicate that returns true for elements that match any of the specified predicates.
+///
+/// The predicates are checked in order, stopping at the first one that matches.
+/// An empty list matches no elements.
+pub fn any_of(
+    predicates: impl IntoIterator<Item = Box<DynElementPredicate>>,
+) -> impl ElementPredicate {
+    let predicates: Arc<[Box<DynElementPredicate>]> = predicates.into_iter().collect();
+    let description = join_descriptions(&predicates, "any of");
+    describe(description, move |elem: WebElement| {
+        let predicates = predicates.clone();
+        async move {
+            for predicate in predicates.iter() {
+                if predicate.call(elem.clone()).await? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+    })
+}
+
+fn join_descriptions(predicates: &[Box<DynElementPredicate>], prefix: &str) -> String {
+    let descriptions: Vec<String> = predicates.iter().map(|p| description_of(&**p)).collect();
+    format!("{prefix} [{}]", descriptions.join(", "))
+}
+
+/// Predicate that returns true for elements whose text matches the specified function.
+pub fn element_text_satisfies<F>(f: F, ignore_errors: bool) -> impl ElementPredicate
+where
+    F: Fn(&str) -> bool + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    describe("text satisfies custom predicate", move |elem: WebElement| {
+        let f = f.clone();
+        async move { handle_errors(elem.text().await.map(|x| f(&x)), ignore_errors) }
+    })
+}
+
+/// Predicate that returns true for elements where the specified attribute (or `None` if it
+/// is missing) matches the specified function.
+pub fn element_attribute_satisfies<F>(
+    attribute_name: impl IntoArcStr,
+    f: F,
+    ignore_errors: bool,
+) -> impl ElementPredicate
+where
+    F: Fn(Option<&str>) -> bool + Send + Sync + 'static,
+{
+    let name: Arc<str> = attribute_name.into();
+    let description = format!("attribute {name:?} satisfies custom predicate");
+    let f = Arc::new(f);
+    describe(description, move |elem: WebElement| {
+        let name = name.clone();
+        let f = f.clone();
+        async move { handle_errors(elem.attr(name).await.map(|x| f(x.as_deref())), ignore_errors) }
+    })
+}
+
+/// Predicate that returns true for elements where the specified property (or `None` if it
+/// is missing) matches the specified function.
+pub fn element_property_satisfies<F>(
+    property_name: impl IntoArcStr,
+    f: F,
+    ignore_errors: bool,
+) -> impl ElementPredicate
+where
+    F: Fn(Option<&str>) -> bool + Send + Sync + 'static,
+{
+    let name: Arc<str> = property_name.into();
+    let description = format!("property {name:?} satisfies custom predicate");
+    let f = Arc::new(f);
+    describe(description, move |elem: WebElement| {
+        let name = name.clone();
+        let f = f.clone();
+        async move { handle_errors(elem.prop(name).await.map(|x| f(x.as_deref())), ignore_errors) }
+    })
+}
+
 /// Predicate that returns true for elements that are enabled.
 pub fn element_is_enabled(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { handle_errors(elem.is_enabled().await, ignore_errors) }
+    describe("enabled", move |elem: WebElement| async move {
+        handle_errors(elem.is_enabled().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that are not enabled.
 pub fn element_is_not_enabled(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { negate(elem.is_enabled().await, ignore_errors) }
+    describe("not enabled", move |elem: WebElement| async move {
+        negate(elem.is_enabled().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that are selected.
 pub fn element_is_selected(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { handle_errors(elem.is_selected().await, ignore_errors) }
+    describe("selected", move |elem: WebElement| async move {
+        handle_errors(elem.is_selected().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that are not selected.
 pub fn element_is_not_selected(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { negate(elem.is_selected().await, ignore_errors) }
+    describe("not selected", move |elem: WebElement| async move {
+        negate(elem.is_selected().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that are displayed.
 pub fn element_is_displayed(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { handle_errors(elem.is_displayed().await, ignore_errors) }
+    describe("displayed", move |elem: WebElement| async move {
+        handle_errors(elem.is_displayed().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that are not displayed.
 pub fn element_is_not_displayed(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { negate(elem.is_displayed().await, ignore_errors) }
+    describe("not displayed", move |elem: WebElement| async move {
+        negate(elem.is_displayed().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that are clickable.
 pub fn element_is_clickable(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { handle_errors(elem.is_clickable().await, ignore_errors) }
+    describe("clickable", move |elem: WebElement| async move {
+        handle_errors(elem.is_clickable().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that are not clickable.
 pub fn element_is_not_clickable(ignore_errors: bool) -> impl ElementPredicate {
-    move |elem: WebElement| async move { negate(elem.is_clickable().await, ignore_errors) }
+    describe("not clickable", move |elem: WebElement| async move {
+        negate(elem.is_clickable().await, ignore_errors)
+    })
 }
 
 /// Predicate that returns true for elements that have the specified class name.
@@ -66,7 +316,8 @@ pub fn element_has_class<N>(class_name: N, ignore_errors: bool) -> impl ElementP
 where
     N: Needle + Clone + Send + Sync + 'static,
 {
-    move |elem: WebElement| {
+    let description = format!("has class {}", describe_needle(&class_name));
+    describe(description, move |elem: WebElement| {
         let class_name = class_name.clone();
         async move {
             match elem.class_name().await {
@@ -75,7 +326,7 @@ where
                 Err(e) => handle_errors(Err(e), ignore_errors),
             }
         }
-    }
+    })
 }
 
 /// Predicate that returns true for elements that do not contain the specified class name.
@@ -85,7 +336,8 @@ pub fn element_lacks_class<N>(class_name: N, ignore_errors: bool) -> impl Elemen
 where
     N: Needle + Clone + Send + Sync + 'static,
 {
-    move |elem: WebElement| {
+    let description = format!("lacks class {}", describe_needle(&class_name));
+    describe(description, move |elem: WebElement| {
         let class_name = class_name.clone();
         async move {
             match elem.class_name().await {
@@ -94,7 +346,7 @@ where
                 Err(e) => handle_errors(Err(e), ignore_errors),
             }
         }
-    }
+    })
 }
 
 /// Predicate that returns true for elements that have the specified text.
@@ -103,10 +355,11 @@ pub fn element_has_text<N>(text: N, ignore_errors: bool) -> impl ElementPredicat
 where
     N: Needle + Clone + Send + Sync + 'static,
 {
-    move |elem: WebElement| {
+    let description = format!("has text {}", describe_needle(&text));
+    describe(description, move |elem: WebElement| {
         let text = text.clone();
         async move { handle_errors(elem.text().await.map(|x| text.is_match(&x)), ignore_errors) }
-    }
+    })
 }
 
 /// Predicate that returns true for elements that do not contain the specified text.
@@ -115,10 +368,11 @@ pub fn element_lacks_text<N>(text: N, ignore_errors: bool) -> impl ElementPredic
 where
     N: Needle + Clone + Send + Sync + 'static,
 {
-    move |elem: WebElement| {
+    let description = format!("lacks text {}", describe_needle(&text));
+    describe(description, move |elem: WebElement| {
         let text = text.clone();
         async move { handle_errors(elem.text().await.map(|x| !text.is_match(&x)), ignore_errors) }
-    }
+    })
 }
 
 /// Predicate that returns true for elements that have the specified value.
@@ -127,7 +381,8 @@ pub fn element_has_value<N>(value: N, ignore_errors: bool) -> impl ElementPredic
 where
     N: Needle + Clone + Send + Sync + 'static,
 {
-    move |elem: WebElement| {
+    let description = format!("has value {}", describe_needle(&value));
+    describe(description, move |elem: WebElement| {
         let value = value.clone();
         async move {
             match elem.value().await {
@@ -136,7 +391,7 @@ where
                 Err(e) => handle_errors(Err(e), ignore_errors),
             }
         }
-    }
+    })
 }
 
 /// Predicate that returns true for elements that do not contain the specified value.
@@ -145,7 +400,8 @@ pub fn element_lacks_value<N>(value: N, ignore_errors: bool) -> impl ElementPred
 where
     N: Needle + Clone + Send + Sync + 'static,
 {
-    move |elem: WebElement| {
+    let description = format!("lacks value {}", describe_needle(&value));
+    describe(description, move |elem: WebElement| {
         let value = value.clone();
         async move {
             match elem.value().await {
@@ -154,9 +410,35 @@ where
                 Err(e) => handle_errors(Err(e), ignore_errors),
             }
         }
+    })
+}
+
+/// Describe the specified needle, for the description of a predicate.
+///
+/// `Needle` has no way to describe itself, so only strings and `StringMatch` are shown in
+/// full. Any other needle (such as a closure) is described generically.
+pub(crate) fn describe_needle<N: Needle + 'static>(needle: &N) -> String {
+    let needle = needle as &dyn Any;
+    if let Some(text) = needle.downcast_ref::<&str>() {
+        format!("{text:?}")
+    } else if let Some(text) = needle.downcast_ref::<String>() {
+        format!("{text:?}")
+    } else if let Some(string_match) = needle.downcast_ref::<StringMatch>() {
+        format!("{string_match:?}")
+    } else {
+        "<custom needle>".to_string()
     }
 }
 
+/// Describe the specified names and needles, for the description of a predicate.
+fn describe_pairs<N: Needle + 'static>(desired: &[(Arc<str>, N)]) -> String {
+    let pairs: Vec<String> = desired
+        .iter()
+        .map(|(name, needle)| format!("{name:?} = {}", describe_needle(needle)))
+        .collect();
+    format!("[{}]", pairs.join(", "))
+}
+
 pub(crate) fn collect_arg_slice<S, N, I>(desired_attributes: I) -> Arc<[(Arc<str>, N)]>
 where
     I: IntoIterator<Item = (S, N)>,
@@ -222,10 +504,15 @@ macro_rules! elem_matches {
             where
                 N: Needle + Send + Sync + 'static,
             {
-                move |elem: WebElement| {
+                let description = format!(
+                    "has {} {}",
+                    stringify!($plural).replace('_', " "),
+                    describe_pairs(&[<desired_ $plural>])
+                );
+                describe(description, move |elem: WebElement| {
                     let desired = [<desired_ $plural>].clone();
                     elem_matches!(@[<inner_many_ $name>] elem, desired, ignore_errors, $field, |val, x| !val.is_match(&x))
-                }
+                })
             }
 
             #[doc = concat!("Predicate that returns true for elements that do not have any of the specified ",stringify!($plural)," specified with the")]
@@ -237,10 +524,15 @@ macro_rules! elem_matches {
             where
                 N: Needle + Send + Sync + 'static,
             {
-                move |elem: WebElement| {
+                let description = format!(
+                    "lacks {} {}",
+                    stringify!($plural).replace('_', " "),
+                    describe_pairs(&[<desired_ $plural>])
+                );
+                describe(description, move |elem: WebElement| {
                     let desired = [<desired_ $plural>].clone();
                     elem_matches!(@[<inner_many_ $name>] elem, desired, ignore_errors, $field, |value, x| value.is_match(&x))
-                }
+                })
             }
         }
     };
@@ -256,11 +548,17 @@ macro_rules! elem_matches {
             where
                 N: Needle + Clone + Send + Sync + 'static,
             {
-                move |elem: WebElement| {
+                let description = format!(
+                    "has {} {:?} = {}",
+                    stringify!($single).replace('_', " "),
+                    [<$single _name>],
+                    describe_needle(&value)
+                );
+                describe(description, move |elem: WebElement| {
                     let name = [<$single _name>].clone();
                     let value = value.clone();
                     elem_matches!(@[<inner_single_ $name>] elem, name, ignore_errors, $field, |x| value.is_match(&x))
-                }
+                })
             }
 
             #[doc = concat!("Predicate that returns true for elements that do not contain the specified ",stringify!($plural)," with the specified")]
@@ -273,11 +571,17 @@ macro_rules! elem_matches {
             where
                 N: Needle + Clone + Send + Sync + 'static,
             {
-                move |elem: WebElement| {
+                let description = format!(
+                    "lacks {} {:?} = {}",
+                    stringify!($single).replace('_', " "),
+                    [<$single _name>],
+                    describe_needle(&value)
+                );
+                describe(description, move |elem: WebElement| {
                     let name = [<$single _name>].clone();
                     let value = value.clone();
                     elem_matches!(@[<inner_single_ $name>] elem, name, ignore_errors, $field, |x| !value.is_match(&x))
-                }
+                })
             }
         }
     };
@@ -291,3 +595,43 @@ macro_rules! elem_matches {
 elem_matches!([opt] attr => attribute, attributes);
 elem_matches!([opt] prop => property,  properties);
 elem_matches!([css] css_value => css_property,  css_properties);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptions() {
+        let predicate = and(
+            element_is_displayed(true),
+            or(not(element_has_class("hidden", true)), element_has_text("Show", true)),
+        );
+        assert_eq!(
+            predicate.description().unwrap(),
+            r#"(displayed and (not has class "hidden" or has text "Show"))"#
+        );
+
+        let predicate = element_has_attribute("data-id".into(), "1", true);
+        assert_eq!(predicate.description().unwrap(), r#"has attribute "data-id" = "1""#);
+
+        // Needles do not need to implement Debug.
+        let predicate = element_has_text(|text: &str| text.ends_with('!'), true);
+        assert_eq!(predicate.description().unwrap(), "has text <custom needle>");
+
+        let desired = collect_arg_slice([("id", String::from("a")), ("name", String::from("b"))]);
+        let predicate = element_lacks_attributes(desired, true);
+        assert_eq!(
+            predicate.description().unwrap(),
+            r#"lacks attributes ["id" = "a", "name" = "b"]"#
+        );
+
+        let predicate = any_of([
+            DynElementPredicate::boxed(element_has_css_property("color".into(), "red", true)),
+            DynElementPredicate::boxed(|_: WebElement| async move { Ok(true) }),
+        ]);
+        assert_eq!(
+            predicate.description().unwrap(),
+            r#"any of [has css property "color" = "red", custom predicate]"#
+        );
+    }
+}
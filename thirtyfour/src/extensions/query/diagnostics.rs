@@ -0,0 +1,150 @@
+use std::fmt::{Display, Formatter};
+
+/// The maximum number of observed values recorded for each filter.
+pub(crate) const MAX_OBSERVED_VALUES: usize = 5;
+
+/// A report explaining why an `ElementQuery` found nothing.
+///
+/// This is attached to the `NoSuchElement` error returned by `ElementQuery::first()`,
+/// `single()`, `any_required()` and `all_from_selector_required()`, and is available via
+/// `WebDriverError::query_diagnostics()`.
+///
+/// It is collected by running each selector of the query once more after the query failed,
+/// applying the relations and filters one at a time, so the page may have changed since
+/// the last poll attempt. Collection is disabled by default, and can be enabled via
+/// `ElementQuery::diagnostics()` or `WebDriverConfigBuilder::query_diagnostics()`.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let result = driver
+///     .query(By::Css("button"))
+///     .with_text("Save")
+///     .and_enabled()
+///     .diagnostics(true)
+///     .first()
+///     .await;
+/// if let Err(e) = result {
+///     if let Some(diagnostics) = e.query_diagnostics() {
+///         for selector in &diagnostics.selectors {
+///             println!("{} matched {} elements", selector.selector, selector.matched);
+///         }
+///     }
+/// }
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct QueryDiagnostics {
+    /// The diagnostics for each selector, in the order they were added to the query
+    /// (the first one, followed by each `or()` branch).
+    pub selectors: Vec<SelectorDiagnostics>,
+}
+
+/// The diagnostics for a single selector in an `ElementQuery`. See `QueryDiagnostics`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SelectorDiagnostics {
+    /// The selector, e.g. `Css(button)`.
+    pub selector: String,
+    /// The number of elements matched by the selector, before any relations or filters.
+    pub matched: usize,
+    /// The relations and filters, in the order they were applied.
+    pub filters: Vec<FilterDiagnostics>,
+    /// The error that stopped the diagnostics for this selector, if any.
+    pub error: Option<String>,
+}
+
+/// The diagnostics for a single relation or filter. See `QueryDiagnostics`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FilterDiagnostics {
+    /// The description of the relation or filter.
+    pub description: String,
+    /// The number of elements checked by this filter.
+    pub candidates: usize,
+    /// The number of elements rejected by this filter.
+    pub rejected: usize,
+    /// The values observed for (up to 5 of) the rejected elements.
+    ///
+    /// Only built-in filters (such as `with_text()`) report observed values.
+    pub observed: Vec<String>,
+}
+
+impl Display for QueryDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Query diagnostics:")?;
+        for selector in &self.selectors {
+            write!(f, "\n    {selector}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SelectorDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} matched {} element(s)", self.selector, self.matched)?;
+        for filter in &self.filters {
+            write!(f, "\n        {filter}")?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, "\n        error: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for FilterDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: rejected {} of {}", self.description, self.rejected, self.candidates)?;
+        if !self.observed.is_empty() {
+            write!(f, " (observed: {})", self.observed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let diagnostics = QueryDiagnostics {
+            selectors: vec![
+                SelectorDiagnostics {
+                    selector: "Css(button)".to_string(),
+                    matched: 4,
+                    filters: vec![FilterDiagnostics {
+                        description: r#"has text "Save""#.to_string(),
+                        candidates: 4,
+                        rejected: 4,
+                        observed: vec![r#""Copy""#.to_string(), r#""Show alert""#.to_string()],
+                    }],
+                    error: None,
+                },
+                SelectorDiagnostics {
+                    selector: "Id(save)".to_string(),
+                    matched: 0,
+                    filters: Vec::new(),
+                    error: None,
+                },
+            ],
+        };
+        assert_eq!(
+            diagnostics.to_string(),
+            "Query diagnostics:\n    Css(button) matched 4 element(s)\n        \
+             has text \"Save\": rejected 4 of 4 (observed: \"Copy\", \"Show alert\")\n    \
+             Id(save) matched 0 element(s)"
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use super::conditions::handle_errors;
+use super::{ElementPollerOnMutation, ElementPollerWithTimeout, IntoElementPoller};
+use crate::error::{WebDriverError, WebDriverErrorInner};
+use crate::js::READ_PAGE_ACTIVITY;
+use crate::prelude::WebDriverResult;
+use crate::session::handle::SessionHandle;
+use crate::By;
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use stringmatch::Needle;
+
+/// The polling interval used by `SessionHandle::wait_for_network_idle()`.
+const NETWORK_IDLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The idle time used by `SessionHandle::wait_for_app_stable()`.
+const APP_STABLE_IDLE_TIME: Duration = Duration::from_millis(500);
+
+/// The in-flight activity on the page, as reported by `READ_PAGE_ACTIVITY`.
+#[derive(Debug, Deserialize)]
+struct PageActivity {
+    loaded: bool,
+    requests: u64,
+    timers: u64,
+    animations: u64,
+    framework_busy: bool,
+    network_idle_ms: u64,
+    dom_idle_ms: u64,
+    framework_idle_ms: u64,
+}
+
+impl PageActivity {
+    async fn read(handle: &Arc<SessionHandle>) -> WebDriverResult<Self> {
+        handle.execute(READ_PAGE_ACTIVITY, Vec::new()).await?.convert()
+    }
+
+    fn network_idle(&self, idle_time: Duration) -> bool {
+        self.loaded
+            && self.requests == 0
+            && Duration::from_millis(self.network_idle_ms) >= idle_time
+    }
+
+    fn app_stable(&self, idle_time: Duration) -> bool {
+        self.network_idle(idle_time)
+            && self.timers == 0
+            && self.animations == 0
+            && !self.framework_busy
+            && Duration::from_millis(self.dom_idle_ms) >= idle_time
+            && Duration::from_millis(self.framework_idle_ms) >= idle_time
+    }
+}
+
+/// High-level interface for waiting on the state of the browser (rather than a single
+/// element), using the builder pattern.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// driver.find(By::Id("button-login")).await?.click().await?;
+/// // Wait until the browser navigates to the dashboard.
+/// driver.wait_until().title_contains("Dashboard").await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DriverWaiter {
+    handle: Arc<SessionHandle>,
+    poller: Arc<dyn IntoElementPoller + Send + Sync>,
+    message: String,
+    ignore_errors: bool,
+}
+
+impl DriverWaiter {
+    /// Create a new `DriverWaiter`.
+    ///
+    /// See `WebDriver::wait_until()` rather than creating this directly.
+    pub fn new(
+        handle: Arc<SessionHandle>,
+        poller: Arc<dyn IntoElementPoller + Send + Sync>,
+    ) -> Self {
+        Self {
+            handle,
+            poller,
+            message: String::new(),
+            ignore_errors: true,
+        }
+    }
+
+    /// Use the specified ElementPoller for this DriverWaiter.
+    /// This will not affect the default ElementPoller used for other waits.
+    pub fn with_poller(mut self, poller: Arc<dyn IntoElementPoller + Send + Sync>) -> Self {
+        self.poller = poller;
+        self
+    }
+
+    /// Provide a human-readable error message to be returned in the case of timeout.
+    pub fn error(mut self, message: &str) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    /// By default, a waiter will ignore any errors that occur while polling for the desired
+    /// condition(s). However, this behaviour can be modified so that the waiter will return
+    /// early if an error is returned from thirtyfour.
+    pub fn ignore_errors(mut self, ignore: bool) -> Self {
+        self.ignore_errors = ignore;
+        self
+    }
+
+    /// Force this DriverWaiter to wait for the specified timeout, polling once
+    /// after each interval. This will override the poller for this
+    /// DriverWaiter only.
+    pub fn wait(self, timeout: Duration, interval: Duration) -> Self {
+        self.with_poller(Arc::new(ElementPollerWithTimeout::new(timeout, interval)))
+    }
+
+    /// Force this DriverWaiter to wait for the specified timeout, polling again whenever
+    /// the DOM changes (or at least once every `max_interval`). This will override the
+    /// poller for this DriverWaiter only.
+    ///
+    /// See `ElementPollerOnMutation` for details.
+    pub fn wait_on_mutation(self, timeout: Duration, max_interval: Duration) -> Self {
+        let poller = ElementPollerOnMutation::new(self.handle.clone(), timeout, max_interval);
+        self.with_poller(Arc::new(poller))
+    }
+
+    /// Wait for the specified condition to be true.
+    ///
+    /// The condition is called with the session handle on each poll, and any error it
+    /// returns is ignored unless `ignore_errors(false)` was set.
+    pub async fn condition<F, Fut>(self, f: F) -> WebDriverResult<()>
+    where
+        F: Fn(Arc<SessionHandle>) -> Fut + Send + Sync,
+        Fut: Future<Output = WebDriverResult<bool>> + Send,
+    {
+        let mut poller = self.poller.start();
+        loop {
+            if handle_errors(f(self.handle.clone()).await, self.ignore_errors)? {
+                return Ok(());
+            }
+
+            if !poller.tick().await {
+                return Err(WebDriverError::Timeout(format!(
+                    "driver condition timed out: {}",
+                    self.message
+                )));
+            }
+        }
+    }
+
+    /// Wait until the current URL matches the specified needle.
+    pub async fn url_matches<N>(self, url: N) -> WebDriverResult<()>
+    where
+        N: Needle + Send + Sync,
+    {
+        let url = &url;
+        self.condition(|handle| async move {
+            let current = handle.current_url().await?;
+            Ok(url.is_match(current.as_str()))
+        })
+        .await
+    }
+
+    /// Wait until the page title matches the specified needle.
+    pub async fn title_matches<N>(self, title: N) -> WebDriverResult<()>
+    where
+        N: Needle + Send + Sync,
+    {
+        let title = &title;
+        self.condition(|handle| async move { Ok(title.is_match(&handle.title().await?)) }).await
+    }
+
+    /// Wait until the page title contains the specified text.
+    pub async fn title_contains(self, text: impl Into<String>) -> WebDriverResult<()> {
+        let text = &text.into();
+        self.condition(|handle| async move { Ok(handle.title().await?.contains(text.as_str())) })
+            .await
+    }
+
+    /// Wait until an alert is open.
+    pub async fn alert_present(self) -> WebDriverResult<()> {
+        self.condition(|handle| async move {
+            match handle.get_alert_text().await {
+                Ok(_) => Ok(true),
+                Err(e) if matches!(*e, WebDriverErrorInner::NoSuchAlert(_)) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
+        .await
+    }
+
+    /// Wait until the session has the specified number of windows (or tabs) open.
+    pub async fn number_of_windows(self, count: usize) -> WebDriverResult<()> {
+        self.condition(|handle| async move { Ok(handle.windows().await?.len() == count) }).await
+    }
+
+    /// Wait until the specified script returns a truthy value (according to JavaScript).
+    ///
+    /// The script is run as the body of a function, so it should use `return` to return
+    /// the value.
+    pub async fn js_returns_truthy(self, script: impl Into<String>) -> WebDriverResult<()> {
+        let script: Arc<str> =
+            format!("return !!(function () {{\n{}\n}}).apply(this, arguments);", script.into())
+                .into();
+        let script = &script;
+        self.condition(|handle| async move {
+            handle.execute(script.clone(), Vec::new()).await?.convert::<bool>()
+        })
+        .await
+    }
+
+    /// Wait until the specified selector matches exactly the specified number of elements.
+    pub async fn element_count(self, by: By, count: usize) -> WebDriverResult<()> {
+        let by = &by;
+        self.condition(
+            |handle| async move { Ok(handle.find_all(by.clone()).await?.len() == count) },
+        )
+        .await
+    }
+
+    /// Wait until the page has loaded and no `fetch()` or `XMLHttpRequest` requests have been
+    /// in flight for at least `idle_time`.
+    ///
+    /// Requests are tracked by instrumenting the page the first time this runs in each
+    /// document, so requests started before that point are not seen. This means that the
+    /// `idle_time` is always measured from the first check at the earliest.
+    pub async fn network_idle(self, idle_time: Duration) -> WebDriverResult<()> {
+        self.condition(|handle| async move {
+            Ok(PageActivity::read(&handle).await?.network_idle(idle_time))
+        })
+        .await
+    }
+
+    /// Wait until the network has been idle (see `network_idle()`) and the DOM has not
+    /// changed for at least `idle_time`, and there are no pending short timers
+    /// (`setTimeout()` with a delay of up to 1 second), running finite animations or
+    /// pending framework work.
+    ///
+    /// Only the first run of each timer is tracked. Timers whose callback has run before,
+    /// or that are scheduled from another timer, are treated as recurring (e.g. polling or
+    /// clocks) and ignored.
+    ///
+    /// Framework work is detected via the testability API for Angular apps, and the
+    /// pending `$http` requests for AngularJS apps. React commits are detected via the
+    /// React DevTools hook (`__REACT_DEVTOOLS_GLOBAL_HOOK__`), which React only uses if it
+    /// exists before React loads, e.g. when the React DevTools extension is installed.
+    /// Otherwise, React and other frameworks are covered by the DOM and timer checks.
+    pub async fn app_stable(self, idle_time: Duration) -> WebDriverResult<()> {
+        self.condition(|handle| async move {
+            Ok(PageActivity::read(&handle).await?.app_stable(idle_time))
+        })
+        .await
+    }
+}
+
+/// Trait for enabling the DriverWaiter interface.
+pub trait DriverWaitable {
+    /// Wait until the browser meets one or more conditions.
+    fn wait_until(&self) -> DriverWaiter;
+}
+
+impl DriverWaitable for Arc<SessionHandle> {
+    /// Return a DriverWaiter instance for executing explicit waits on the browser state.
+    ///
+    /// This uses the builder pattern to construct explicit waits using one of the
+    /// provided conditions. Or you can provide your own custom condition if desired.
+    ///
+    /// See [`DriverWaiter`] for more documentation.
+    fn wait_until(&self) -> DriverWaiter {
+        DriverWaiter::new(self.clone(), self.config().poller.clone())
+    }
+}
+
+impl SessionHandle {
+    /// Wait until the page has loaded and no `fetch()` or `XMLHttpRequest` requests have
+    /// been in flight for at least `idle_time`, or return a timeout error after `timeout`.
+    ///
+    /// Unlike the page load strategy, this also covers requests made after the `load`
+    /// event. See `DriverWaiter::network_idle()` for details.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// # use std::time::Duration;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// driver.goto("https://www.rust-lang.org").await?;
+    /// driver.wait_for_network_idle(Duration::from_millis(500), Duration::from_secs(10)).await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub async fn wait_for_network_idle(
+        self: &Arc<Self>,
+        idle_time: Duration,
+        timeout: Duration,
+    ) -> WebDriverResult<()> {
+        self.wait_until()
+            .wait(timeout, NETWORK_IDLE_INTERVAL)
+            .error("network did not become idle")
+            .network_idle(idle_time)
+            .await
+    }
+
+    /// Wait until there is no in-flight network, DOM, timer, animation or framework
+    /// activity on the page, using the configured poller.
+    ///
+    /// See `DriverWaiter::app_stable()` for details, and to customize the idle time.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// driver.find(By::Id("button-search")).await?.click().await?;
+    /// driver.wait_for_app_stable().await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub async fn wait_for_app_stable(self: &Arc<Self>) -> WebDriverResult<()> {
+        self.wait_until().error("app did not become stable").app_stable(APP_STABLE_IDLE_TIME).await
+    }
+}
+
+#[cfg(test)]
+/// This function checks if the public async methods implement Send. It is not intended to be executed.
+async fn _test_is_send() -> WebDriverResult<()> {
+    use crate::prelude::*;
+
+    // Helper methods
+    fn is_send_val<T: Send>(_val: &T) {}
+
+    // Pre values
+    let caps = DesiredCapabilities::chrome();
+    let driver = WebDriver::new("http://localhost:4444", caps).await?;
+
+    // DriverWaitCondition
+    is_send_val(&driver.wait_until().url_matches("https://example.com/"));
+    is_send_val(&driver.wait_until().title_contains("Example"));
+    is_send_val(&driver.wait_until().alert_present());
+    is_send_val(&driver.wait_until().number_of_windows(2));
+    is_send_val(&driver.wait_until().js_returns_truthy("return true;"));
+    is_send_val(&driver.wait_until().element_count(By::Tag("div"), 2));
+    is_send_val(&driver.wait_until().network_idle(Duration::from_millis(500)));
+    is_send_val(&driver.wait_until().app_stable(Duration::from_millis(500)));
+    is_send_val(&driver.wait_for_network_idle(Duration::from_millis(500), Duration::from_secs(5)));
+    is_send_val(&driver.wait_for_app_stable());
+    is_send_val(
+        &driver
+            .wait_until()
+            .condition(|handle| async move { Ok(handle.title().await?.is_empty()) }),
+    );
+
+    Ok(())
+}
@@ -1,12 +1,21 @@
+use super::batch::{filter_elements_batched, read_filter_values, BatchFilter, Probe, ProbeValue};
 use super::conditions::{collect_arg_slice, handle_errors, negate};
-use super::{conditions, ElementPollerNoWait, ElementPollerWithTimeout, IntoElementPoller};
+use super::diagnostics::MAX_OBSERVED_VALUES;
+use super::relative::{filter_relative, Relation};
+use super::{
+    conditions, sort_elements, ElementOrder, ElementPollerNoWait, ElementPollerOnMutation,
+    ElementPollerWithTimeout, FilterDiagnostics, IntoElementPoller, QueryDiagnostics,
+    SelectorDiagnostics,
+};
 use crate::error::{WebDriverError, WebDriverErrorInner};
 use crate::prelude::WebDriverResult;
 use crate::session::handle::SessionHandle;
 use crate::IntoArcStr;
-use crate::{By, DynElementPredicate, ElementPredicate, WebElement};
+use crate::{By, DynElementPredicate, ElementId, ElementPredicate, ElementQueryFn, WebElement};
+use futures_util::future::BoxFuture;
 use indexmap::IndexMap;
 use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fmt::{Debug, Display, Formatter, Write};
 use std::sync::Arc;
 use std::time::Duration;
@@ -18,11 +27,27 @@ fn get_selector_summary(selectors: &[ElementSelector]) -> String {
 
     impl Display for Criteria<'_> {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-            for (i, by) in self.0.iter().map(|s| &s.by).enumerate() {
+            for (i, selector) in self.0.iter().enumerate() {
                 if i != 0 {
                     f.write_char(',')?
                 }
-                Display::fmt(by, f)?;
+                Display::fmt(&selector.by, f)?;
+                for relation in &selector.relations {
+                    write!(f, " {relation}")?;
+                }
+                for (i, filter) in selector.filters.iter().enumerate() {
+                    let description = filter.description();
+                    let description = description.as_deref().unwrap_or("custom filter");
+                    write!(
+                        f,
+                        " {} {description}",
+                        if i == 0 {
+                            "where"
+                        } else {
+                            "and"
+                        }
+                    )?;
+                }
             }
             Ok(())
         }
@@ -31,7 +56,7 @@ fn get_selector_summary(selectors: &[ElementSelector]) -> String {
     format!("[{}]", Criteria(selectors))
 }
 
-fn get_elements_description(len: Option<usize>, description: &str) -> Cow<str> {
+fn get_elements_description(len: Option<usize>, description: &str) -> Cow<'_, str> {
     let suffix = match len {
         Some(1) => "element",
         Some(_) => "elements",
@@ -44,6 +69,48 @@ fn get_elements_description(len: Option<usize>, description: &str) -> Cow<str> {
     }
 }
 
+/// Check the values read for a filter with multiple attributes or properties.
+///
+/// Like the corresponding conditions, missing values never match, even when checking that
+/// an element does not have the specified value.
+fn all_match<N: Needle>(desired: &[(Arc<str>, N)], values: &[ProbeValue], expected: bool) -> bool {
+    desired
+        .iter()
+        .zip(values)
+        .all(|((_, needle), value)| value.opt().is_some_and(|x| needle.is_match(x) == expected))
+}
+
+/// Describe the values read for a rejected element, for `FilterDiagnostics::observed`.
+fn describe_values(values: &[ProbeValue]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    values.join(" / ")
+}
+
+/// The selector used to find frames when searching nested frames.
+fn frame_by() -> By {
+    By::Css("iframe, frame")
+}
+
+/// Treat errors caused by a nested frame disappearing (or navigating away) during a frame
+/// search as that frame containing no elements.
+fn ignore_missing_frame<T>(result: WebDriverResult<T>, nested: bool) -> WebDriverResult<Option<T>> {
+    match result {
+        Ok(x) => Ok(Some(x)),
+        Err(e)
+            if nested
+                && matches!(
+                    *e,
+                    WebDriverErrorInner::NoSuchFrame(_)
+                        | WebDriverErrorInner::NoSuchElement(_)
+                        | WebDriverErrorInner::StaleElementReference(_)
+                ) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
 /// Helper function to return the NoSuchElement error struct.
 fn no_such_element(selectors: &[ElementSelector], description: &str) -> WebDriverError {
     let element_description = get_elements_description(None, description);
@@ -55,7 +122,7 @@ fn no_such_element(selectors: &[ElementSelector], description: &str) -> WebDrive
 }
 
 /// Filter the specified elements using the specified filters.
-pub async fn filter_elements<'a, I, P, Ref>(
+pub async fn filter_elements<I, P, Ref>(
     mut elements: Vec<WebElement>,
     filters: I,
 ) -> WebDriverResult<Vec<WebElement>>
@@ -80,19 +147,32 @@ where
     Ok(elements)
 }
 
-/// An ElementSelector contains a selector method (By) as well as zero or more filters.
-/// The filters will be applied to any elements matched by the selector.
-/// Selectors and filters all run in full on every poll iteration.
+/// An ElementSelector contains a selector method (By) as well as zero or more relations
+/// and filters.
+/// The relations and then the filters (in the order they were added) will be applied to
+/// any elements matched by the selector.
+/// Selectors, relations and filters all run in full on every poll iteration.
 pub struct ElementSelector {
     /// The selector to use.
     pub by: By,
-    /// The filters for this element selector.
+    /// The positions relative to other elements that matched elements must have.
+    pub relations: Vec<Relation>,
+    /// The filters for this element selector, including the built-in filters.
     pub filters: Vec<Box<DynElementPredicate>>,
+    /// The built-in filter for each of the `filters`, or `None` for other filters.
+    ///
+    /// Adjacent built-in filters can be checked with a single script. See
+    /// `ElementQuery::batch_filters()` for details.
+    pub batch_filters: Vec<Option<BatchFilter>>,
 }
 
 impl Debug for ElementSelector {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ElementSelector").field("by", &self.by).finish()
+        f.debug_struct("ElementSelector")
+            .field("by", &self.by)
+            .field("relations", &self.relations)
+            .field("batch_filters", &self.batch_filters)
+            .finish()
     }
 }
 
@@ -101,10 +181,29 @@ impl ElementSelector {
     pub fn new(by: By) -> Self {
         Self {
             by,
+            relations: Vec::new(),
             filters: Vec::new(),
+            batch_filters: Vec::new(),
         }
     }
 
+    /// Add the specified built-in filter to the list of filters for this selector.
+    pub fn add_batch_filter(&mut self, f: BatchFilter) {
+        self.batch_filters.resize(self.filters.len(), None);
+        self.filters.push(DynElementPredicate::boxed(f.clone()));
+        self.batch_filters.push(Some(f));
+    }
+
+    /// The built-in filter for the filter at the specified index, if any.
+    fn batch_filter(&self, index: usize) -> Option<&BatchFilter> {
+        self.batch_filters.get(index).and_then(Option::as_ref)
+    }
+
+    /// Add the specified relation to the list of relations for this selector.
+    pub fn add_relation(&mut self, relation: Relation) {
+        self.relations.push(relation);
+    }
+
     /// Add the specified filter to the list of filters for this selector.
     pub fn add_filter(&mut self, f: impl ElementPredicate + 'static) {
         self.add_box_filter(DynElementPredicate::boxed(f));
@@ -112,15 +211,60 @@ impl ElementSelector {
 
     /// Add the specified filter to the list of filters for this selector.
     pub fn add_box_filter(&mut self, f: Box<DynElementPredicate>) {
+        self.batch_filters.resize(self.filters.len(), None);
         self.filters.push(f);
+        self.batch_filters.push(None);
+    }
+}
+
+/// A filter belonging to a selector that is shared between clones of an `ElementQuery`.
+struct SharedFilter {
+    selectors: Arc<Vec<ElementSelector>>,
+    selector: usize,
+    filter: usize,
+}
+
+impl ElementQueryFn<bool> for SharedFilter {
+    type Fut = BoxFuture<'static, WebDriverResult<bool>>;
+
+    fn call(&self, arg: WebElement) -> Self::Fut {
+        self.selectors[self.selector].filters[self.filter].call(arg)
+    }
+
+    fn description(&self) -> Option<String> {
+        self.selectors[self.selector].filters[self.filter].description()
     }
 }
 
+/// Copy the specified shared selectors, so that they can be modified.
+///
+/// Filters cannot be cloned, so the copies refer to the filters in the shared selectors.
+fn copy_selectors(selectors: &Arc<Vec<ElementSelector>>) -> Vec<ElementSelector> {
+    selectors
+        .iter()
+        .enumerate()
+        .map(|(i, selector)| ElementSelector {
+            by: selector.by.clone(),
+            relations: selector.relations.clone(),
+            batch_filters: selector.batch_filters.clone(),
+            filters: (0..selector.filters.len())
+                .map(|filter| {
+                    DynElementPredicate::boxed(SharedFilter {
+                        selectors: Arc::clone(selectors),
+                        selector: i,
+                        filter,
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
 /// Elements can be queried from either a WebDriver or from a WebElement.
 /// The command issued to the webdriver will differ depending on the source,
 /// i.e. FindElement vs FindElementFromElement etc. but the ElementQuery
 /// interface is the same for both.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ElementQuerySource {
     /// Execute a query from the `WebDriver` instance.
     Driver(Arc<SessionHandle>),
@@ -129,9 +273,10 @@ pub enum ElementQuerySource {
 }
 
 /// Options for wait characteristics for an element query.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum ElementQueryWaitOptions {
     /// Use the default poller.
+    #[default]
     WaitDefault,
     /// Use a poller with the specified timeout and interval.
     Wait {
@@ -144,10 +289,19 @@ pub enum ElementQueryWaitOptions {
     NoWait,
 }
 
-impl Default for ElementQueryWaitOptions {
-    fn default() -> Self {
-        Self::WaitDefault
-    }
+/// Which frames to search in an element query.
+///
+/// Elements found inside a frame are bound to that frame, so that any later commands
+/// for those elements switch into the frame automatically, and then back to the frame that
+/// was current before. Because of this, frame searches always start from the top-level
+/// document (or, for `WebElement::query()`, from the frame containing that element).
+#[derive(Debug, Clone)]
+pub enum FrameSearch {
+    /// Search the document, and then every frame within it, recursively.
+    Any,
+    /// Search only the frame found by following the specified selectors, one for each
+    /// level of nesting. Every frame matching a selector is searched.
+    Path(Vec<By>),
 }
 
 /// All options applicable to an ElementQuery.
@@ -160,6 +314,10 @@ pub struct ElementQueryOptions {
     ignore_errors: Option<bool>,
     description: Option<Arc<str>>,
     wait: Option<ElementQueryWaitOptions>,
+    frames: Option<FrameSearch>,
+    batch_filters: Option<bool>,
+    diagnostics: Option<bool>,
+    order: Option<ElementOrder>,
 }
 
 impl ElementQueryOptions {
@@ -198,6 +356,54 @@ impl ElementQueryOptions {
         self.wait = wait_option;
         self
     }
+
+    /// Set which frames to search for this element query.
+    pub fn frames(mut self, frames: FrameSearch) -> Self {
+        self.frames = Some(frames);
+        self
+    }
+
+    /// Set which frames to search for this element query.
+    pub fn set_frames(mut self, frames: Option<FrameSearch>) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Set whether to check the built-in filters for all elements with a single script.
+    pub fn batch_filters(mut self, batch: bool) -> Self {
+        self.batch_filters = Some(batch);
+        self
+    }
+
+    /// Set whether to check the built-in filters for all elements with a single script.
+    pub fn set_batch_filters(mut self, batch: Option<bool>) -> Self {
+        self.batch_filters = batch;
+        self
+    }
+
+    /// Set whether to attach a `QueryDiagnostics` report when the query finds nothing.
+    pub fn diagnostics(mut self, diagnostics: bool) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Set whether to attach a `QueryDiagnostics` report when the query finds nothing.
+    pub fn set_diagnostics(mut self, diagnostics: Option<bool>) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    /// Set the order of the elements returned by this element query.
+    pub fn order(mut self, order: ElementOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    /// Set the order of the elements returned by this element query.
+    pub fn set_order(mut self, order: Option<ElementOrder>) -> Self {
+        self.order = order;
+        self
+    }
 }
 
 /// High-level interface for performing powerful element queries using a
@@ -223,19 +429,18 @@ impl ElementQueryOptions {
 /// #     })
 /// # }
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ElementQuery {
     source: ElementQuerySource,
     poller: Arc<dyn IntoElementPoller + Send + Sync>,
-    selectors: Vec<ElementSelector>,
+    selectors: Arc<Vec<ElementSelector>>,
     options: ElementQueryOptions,
 }
 
 macro_rules! disallow_empty {
     ($elements: expr, $self: expr) => {
         if $elements.is_empty() {
-            let desc: &str = $self.options.description.as_deref().unwrap_or("");
-            Err(no_such_element(&$self.selectors, desc))
+            Err($self.not_found().await)
         } else {
             Ok($elements)
         }
@@ -256,7 +461,7 @@ impl ElementQuery {
         Self {
             source,
             poller,
-            selectors: vec![selector],
+            selectors: Arc::new(vec![selector]),
             options: ElementQueryOptions::default(),
         }
     }
@@ -292,6 +497,77 @@ impl ElementQuery {
         self
     }
 
+    /// Check the built-in filters (`with_text()`, `with_class()`, `and_displayed()` etc.)
+    /// for all candidate elements with a single script per poll iteration, rather than
+    /// sending separate WebDriver commands for every filter and element.
+    ///
+    /// This is much faster when a selector matches many elements, but the values are read
+    /// via JavaScript rather than WebDriver, so they can differ slightly. In particular,
+    /// text is read via `innerText`, attributes via `getAttribute()` (so boolean attributes
+    /// are not reported as `"true"`), CSS values are not normalized (e.g. colors are not
+    /// converted to `rgba()`), and visibility is approximated.
+    ///
+    /// If the script fails (for example, because an element became stale), the filters
+    /// are run normally instead. Custom filters added via `with_filter()` always run normally.
+    ///
+    /// The default is set via `WebDriverConfigBuilder::batch_query_filters()`.
+    pub fn batch_filters(mut self, batch: bool) -> Self {
+        self.options = self.options.batch_filters(batch);
+        self
+    }
+
+    /// Attach a `QueryDiagnostics` report to the `NoSuchElement` error returned when
+    /// `first()`, `single()`, `any_required()` or `all_from_selector_required()` find
+    /// nothing. The report is available via `WebDriverError::query_diagnostics()`, and is
+    /// also included in the error message.
+    ///
+    /// Collecting the report runs each selector, relation and filter once more after the
+    /// query has failed, which sends additional WebDriver commands, so it is disabled by
+    /// default.
+    ///
+    /// The default is set via `WebDriverConfigBuilder::query_diagnostics()`.
+    pub fn diagnostics(mut self, diagnostics: bool) -> Self {
+        self.options = self.options.diagnostics(diagnostics);
+        self
+    }
+
+    /// Set the order of the elements returned by this query. This affects all retrievers,
+    /// including which element is returned by `first()`, `nth()` and `last()`.
+    /// With an order other than `ElementOrder::Selector`, `first()` processes all selectors
+    /// rather than returning the elements from the first selector that matches.
+    ///
+    /// By default, the elements matched by each `or()` branch are returned after those
+    /// matched by the previous branches (see `ElementOrder::Selector`). Sorting the
+    /// elements requires an additional script execution (per frame) once the query has
+    /// finished polling.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// use thirtyfour::extensions::query::ElementOrder;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// // The headings and paragraphs, interleaved as they appear in the page.
+    /// let elems = driver
+    ///     .query(By::Tag("h2"))
+    ///     .or(By::Tag("p"))
+    ///     .order_by(ElementOrder::Document)
+    ///     .any_required()
+    ///     .await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn order_by(mut self, order: ElementOrder) -> Self {
+        self.options = self.options.order(order);
+        self
+    }
+
     //
     // Poller / Waiter
     //
@@ -310,12 +586,69 @@ impl ElementQuery {
         self.with_poller(Arc::new(ElementPollerWithTimeout::new(timeout, interval)))
     }
 
+    /// Force this ElementQuery to wait for the specified timeout, polling again whenever
+    /// the DOM changes (or at least once every `max_interval`). This will override the
+    /// poller for this ElementQuery only.
+    ///
+    /// See `ElementPollerOnMutation` for details.
+    pub fn wait_on_mutation(self, timeout: Duration, max_interval: Duration) -> Self {
+        let poller = ElementPollerOnMutation::new(self.handle().clone(), timeout, max_interval);
+        self.with_poller(Arc::new(poller))
+    }
+
     /// Force this ElementQuery to not wait for the specified condition(s).
     /// This will override the poller for this ElementQuery only.
     pub fn nowait(self) -> Self {
         self.with_poller(Arc::new(ElementPollerNoWait))
     }
 
+    //
+    // Frames
+    //
+
+    /// Search the document and every frame within it (recursively) for elements.
+    ///
+    /// Any elements found are bound to their frame, so that later commands for these
+    /// elements switch into the frame automatically. See [`FrameSearch`] for details.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// let card_number = driver.query(By::Name("cardnumber")).in_any_frame().first().await?;
+    /// // This switches into the frame, and back again afterwards.
+    /// card_number.send_keys("4242424242424242").await?;
+    ///
+    /// let editor = driver
+    ///     .query(By::Css("body[contenteditable]"))
+    ///     .in_frame_path([By::Id("editor-frame")])
+    ///     .first()
+    ///     .await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn in_any_frame(mut self) -> Self {
+        self.options = self.options.frames(FrameSearch::Any);
+        self
+    }
+
+    /// Search only within the frame found by following the specified frame selectors,
+    /// one for each level of nesting.
+    ///
+    /// Any elements found are bound to their frame, so that later commands for these
+    /// elements switch into the frame automatically. See [`FrameSearch`] for details.
+    pub fn in_frame_path(mut self, frames: impl IntoIterator<Item = By>) -> Self {
+        self.options = self.options.frames(FrameSearch::Path(frames.into_iter().collect()));
+        self
+    }
+
     //
     // Selectors
     //
@@ -323,10 +656,18 @@ impl ElementQuery {
     /// Add the specified selector to this ElementQuery. Callers should use
     /// the `or()` method instead.
     fn add_selector(mut self, selector: ElementSelector) -> Self {
-        self.selectors.push(selector);
+        self.selectors_mut().push(selector);
         self
     }
 
+    /// The selectors for this query, copying them first if they are shared with a clone.
+    fn selectors_mut(&mut self) -> &mut Vec<ElementSelector> {
+        if Arc::get_mut(&mut self.selectors).is_none() {
+            self.selectors = Arc::new(copy_selectors(&self.selectors));
+        }
+        Arc::get_mut(&mut self.selectors).expect("selectors should not be shared")
+    }
+
     /// Add a new selector to this ElementQuery. All conditions specified after
     /// this selector (up until the next `or()` method) will apply to this
     /// selector.
@@ -340,13 +681,13 @@ impl ElementQuery {
 
     /// Return true if an element matches any selector (including filters), otherwise false.
     pub async fn exists(&self) -> WebDriverResult<bool> {
-        let elements = self.run_poller(true, false).await?;
+        let elements = self.run_poller(true, |n| n > 0).await?;
         Ok(!elements.is_empty())
     }
 
     /// Return true if no element matches any selector (including filters), otherwise false.
     pub async fn not_exists(&self) -> WebDriverResult<bool> {
-        let elements = self.run_poller(false, true).await?;
+        let elements = self.run_poller(false, |n| n == 0).await?;
         Ok(elements.is_empty())
     }
 
@@ -354,19 +695,20 @@ impl ElementQuery {
     ///
     /// Returns None if no elements match.
     pub async fn first_opt(&self) -> WebDriverResult<Option<WebElement>> {
-        let elements = self.run_poller(true, false).await?;
-        Ok(elements.into_iter().next())
+        // When sorting, the first element could have been matched by any selector.
+        let short_circuit = matches!(self.options.order, None | Some(ElementOrder::Selector));
+        let elements = self.run_poller(short_circuit, |n| n > 0).await?;
+        Ok(elements.into_iter().next().map(|e| e.with_query_locator(self, false)))
     }
 
     /// Return only the first WebElement that matches any selector (including filters).
     ///
     /// Returns Err(WebDriverError::NoSuchElement) if no elements match.
     pub async fn first(&self) -> WebDriverResult<WebElement> {
-        self.first_opt().await?.ok_or_else(|| {
-            let desc: &str = self.options.description.as_deref().unwrap_or("");
-            let err = no_such_element(&self.selectors, desc);
-            err
-        })
+        match self.first_opt().await? {
+            Some(element) => Ok(element),
+            None => Err(self.not_found().await),
+        }
     }
 
     /// Return only a single WebElement that matches any selector (including filters).
@@ -382,10 +724,10 @@ impl ElementQuery {
     /// By requiring that only one element is matched, you can be more sure that it is the
     /// one you intended.
     pub async fn single(&self) -> WebDriverResult<WebElement> {
-        let mut elements = self.run_poller(false, false).await?;
+        let mut elements = self.run_poller(false, |n| n > 0).await?;
 
         if elements.len() == 1 {
-            Ok(elements.swap_remove(0))
+            Ok(elements.swap_remove(0).with_query_locator(self, true))
         } else if !elements.is_empty() {
             let element_description = get_elements_description(
                 Some(elements.len()),
@@ -398,9 +740,7 @@ impl ElementQuery {
                 selectors = get_selector_summary(&self.selectors)
             )))
         } else {
-            let desc: &str = self.options.description.as_deref().unwrap_or("");
-            let err = no_such_element(&self.selectors, desc);
-            Err(err)
+            Err(self.not_found().await)
         }
     }
 
@@ -410,7 +750,7 @@ impl ElementQuery {
     ///
     /// Returns an empty Vec if no elements match.
     pub async fn any(&self) -> WebDriverResult<Vec<WebElement>> {
-        self.run_poller(false, false).await
+        self.run_poller(false, |n| n > 0).await
     }
 
     /// Return all WebElements that match any selector (including filters).
@@ -419,7 +759,7 @@ impl ElementQuery {
     ///
     /// Returns Err(WebDriverError::NoSuchElement) if no elements match.
     pub async fn any_required(&self) -> WebDriverResult<Vec<WebElement>> {
-        let elements = self.run_poller(false, false).await?;
+        let elements = self.run_poller(false, |n| n > 0).await?;
         disallow_empty!(elements, self)
     }
 
@@ -436,7 +776,7 @@ impl ElementQuery {
     ///
     /// Returns an empty Vec if no elements match.
     pub async fn all_from_selector(&self) -> WebDriverResult<Vec<WebElement>> {
-        self.run_poller(true, false).await
+        self.run_poller(true, |n| n > 0).await
     }
 
     /// Return all WebElements that match any single selector (including filters).
@@ -452,11 +792,109 @@ impl ElementQuery {
     ///
     /// Returns Err(WebDriverError::NoSuchElement) if no elements match.
     pub async fn all_from_selector_required(&self) -> WebDriverResult<Vec<WebElement>> {
-        let elements = self.run_poller(true, false).await?;
+        let elements = self.run_poller(true, |n| n > 0).await?;
         disallow_empty!(elements, self)
     }
 
-    /// Run the poller for this ElementQuery and return the Vec of WebElements matched.
+    /// Return exactly the specified number of WebElements that match any selector
+    /// (including filters).
+    ///
+    /// This will wait until exactly `count` elements are found, after processing all
+    /// selectors, e.g. to wait until a table has finished loading all of its rows.
+    ///
+    /// Returns Err(WebDriverError::NoSuchElement) if the number of elements found was not
+    /// equal to `count` when the query timed out.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// let rows = driver.query(By::Css("table#results tbody tr")).exactly(5).await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub async fn exactly(&self, count: usize) -> WebDriverResult<Vec<WebElement>> {
+        let elements = self.run_poller(false, |n| n == count).await?;
+        if elements.len() == count {
+            Ok(elements)
+        } else if elements.is_empty() {
+            Err(self.not_found().await)
+        } else {
+            Err(self.wrong_count(elements.len(), format_args!("exactly {count}")))
+        }
+    }
+
+    /// Return all WebElements that match any selector (including filters), once at least
+    /// the specified number of elements have been found, after processing all selectors.
+    ///
+    /// Returns Err(WebDriverError::NoSuchElement) if fewer than `count` elements were found
+    /// when the query timed out.
+    pub async fn at_least(&self, count: usize) -> WebDriverResult<Vec<WebElement>> {
+        let elements = self.run_poller(false, |n| n >= count).await?;
+        if elements.len() >= count {
+            Ok(elements)
+        } else if elements.is_empty() {
+            Err(self.not_found().await)
+        } else {
+            Err(self.wrong_count(elements.len(), format_args!("at least {count}")))
+        }
+    }
+
+    /// Return the WebElement at the specified (zero-based) index among all WebElements that
+    /// match any selector (including filters).
+    ///
+    /// This will wait until more than `index` elements are found, after processing all
+    /// selectors. See `order_by()` to control the order of the elements.
+    ///
+    /// Returns Err(WebDriverError::NoSuchElement) if there were not enough elements when
+    /// the query timed out.
+    pub async fn nth(&self, index: usize) -> WebDriverResult<WebElement> {
+        let mut elements = self.run_poller(false, |n| n > index).await?;
+        if elements.len() > index {
+            Ok(elements.swap_remove(index))
+        } else if elements.is_empty() {
+            Err(self.not_found().await)
+        } else {
+            Err(self.wrong_count(elements.len(), format_args!("at least {}", index + 1)))
+        }
+    }
+
+    /// Return the last WebElement that matches any selector (including filters).
+    ///
+    /// This will return when at least one element is found, after processing all selectors.
+    /// See `order_by()` to control the order of the elements.
+    ///
+    /// Returns Err(WebDriverError::NoSuchElement) if no elements match.
+    pub async fn last(&self) -> WebDriverResult<WebElement> {
+        let mut elements = self.run_poller(false, |n| n > 0).await?;
+        match elements.pop() {
+            Some(element) => Ok(element),
+            None => Err(self.not_found().await),
+        }
+    }
+
+    /// The NoSuchElement error for a query that found the wrong number of elements.
+    fn wrong_count(&self, found: usize, expected: impl Display) -> WebDriverError {
+        let element_description = get_elements_description(
+            Some(found),
+            self.options.description.as_deref().unwrap_or(""),
+        );
+        crate::error::no_such_element(format!(
+            "wrong number of elements received; expected {expected} but found {found} \
+             {element_description} using selectors: {selectors}",
+            selectors = get_selector_summary(&self.selectors)
+        ))
+    }
+
+    /// Run the poller for this ElementQuery and return the Vec of WebElements matched,
+    /// sorted according to the `ElementOrder` for this query.
     ///
     /// NOTE: This function doesn't return a no_such_element error and the caller must handle it.
     ///
@@ -465,14 +903,25 @@ impl ElementQuery {
     ///   - if true, return as soon as any selector meets the condition.
     ///     The elements returned will be only the elements from that selector.
     ///   - if false, only check the condition (and possibly return) after processing all selectors.
-    /// - `stop_on_miss`:
-    ///   - if true, the condition is true if no elements were found.
-    ///   - if false, the condition is true if at least one element was found.
+    /// - `done`: the condition, which is called with the number of elements found.
     ///
     async fn run_poller(
         &self,
         short_circuit: bool,
-        stop_on_miss: bool,
+        done: impl Fn(usize) -> bool,
+    ) -> WebDriverResult<Vec<WebElement>> {
+        let elements = self.poll_elements(short_circuit, done).await?;
+        match self.options.order {
+            Some(order) => sort_elements(self.handle(), elements, order).await,
+            None => Ok(elements),
+        }
+    }
+
+    /// Run the poller for `run_poller()`, without sorting the elements.
+    async fn poll_elements(
+        &self,
+        short_circuit: bool,
+        done: impl Fn(usize) -> bool,
     ) -> WebDriverResult<Vec<WebElement>> {
         let desc: &str = self.options.description.as_deref().unwrap_or("");
         let no_such_element_error = no_such_element(&self.selectors, desc);
@@ -480,25 +929,20 @@ impl ElementQuery {
             return Err(no_such_element_error);
         }
 
+        let batch = self.batch();
+
         // Start the poller.
         let mut poller = self.poller.start();
 
-        let mut elements = IndexMap::new();
         loop {
-            for selector in &self.selectors {
-                let mut new_elements =
-                    match self.fetch_elements_from_source(selector.by.clone()).await {
-                        Ok(x) => x,
-                        Err(e) if matches!(*e, WebDriverErrorInner::NoSuchElement(_)) => Vec::new(),
-                        Err(e) => return Err(e),
-                    };
-
-                if !new_elements.is_empty() {
-                    new_elements = filter_elements(new_elements, &selector.filters).await?;
-                }
+            // Only count the elements found by this poll attempt, so that elements removed
+            // from the page (or replaced by a re-render) since the last attempt are excluded.
+            let mut elements = IndexMap::new();
+            for selector in self.selectors.iter() {
+                let new_elements = self.select_elements(selector, batch).await?;
 
                 // Stop early?
-                if short_circuit && (stop_on_miss == new_elements.is_empty()) {
+                if short_circuit && done(new_elements.len()) {
                     return Ok(new_elements);
                 }
 
@@ -509,22 +953,289 @@ impl ElementQuery {
             }
 
             // Once all selectors have been processed, check if we have a match.
-            if stop_on_miss == elements.is_empty() {
+            if done(elements.len()) {
                 return Ok(elements.into_values().collect());
             }
 
-            // On timeout, return any elements found so far.
+            // On timeout, return the elements found by the last attempt.
             if !poller.tick().await {
                 return Ok(elements.into_values().collect());
             }
         }
     }
 
+    /// Run all selectors once, without polling, and return the WebElements matched,
+    /// excluding duplicates and sorted according to the `ElementOrder` for this query.
+    pub(crate) async fn select_all_once(&self) -> WebDriverResult<Vec<WebElement>> {
+        let batch = self.batch();
+        let mut elements = IndexMap::new();
+        for selector in self.selectors.iter() {
+            for element in self.select_elements(selector, batch).await? {
+                elements.insert(element.element_id(), element);
+            }
+        }
+
+        let elements = elements.into_values().collect();
+        match self.options.order {
+            Some(order) => sort_elements(self.handle(), elements, order).await,
+            None => Ok(elements),
+        }
+    }
+
+    /// Run the specified selector once, applying its relations and filters.
+    async fn select_elements(
+        &self,
+        selector: &ElementSelector,
+        batch: bool,
+    ) -> WebDriverResult<Vec<WebElement>> {
+        let mut elements = match self.fetch_elements_from_source(selector.by.clone()).await {
+            Ok(x) => x,
+            Err(e) if matches!(*e, WebDriverErrorInner::NoSuchElement(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        if !elements.is_empty() {
+            elements = filter_relative(self.handle(), elements, &selector.relations).await?;
+        }
+
+        // Apply the filters in order, checking each run of adjacent built-in filters with a
+        // single script if batching is enabled.
+        let is_batched = |i: usize| batch && selector.batch_filter(i).is_some();
+        let mut start = 0;
+        while start < selector.filters.len() && !elements.is_empty() {
+            let batched = is_batched(start);
+            let end = (start..selector.filters.len())
+                .find(|&i| is_batched(i) != batched)
+                .unwrap_or(selector.filters.len());
+            elements = if batched {
+                let filters: Vec<BatchFilter> =
+                    (start..end).filter_map(|i| selector.batch_filter(i).cloned()).collect();
+                filter_elements_batched(self.handle(), elements, &filters, true).await?
+            } else {
+                filter_elements(elements, &selector.filters[start..end]).await?
+            };
+            start = end;
+        }
+        Ok(elements)
+    }
+
+    /// Whether to check the built-in filters with a single script.
+    fn batch(&self) -> bool {
+        match self.options.batch_filters {
+            Some(batch) => batch,
+            None => self.handle().config().batch_query_filters,
+        }
+    }
+
+    /// The NoSuchElement error for this query, including diagnostics if enabled.
+    async fn not_found(&self) -> WebDriverError {
+        let desc: &str = self.options.description.as_deref().unwrap_or("");
+        let err = no_such_element(&self.selectors, desc);
+        let enabled = match self.options.diagnostics {
+            Some(enabled) => enabled,
+            None => self.handle().config().query_diagnostics,
+        };
+        if enabled {
+            err.with_query_diagnostics(self.diagnose().await)
+        } else {
+            err
+        }
+    }
+
+    /// Run each selector once, applying the relations and filters one at a time, and
+    /// report how many elements each of them rejected.
+    async fn diagnose(&self) -> QueryDiagnostics {
+        let mut diagnostics = QueryDiagnostics::default();
+        for selector in self.selectors.iter() {
+            let mut selector_diagnostics = SelectorDiagnostics {
+                selector: selector.by.to_string(),
+                matched: 0,
+                filters: Vec::new(),
+                error: None,
+            };
+            if let Err(e) = self.diagnose_selector(selector, &mut selector_diagnostics).await {
+                selector_diagnostics.error = Some(e.to_string());
+            }
+            diagnostics.selectors.push(selector_diagnostics);
+        }
+        diagnostics
+    }
+
+    /// Run a single selector for `diagnose()`, recording each stage as it completes.
+    async fn diagnose_selector(
+        &self,
+        selector: &ElementSelector,
+        diagnostics: &mut SelectorDiagnostics,
+    ) -> WebDriverResult<()> {
+        let mut elements = match self.fetch_elements_from_source(selector.by.clone()).await {
+            Ok(x) => x,
+            Err(e) if matches!(*e, WebDriverErrorInner::NoSuchElement(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        diagnostics.matched = elements.len();
+
+        if !selector.relations.is_empty() {
+            let candidates = elements.len();
+            elements = filter_relative(self.handle(), elements, &selector.relations).await?;
+            let relations: Vec<String> = selector.relations.iter().map(|r| r.to_string()).collect();
+            diagnostics.filters.push(FilterDiagnostics {
+                description: relations.join(" and "),
+                candidates,
+                rejected: candidates - elements.len(),
+                observed: Vec::new(),
+            });
+        }
+
+        let batch = self.batch();
+        for (index, custom) in selector.filters.iter().enumerate() {
+            let candidates = elements.len();
+            let Some(filter) = selector.batch_filter(index) else {
+                elements = filter_elements(elements, [custom]).await?;
+                diagnostics.filters.push(FilterDiagnostics {
+                    description: custom.description().unwrap_or_else(|| "custom filter".into()),
+                    candidates,
+                    rejected: candidates - elements.len(),
+                    observed: Vec::new(),
+                });
+                continue;
+            };
+            let predicate = filter.predicate();
+            let values = match batch {
+                true => read_filter_values(self.handle(), &elements, filter).await.ok(),
+                false => None,
+            };
+
+            let mut passed = Vec::new();
+            let mut rejected = Vec::new();
+            let mut observed = Vec::new();
+            for (i, element) in elements.into_iter().enumerate() {
+                let values = values.as_ref().map(|v| v[i].as_slice());
+                let matched = match values {
+                    Some(values) => filter.check(values),
+                    None => predicate.call(element.clone()).await?,
+                };
+                if matched {
+                    passed.push(element);
+                } else if observed.len() + rejected.len() < MAX_OBSERVED_VALUES {
+                    match values {
+                        Some(values) => observed.push(describe_values(values)),
+                        None => rejected.push(element),
+                    }
+                }
+            }
+            elements = passed;
+
+            // Without batching, only the values of the rejected elements are needed.
+            if !rejected.is_empty() {
+                if let Ok(values) = read_filter_values(self.handle(), &rejected, filter).await {
+                    observed.extend(values.iter().map(|v| describe_values(v)));
+                }
+            }
+
+            diagnostics.filters.push(FilterDiagnostics {
+                description: predicate.description().unwrap_or_else(|| "custom filter".into()),
+                candidates,
+                rejected: candidates - elements.len(),
+                observed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Switch this query to a single attempt, returning the poller it was using.
+    pub(crate) fn into_single_attempt(self) -> (Self, Arc<dyn IntoElementPoller + Send + Sync>) {
+        let poller = self.poller.clone();
+        (self.nowait(), poller)
+    }
+
+    /// A summary of the selectors in this query, for error messages.
+    pub(crate) fn selector_summary(&self) -> String {
+        get_selector_summary(&self.selectors)
+    }
+
+    /// The session handle used to execute this query.
+    pub(crate) fn handle(&self) -> &Arc<SessionHandle> {
+        match &self.source {
+            ElementQuerySource::Driver(driver) => driver,
+            ElementQuerySource::Element(element) => &element.handle,
+        }
+    }
+
     /// Execute the specified selector and return any matched WebElements.
     async fn fetch_elements_from_source(&self, by: By) -> WebDriverResult<Vec<WebElement>> {
-        match &self.source {
-            ElementQuerySource::Driver(driver) => driver.find_all(by).await,
-            ElementQuerySource::Element(element) => element.find_all(by).await,
+        let root = match &self.source {
+            ElementQuerySource::Driver(driver) => match &self.options.frames {
+                None => return driver.find_all(by).await,
+                Some(_) => None,
+            },
+            ElementQuerySource::Element(element) => Some(element),
+        };
+
+        let frames: Arc<[ElementId]> = root.map(|e| e.frame_path().into()).unwrap_or_default();
+        match &self.options.frames {
+            None => self.fetch_elements_in_frame(&frames, root, by).await,
+            Some(FrameSearch::Any) => {
+                let mut elements = Vec::new();
+                let mut scopes = VecDeque::from([(frames, root.cloned())]);
+                while let Some((frames, root)) = scopes.pop_front() {
+                    let nested = !frames.is_empty();
+                    let found = self.fetch_elements_in_frame(&frames, root.as_ref(), by.clone());
+                    match ignore_missing_frame(found.await, nested)? {
+                        Some(found) => elements.extend(found),
+                        None => continue,
+                    }
+
+                    let found = self.fetch_elements_in_frame(&frames, root.as_ref(), frame_by());
+                    for frame in ignore_missing_frame(found.await, nested)?.unwrap_or_default() {
+                        let path = frames.iter().cloned().chain([frame.element_id()]).collect();
+                        scopes.push_back((path, None));
+                    }
+                }
+                Ok(elements)
+            }
+            Some(FrameSearch::Path(path)) => {
+                let mut scopes = vec![(frames, root.cloned())];
+                for frame_by in path {
+                    let mut next = Vec::new();
+                    for (frames, root) in scopes {
+                        let found =
+                            self.fetch_elements_in_frame(&frames, root.as_ref(), frame_by.clone());
+                        for frame in ignore_missing_frame(found.await, !frames.is_empty())?
+                            .unwrap_or_default()
+                        {
+                            let path = frames.iter().cloned().chain([frame.element_id()]).collect();
+                            next.push((path, None));
+                        }
+                    }
+                    scopes = next;
+                }
+
+                let mut elements = Vec::new();
+                for (frames, root) in scopes {
+                    let found = self.fetch_elements_in_frame(&frames, root.as_ref(), by.clone());
+                    elements.extend(ignore_missing_frame(found.await, true)?.unwrap_or_default());
+                }
+                Ok(elements)
+            }
+        }
+    }
+
+    /// Execute the specified selector from the specified element, or from the document
+    /// inside the specified frame, and bind any matched WebElements to that frame.
+    async fn fetch_elements_in_frame(
+        &self,
+        frames: &Arc<[ElementId]>,
+        root: Option<&WebElement>,
+        by: By,
+    ) -> WebDriverResult<Vec<WebElement>> {
+        match root {
+            // Elements found from an element are already bound to its frame.
+            Some(root) => root.find_all(by).await,
+            None => {
+                let handle = self.handle();
+                let elements = handle.in_frame_path(frames, handle.find_all(by)).await?;
+                Ok(elements.into_iter().map(|e| e.in_frames(frames)).collect())
+            }
         }
     }
 
@@ -534,62 +1245,162 @@ impl ElementQuery {
 
     /// Add the specified ElementPredicate to the last selector.
     pub fn with_filter(mut self, f: impl ElementPredicate + 'static) -> Self {
-        if let Some(selector) = self.selectors.last_mut() {
+        if let Some(selector) = self.selectors_mut().last_mut() {
             selector.add_filter(f);
         }
         self
     }
 
+    //
+    // Relative selectors
+    //
+
+    /// Add the specified Relation to the last selector.
+    ///
+    /// All relations for a selector are checked using a single script execution per poll
+    /// iteration, before any other filters are applied. Elements that match are sorted by
+    /// their distance from the first anchor element, nearest first.
+    pub fn with_relation(mut self, relation: Relation) -> Self {
+        if let Some(selector) = self.selectors_mut().last_mut() {
+            selector.add_relation(relation);
+        }
+        self
+    }
+
+    /// Only match elements that are entirely above the specified element.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// let label = driver.query(By::Tag("label")).with_text("Email").first().await?;
+    /// let icon = driver.find(By::ClassName("email-icon")).await?;
+    /// let input = driver.query(By::Tag("input")).below(&label).near(&icon, 50).first().await?;
+    /// let heading = driver.query(By::Tag("h2")).above(&label).first().await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn above(self, element: &WebElement) -> Self {
+        self.with_relation(Relation::Above(element.clone()))
+    }
+
+    /// Only match elements that are entirely below the specified element.
+    pub fn below(self, element: &WebElement) -> Self {
+        self.with_relation(Relation::Below(element.clone()))
+    }
+
+    /// Only match elements that are entirely to the left of the specified element.
+    pub fn left_of(self, element: &WebElement) -> Self {
+        self.with_relation(Relation::LeftOf(element.clone()))
+    }
+
+    /// Only match elements that are entirely to the right of the specified element.
+    pub fn right_of(self, element: &WebElement) -> Self {
+        self.with_relation(Relation::RightOf(element.clone()))
+    }
+
+    /// Only match elements that are at most `distance` pixels away from the specified element.
+    pub fn near(self, element: &WebElement, distance: u32) -> Self {
+        self.with_relation(Relation::Near(element.clone(), distance))
+    }
+
     //
     // Advance selectors
     //
 
+    /// Add the specified built-in filter to the last selector.
+    fn with_batch_filter(mut self, f: BatchFilter) -> Self {
+        if let Some(selector) = self.selectors_mut().last_mut() {
+            selector.add_batch_filter(f);
+        }
+        self
+    }
+
     /// Only match elements that are enabled.
     pub fn and_enabled(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_enabled(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_enabled(ignore_errors),
+            vec![Probe::Enabled],
+            |v| v[0].flag(),
+        ))
     }
 
     /// Only match elements that are NOT enabled.
     pub fn and_not_enabled(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_not_enabled(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_not_enabled(ignore_errors),
+            vec![Probe::Enabled],
+            |v| !v[0].flag(),
+        ))
     }
 
     /// Only match elements that are selected.
     pub fn and_selected(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_selected(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_selected(ignore_errors),
+            vec![Probe::Selected],
+            |v| v[0].flag(),
+        ))
     }
 
     /// Only match elements that are NOT selected.
     pub fn and_not_selected(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_not_selected(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_not_selected(ignore_errors),
+            vec![Probe::Selected],
+            |v| !v[0].flag(),
+        ))
     }
 
     /// Only match elements that are displayed.
     pub fn and_displayed(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_displayed(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_displayed(ignore_errors),
+            vec![Probe::Displayed],
+            |v| v[0].flag(),
+        ))
     }
 
     /// Only match elements that are NOT displayed.
     pub fn and_not_displayed(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_not_displayed(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_not_displayed(ignore_errors),
+            vec![Probe::Displayed],
+            |v| !v[0].flag(),
+        ))
     }
 
     /// Only match elements that are clickable.
     pub fn and_clickable(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_clickable(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_clickable(ignore_errors),
+            vec![Probe::Displayed, Probe::Enabled],
+            |v| v[0].flag() && v[1].flag(),
+        ))
     }
 
     /// Only match elements that are NOT clickable.
     pub fn and_not_clickable(self) -> Self {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_is_not_clickable(ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_is_not_clickable(ignore_errors),
+            vec![Probe::Displayed, Probe::Enabled],
+            |v| !(v[0].flag() && v[1].flag()),
+        ))
     }
 
     //
@@ -603,7 +1414,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_text(text, ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_text(text.clone(), ignore_errors),
+            vec![Probe::Text],
+            move |v| text.is_match(v[0].text()),
+        ))
     }
 
     /// Only match elements that do not have the specified text.
@@ -613,7 +1428,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_text(text, ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_text(text.clone(), ignore_errors),
+            vec![Probe::Text],
+            move |v| !text.is_match(v[0].text()),
+        ))
     }
 
     /// Only match elements that have the specified id.
@@ -623,16 +1442,21 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(move |elem: WebElement| {
-            let id = id.clone();
-            async move {
-                match elem.id().await {
-                    Ok(Some(x)) => Ok(id.is_match(&x)),
-                    Ok(None) => Ok(false),
-                    Err(e) => handle_errors(Err(e), ignore_errors),
+        let needle = id.clone();
+        self.with_batch_filter(BatchFilter::new(
+            move |elem: WebElement| {
+                let id = id.clone();
+                async move {
+                    match elem.id().await {
+                        Ok(Some(x)) => Ok(id.is_match(&x)),
+                        Ok(None) => Ok(false),
+                        Err(e) => handle_errors(Err(e), ignore_errors),
+                    }
                 }
-            }
-        })
+            },
+            vec![Probe::Attribute("id".into())],
+            move |v| v[0].opt().is_some_and(|x| needle.is_match(x)),
+        ))
     }
 
     /// Only match elements that do not have the specified id.
@@ -642,16 +1466,21 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(move |elem: WebElement| {
-            let id = id.clone();
-            async move {
-                match elem.id().await {
-                    Ok(Some(x)) => Ok(!id.is_match(&x)),
-                    Ok(None) => Ok(true),
-                    Err(e) => handle_errors(Err(e), ignore_errors),
+        let needle = id.clone();
+        self.with_batch_filter(BatchFilter::new(
+            move |elem: WebElement| {
+                let id = id.clone();
+                async move {
+                    match elem.id().await {
+                        Ok(Some(x)) => Ok(!id.is_match(&x)),
+                        Ok(None) => Ok(true),
+                        Err(e) => handle_errors(Err(e), ignore_errors),
+                    }
                 }
-            }
-        })
+            },
+            vec![Probe::Attribute("id".into())],
+            move |v| v[0].opt().is_none_or(|x| !needle.is_match(x)),
+        ))
     }
 
     /// Only match elements that contain the specified class name.
@@ -661,7 +1490,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_class(class_name, ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_class(class_name.clone(), ignore_errors),
+            vec![Probe::Attribute("class".into())],
+            move |v| v[0].opt().is_some_and(|x| class_name.is_match(x)),
+        ))
     }
 
     /// Only match elements that do not contain the specified class name.
@@ -671,7 +1504,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_class(class_name, ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_class(class_name.clone(), ignore_errors),
+            vec![Probe::Attribute("class".into())],
+            move |v| v[0].opt().is_none_or(|x| !class_name.is_match(x)),
+        ))
     }
 
     /// Only match elements that have the specified tag.
@@ -681,12 +1518,20 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(move |elem: WebElement| {
-            let tag_name = tag_name.clone();
-            async move {
-                handle_errors(elem.tag_name().await.map(|x| tag_name.is_match(&x)), ignore_errors)
-            }
-        })
+        let needle = tag_name.clone();
+        self.with_batch_filter(BatchFilter::new(
+            move |elem: WebElement| {
+                let tag_name = tag_name.clone();
+                async move {
+                    handle_errors(
+                        elem.tag_name().await.map(|x| tag_name.is_match(&x)),
+                        ignore_errors,
+                    )
+                }
+            },
+            vec![Probe::TagName],
+            move |v| needle.is_match(v[0].text()),
+        ))
     }
 
     /// Only match elements that do not have the specified tag.
@@ -696,12 +1541,17 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(move |elem: WebElement| {
-            let tag_name = tag_name.clone();
-            async move {
-                negate(elem.tag_name().await.map(|x| tag_name.is_match(&x)), ignore_errors)
-            }
-        })
+        let needle = tag_name.clone();
+        self.with_batch_filter(BatchFilter::new(
+            move |elem: WebElement| {
+                let tag_name = tag_name.clone();
+                async move {
+                    negate(elem.tag_name().await.map(|x| tag_name.is_match(&x)), ignore_errors)
+                }
+            },
+            vec![Probe::TagName],
+            move |v| !needle.is_match(v[0].text()),
+        ))
     }
 
     /// Only match elements that have the specified value.
@@ -711,7 +1561,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_value(value, ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_value(value.clone(), ignore_errors),
+            vec![Probe::Property("value".into())],
+            move |v| v[0].opt().is_some_and(|x| value.is_match(x)),
+        ))
     }
 
     /// Only match elements that do not have the specified value.
@@ -721,7 +1575,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_value(value, ignore_errors))
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_value(value.clone(), ignore_errors),
+            vec![Probe::Property("value".into())],
+            move |v| v[0].opt().is_none_or(|x| !value.is_match(x)),
+        ))
     }
 
     /// Only match elements that have the specified attribute with the specified value.
@@ -732,10 +1590,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_attribute(
-            attribute_name.into(),
-            value,
-            ignore_errors,
+        let name = attribute_name.into();
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_attribute(name.clone(), value.clone(), ignore_errors),
+            vec![Probe::Attribute(name)],
+            move |v| v[0].opt().is_some_and(|x| value.is_match(x)),
         ))
     }
 
@@ -747,10 +1606,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_attribute(
-            attribute_name.into(),
-            value,
-            ignore_errors,
+        let name = attribute_name.into();
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_attribute(name.clone(), value.clone(), ignore_errors),
+            vec![Probe::Attribute(name)],
+            move |v| v[0].opt().is_some_and(|x| !value.is_match(x)),
         ))
     }
 
@@ -762,9 +1622,11 @@ impl ElementQuery {
         N: Needle + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_attributes(
-            collect_arg_slice(desired_attributes),
-            ignore_errors,
+        let desired = collect_arg_slice(desired_attributes);
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_attributes(desired.clone(), ignore_errors),
+            desired.iter().map(|(name, _)| Probe::Attribute(name.clone())).collect(),
+            move |v| all_match(&desired, v, true),
         ))
     }
 
@@ -779,9 +1641,11 @@ impl ElementQuery {
         N: Needle + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_attributes(
-            collect_arg_slice(desired_attributes),
-            ignore_errors,
+        let desired = collect_arg_slice(desired_attributes);
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_attributes(desired.clone(), ignore_errors),
+            desired.iter().map(|(name, _)| Probe::Attribute(name.clone())).collect(),
+            move |v| all_match(&desired, v, false),
         ))
     }
 
@@ -793,10 +1657,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_property(
-            property_name.into(),
-            value,
-            ignore_errors,
+        let name = property_name.into();
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_property(name.clone(), value.clone(), ignore_errors),
+            vec![Probe::Property(name)],
+            move |v| v[0].opt().is_some_and(|x| value.is_match(x)),
         ))
     }
 
@@ -808,10 +1673,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_property(
-            property_name.into(),
-            value,
-            ignore_errors,
+        let name = property_name.into();
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_property(name.clone(), value.clone(), ignore_errors),
+            vec![Probe::Property(name)],
+            move |v| v[0].opt().is_some_and(|x| !value.is_match(x)),
         ))
     }
 
@@ -823,9 +1689,11 @@ impl ElementQuery {
         N: Needle + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_properties(
-            collect_arg_slice(desired_properties),
-            ignore_errors,
+        let desired = collect_arg_slice(desired_properties);
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_properties(desired.clone(), ignore_errors),
+            desired.iter().map(|(name, _)| Probe::Property(name.clone())).collect(),
+            move |v| all_match(&desired, v, true),
         ))
     }
 
@@ -840,9 +1708,11 @@ impl ElementQuery {
         N: Needle + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_properties(
-            collect_arg_slice(desired_properties),
-            ignore_errors,
+        let desired = collect_arg_slice(desired_properties);
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_properties(desired.clone(), ignore_errors),
+            desired.iter().map(|(name, _)| Probe::Property(name.clone())).collect(),
+            move |v| all_match(&desired, v, false),
         ))
     }
 
@@ -854,10 +1724,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_css_property(
-            css_property_name.into(),
-            value,
-            ignore_errors,
+        let name = css_property_name.into();
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_css_property(name.clone(), value.clone(), ignore_errors),
+            vec![Probe::CssValue(name)],
+            move |v| value.is_match(v[0].text()),
         ))
     }
 
@@ -869,10 +1740,11 @@ impl ElementQuery {
         N: Needle + Clone + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_css_property(
-            css_property_name.into(),
-            value,
-            ignore_errors,
+        let name = css_property_name.into();
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_css_property(name.clone(), value.clone(), ignore_errors),
+            vec![Probe::CssValue(name)],
+            move |v| !value.is_match(v[0].text()),
         ))
     }
 
@@ -888,9 +1760,11 @@ impl ElementQuery {
         N: Needle + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_has_css_properties(
-            collect_arg_slice(desired_css_properties),
-            ignore_errors,
+        let desired = collect_arg_slice(desired_css_properties);
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_has_css_properties(desired.clone(), ignore_errors),
+            desired.iter().map(|(name, _)| Probe::CssValue(name.clone())).collect(),
+            move |v| desired.iter().zip(v).all(|((_, value), x)| value.is_match(x.text())),
         ))
     }
 
@@ -906,9 +1780,11 @@ impl ElementQuery {
         N: Needle + Send + Sync + 'static,
     {
         let ignore_errors = self.options.ignore_errors.unwrap_or_default();
-        self.with_filter(conditions::element_lacks_css_properties(
-            collect_arg_slice(desired_css_properties),
-            ignore_errors,
+        let desired = collect_arg_slice(desired_css_properties);
+        self.with_batch_filter(BatchFilter::new(
+            conditions::element_lacks_css_properties(desired.clone(), ignore_errors),
+            desired.iter().map(|(name, _)| Probe::CssValue(name.clone())).collect(),
+            move |v| desired.iter().zip(v).all(|((_, value), x)| !value.is_match(x.text())),
         ))
     }
 }
@@ -1,5 +1,5 @@
 use super::conditions::{collect_arg_slice, handle_errors};
-use super::{conditions, ElementPollerWithTimeout, IntoElementPoller};
+use super::{conditions, ElementPollerOnMutation, ElementPollerWithTimeout, IntoElementPoller};
 use crate::error::WebDriverError;
 use crate::prelude::WebDriverResult;
 use crate::IntoArcStr;
@@ -77,6 +77,17 @@ impl ElementWaiter {
         self.with_poller(Arc::new(ElementPollerWithTimeout::new(timeout, interval)))
     }
 
+    /// Force this ElementWaiter to wait for the specified timeout, polling again whenever
+    /// the DOM changes (or at least once every `max_interval`). This will override the
+    /// poller for this ElementWaiter only.
+    ///
+    /// See `ElementPollerOnMutation` for details.
+    pub fn wait_on_mutation(self, timeout: Duration, max_interval: Duration) -> Self {
+        let poller =
+            ElementPollerOnMutation::new(self.element.handle.clone(), timeout, max_interval);
+        self.with_poller(Arc::new(poller))
+    }
+
     async fn run_poller<'a, F, I, P>(&self, conditions: F) -> WebDriverResult<bool>
     where
         F: Fn() -> I,
@@ -103,15 +114,28 @@ impl ElementWaiter {
         }
     }
 
-    fn timeout(self) -> WebDriverResult<()> {
-        Err(WebDriverError::Timeout(format!("element condition timed out: {}", self.message)))
+    /// Return a timeout error, using the descriptions of the conditions if no message was
+    /// provided.
+    fn timeout(
+        self,
+        descriptions: impl IntoIterator<Item = Option<String>>,
+    ) -> WebDriverResult<()> {
+        let message = match self.message.is_empty() {
+            true => descriptions
+                .into_iter()
+                .map(|d| d.unwrap_or_else(|| "custom predicate".to_string()))
+                .collect::<Vec<_>>()
+                .join(" and "),
+            false => self.message,
+        };
+        Err(WebDriverError::Timeout(format!("element condition timed out: {message}")))
     }
 
     /// Wait for the specified condition to be true.
     pub async fn condition(self, f: impl ElementPredicate) -> WebDriverResult<()> {
         match self.run_poller(|| [&f].into_iter()).await? {
             true => Ok(()),
-            false => self.timeout(),
+            false => self.timeout([f.description()]),
         }
     }
 
@@ -122,7 +146,7 @@ impl ElementWaiter {
     ) -> WebDriverResult<()> {
         match self.run_poller(|| conditions.iter().map(Box::deref)).await? {
             true => Ok(()),
-            false => self.timeout(),
+            false => self.timeout(conditions.iter().map(|f| f.description())),
         }
     }
 
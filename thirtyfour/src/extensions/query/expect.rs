@@ -0,0 +1,559 @@
+use super::{ElementPollerOnMutation, ElementPollerWithTimeout, ElementQuery, IntoElementPoller};
+use crate::error::{WebDriverError, WebDriverErrorInner, WebDriverResult};
+use crate::session::handle::SessionHandle;
+use crate::{IntoArcStr, WebDriver, WebElement};
+use std::fmt::{Debug, Write};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use stringmatch::Needle;
+
+/// The maximum number of lines compared by the line-by-line diff in failure messages.
+const MAX_DIFF_LINES: usize = 200;
+
+/// Start an assertion that is retried until it passes or times out.
+///
+/// The target can be a `&WebElement`, a `&WebDriver` (or `&Arc<SessionHandle>`) for
+/// page-level assertions, or an `ElementQuery` to assert on the number of matching
+/// elements. Assertions are retried using the configured poller (or the poller of the
+/// query), and on failure, the error reports the last observed value, with a diff
+/// against the expected value where possible.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use thirtyfour::extensions::query::expect;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let elem = driver.find(By::Id("status")).await?;
+/// expect(&elem).to_have_text("Saved").await?;
+/// expect(&elem).not().to_have_class("error").await?;
+/// expect(driver.query(By::Css("li.item"))).to_have_count(3).await?;
+/// expect(&driver).to_have_title("Dashboard").await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+pub fn expect<T: ExpectTarget>(target: T) -> Expect<T::Target> {
+    let (target, poller) = target.into_target();
+    Expect {
+        target,
+        poller,
+        negate: false,
+        message: String::new(),
+    }
+}
+
+/// Trait for values that can be passed to `expect()`.
+pub trait ExpectTarget {
+    /// The value that the assertions are made on.
+    type Target;
+
+    /// Return the target and the poller to use for retrying assertions.
+    fn into_target(self) -> (Self::Target, Arc<dyn IntoElementPoller + Send + Sync>);
+}
+
+impl ExpectTarget for &WebElement {
+    type Target = WebElement;
+
+    fn into_target(self) -> (Self::Target, Arc<dyn IntoElementPoller + Send + Sync>) {
+        (self.clone(), self.handle.config().poller.clone())
+    }
+}
+
+impl ExpectTarget for &Arc<SessionHandle> {
+    type Target = Arc<SessionHandle>;
+
+    fn into_target(self) -> (Self::Target, Arc<dyn IntoElementPoller + Send + Sync>) {
+        (self.clone(), self.config().poller.clone())
+    }
+}
+
+impl ExpectTarget for &WebDriver {
+    type Target = Arc<SessionHandle>;
+
+    fn into_target(self) -> (Self::Target, Arc<dyn IntoElementPoller + Send + Sync>) {
+        (&self.handle).into_target()
+    }
+}
+
+impl ExpectTarget for ElementQuery {
+    type Target = ElementQuery;
+
+    fn into_target(self) -> (Self::Target, Arc<dyn IntoElementPoller + Send + Sync>) {
+        self.into_single_attempt()
+    }
+}
+
+/// A value observed while checking an assertion.
+#[derive(Debug)]
+struct Observed {
+    passed: bool,
+    value: String,
+    /// The expected text, if the observed value is text that can be diffed against it.
+    expected: Option<String>,
+    actual: Option<String>,
+}
+
+impl Observed {
+    fn new(passed: bool, value: impl Debug) -> Self {
+        Self {
+            passed,
+            value: format!("{value:?}"),
+            expected: None,
+            actual: None,
+        }
+    }
+
+    fn text(expected: &str, actual: String, passed: bool) -> Self {
+        Self {
+            passed,
+            value: format!("{actual:?}"),
+            expected: Some(expected.to_string()),
+            actual: Some(actual),
+        }
+    }
+}
+
+/// An assertion that is retried until it passes or times out. See `expect()`.
+#[derive(Debug)]
+pub struct Expect<T> {
+    target: T,
+    poller: Arc<dyn IntoElementPoller + Send + Sync>,
+    negate: bool,
+    message: String,
+}
+
+impl<T> Expect<T> {
+    /// Negate the assertion, so that it passes when the condition is not met.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(mut self) -> Self {
+        self.negate = !self.negate;
+        self
+    }
+
+    /// Provide a human-readable message to be included in the error if the assertion fails.
+    pub fn error(mut self, message: &str) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    /// Use the specified ElementPoller for this assertion.
+    pub fn with_poller(mut self, poller: Arc<dyn IntoElementPoller + Send + Sync>) -> Self {
+        self.poller = poller;
+        self
+    }
+
+    /// Retry this assertion until the specified timeout, checking once after each interval.
+    pub fn wait(self, timeout: Duration, interval: Duration) -> Self {
+        self.with_poller(Arc::new(ElementPollerWithTimeout::new(timeout, interval)))
+    }
+
+    /// Check the assertion until it passes or the poller times out.
+    ///
+    /// Errors while checking are treated as a failed check, and the last one is reported if
+    /// the value was never observed.
+    async fn retry<F, Fut>(&self, subject: &str, description: &str, check: F) -> WebDriverResult<()>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = WebDriverResult<Observed>>,
+    {
+        let mut poller = self.poller.start();
+        let mut last = None;
+        loop {
+            match check().await {
+                Ok(observed) if observed.passed != self.negate => return Ok(()),
+                Ok(observed) => last = Some(Ok(observed)),
+                Err(e) => {
+                    if !matches!(last, Some(Ok(_))) {
+                        last = Some(Err(e));
+                    }
+                }
+            }
+
+            if !poller.tick().await {
+                break;
+            }
+        }
+
+        let not = if self.negate {
+            "not "
+        } else {
+            ""
+        };
+        let mut message = format!("expected {subject} {not}{description}");
+        if !self.message.is_empty() {
+            message = format!("{}: {message}", self.message);
+        }
+        match last {
+            Some(Ok(Observed {
+                expected: Some(expected),
+                actual: Some(actual),
+                ..
+            })) if !self.negate => {
+                message.push('\n');
+                message.push_str(&diff(&expected, &actual));
+            }
+            Some(Ok(observed)) => {
+                let _ = write!(message, "\nlast observed: {}", observed.value);
+            }
+            Some(Err(e)) => {
+                let _ = write!(message, "\nlast error: {e}");
+            }
+            None => {}
+        }
+        Err(WebDriverError::ExpectationFailed(message))
+    }
+}
+
+impl Expect<WebElement> {
+    /// Force this assertion to be retried until the specified timeout, checking again
+    /// whenever the DOM changes (or at least once every `max_interval`).
+    ///
+    /// See `ElementPollerOnMutation` for details.
+    pub fn wait_on_mutation(self, timeout: Duration, max_interval: Duration) -> Self {
+        let handle = self.target.handle.clone();
+        self.with_poller(Arc::new(ElementPollerOnMutation::new(handle, timeout, max_interval)))
+    }
+
+    fn subject(&self) -> String {
+        format!("element {}", self.target)
+    }
+
+    /// Assert that the text of the element is exactly the specified text.
+    pub async fn to_have_text(self, text: impl Into<String>) -> WebDriverResult<()> {
+        let text = text.into();
+        let (elem, expected) = (&self.target, text.as_str());
+        self.retry(&self.subject(), &format!("to have text {text:?}"), || async move {
+            let actual = elem.text().await?;
+            Ok(Observed::text(expected, actual.clone(), actual == expected))
+        })
+        .await
+    }
+
+    /// Assert that the text of the element contains the specified text.
+    pub async fn to_contain_text(self, text: impl Into<String>) -> WebDriverResult<()> {
+        let text = text.into();
+        let (elem, expected) = (&self.target, text.as_str());
+        self.retry(&self.subject(), &format!("to contain text {text:?}"), || async move {
+            let actual = elem.text().await?;
+            Ok(Observed::new(actual.contains(expected), actual))
+        })
+        .await
+    }
+
+    /// Assert that the text of the element matches the specified needle.
+    /// See the `Needle` documentation for more details on text matching rules.
+    pub async fn to_match_text<N>(self, text: N) -> WebDriverResult<()>
+    where
+        N: Needle + Debug + Send + Sync,
+    {
+        let (elem, needle) = (&self.target, &text);
+        self.retry(&self.subject(), &format!("to match text {text:?}"), || async move {
+            let actual = elem.text().await?;
+            Ok(Observed::new(needle.is_match(&actual), actual))
+        })
+        .await
+    }
+
+    /// Assert that the element is displayed.
+    pub async fn to_be_visible(self) -> WebDriverResult<()> {
+        let elem = &self.target;
+        self.retry(&self.subject(), "to be visible", || async move {
+            let displayed = elem.is_displayed().await?;
+            Ok(Observed::new(
+                displayed,
+                if displayed {
+                    "visible"
+                } else {
+                    "hidden"
+                },
+            ))
+        })
+        .await
+    }
+
+    /// Assert that the element is not displayed, or is no longer in the document.
+    pub async fn to_be_hidden(self) -> WebDriverResult<()> {
+        let elem = &self.target;
+        self.retry(&self.subject(), "to be hidden", || async move {
+            match elem.is_displayed().await {
+                Ok(displayed) => Ok(Observed::new(
+                    !displayed,
+                    if displayed {
+                        "visible"
+                    } else {
+                        "hidden"
+                    },
+                )),
+                Err(e)
+                    if matches!(
+                        *e,
+                        WebDriverErrorInner::StaleElementReference(_)
+                            | WebDriverErrorInner::NoSuchElement(_)
+                    ) =>
+                {
+                    Ok(Observed::new(true, "detached"))
+                }
+                Err(e) => Err(e),
+            }
+        })
+        .await
+    }
+
+    /// Assert that the element is enabled.
+    pub async fn to_be_enabled(self) -> WebDriverResult<()> {
+        let elem = &self.target;
+        self.retry(&self.subject(), "to be enabled", || async move {
+            let enabled = elem.is_enabled().await?;
+            Ok(Observed::new(
+                enabled,
+                if enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                },
+            ))
+        })
+        .await
+    }
+
+    /// Assert that the element is not enabled.
+    pub async fn to_be_disabled(self) -> WebDriverResult<()> {
+        let elem = &self.target;
+        self.retry(&self.subject(), "to be disabled", || async move {
+            let enabled = elem.is_enabled().await?;
+            Ok(Observed::new(
+                !enabled,
+                if enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                },
+            ))
+        })
+        .await
+    }
+
+    /// Assert that the element is selected.
+    pub async fn to_be_selected(self) -> WebDriverResult<()> {
+        let elem = &self.target;
+        self.retry(&self.subject(), "to be selected", || async move {
+            let selected = elem.is_selected().await?;
+            Ok(Observed::new(
+                selected,
+                if selected {
+                    "selected"
+                } else {
+                    "not selected"
+                },
+            ))
+        })
+        .await
+    }
+
+    /// Assert that the value of the element is exactly the specified value.
+    pub async fn to_have_value(self, value: impl Into<String>) -> WebDriverResult<()> {
+        let value = value.into();
+        let (elem, expected) = (&self.target, value.as_str());
+        self.retry(&self.subject(), &format!("to have value {value:?}"), || async move {
+            match elem.value().await? {
+                Some(actual) => Ok(Observed::text(expected, actual.clone(), actual == expected)),
+                None => Ok(Observed::new(false, None::<String>)),
+            }
+        })
+        .await
+    }
+
+    /// Assert that the specified attribute of the element is exactly the specified value.
+    pub async fn to_have_attribute(
+        self,
+        attribute_name: impl IntoArcStr,
+        value: impl Into<String>,
+    ) -> WebDriverResult<()> {
+        let name: Arc<str> = attribute_name.into();
+        let value = value.into();
+        let (elem, name_ref, expected) = (&self.target, &name, value.as_str());
+        let description = format!("to have attribute {name:?} = {value:?}");
+        self.retry(&self.subject(), &description, || async move {
+            match elem.attr(name_ref.clone()).await? {
+                Some(actual) => Ok(Observed::text(expected, actual.clone(), actual == expected)),
+                None => Ok(Observed::new(false, None::<String>)),
+            }
+        })
+        .await
+    }
+
+    /// Assert that the class of the element matches the specified needle.
+    /// See the `Needle` documentation for more details on text matching rules.
+    pub async fn to_have_class<N>(self, class_name: N) -> WebDriverResult<()>
+    where
+        N: Needle + Debug + Send + Sync,
+    {
+        let (elem, needle) = (&self.target, &class_name);
+        self.retry(&self.subject(), &format!("to have class {class_name:?}"), || async move {
+            let actual = elem.class_name().await?;
+            let passed = actual.as_deref().is_some_and(|x| needle.is_match(x));
+            Ok(Observed::new(passed, actual))
+        })
+        .await
+    }
+}
+
+impl Expect<Arc<SessionHandle>> {
+    /// Force this assertion to be retried until the specified timeout, checking again
+    /// whenever the DOM changes (or at least once every `max_interval`).
+    ///
+    /// See `ElementPollerOnMutation` for details.
+    pub fn wait_on_mutation(self, timeout: Duration, max_interval: Duration) -> Self {
+        let handle = self.target.clone();
+        self.with_poller(Arc::new(ElementPollerOnMutation::new(handle, timeout, max_interval)))
+    }
+
+    /// Assert that the current URL is exactly the specified URL.
+    pub async fn to_have_url(self, url: impl Into<String>) -> WebDriverResult<()> {
+        let url = url.into();
+        let (handle, expected) = (&self.target, url.as_str());
+        self.retry("page", &format!("to have URL {url:?}"), || async move {
+            let actual = handle.current_url().await?.to_string();
+            Ok(Observed::text(expected, actual.clone(), actual == expected))
+        })
+        .await
+    }
+
+    /// Assert that the current URL matches the specified needle.
+    /// See the `Needle` documentation for more details on text matching rules.
+    pub async fn to_match_url<N>(self, url: N) -> WebDriverResult<()>
+    where
+        N: Needle + Debug + Send + Sync,
+    {
+        let (handle, needle) = (&self.target, &url);
+        self.retry("page", &format!("to match URL {url:?}"), || async move {
+            let actual = handle.current_url().await?.to_string();
+            Ok(Observed::new(needle.is_match(&actual), actual))
+        })
+        .await
+    }
+
+    /// Assert that the page title is exactly the specified title.
+    pub async fn to_have_title(self, title: impl Into<String>) -> WebDriverResult<()> {
+        let title = title.into();
+        let (handle, expected) = (&self.target, title.as_str());
+        self.retry("page", &format!("to have title {title:?}"), || async move {
+            let actual = handle.title().await?;
+            Ok(Observed::text(expected, actual.clone(), actual == expected))
+        })
+        .await
+    }
+
+    /// Assert that the page title matches the specified needle.
+    /// See the `Needle` documentation for more details on text matching rules.
+    pub async fn to_match_title<N>(self, title: N) -> WebDriverResult<()>
+    where
+        N: Needle + Debug + Send + Sync,
+    {
+        let (handle, needle) = (&self.target, &title);
+        self.retry("page", &format!("to match title {title:?}"), || async move {
+            let actual = handle.title().await?;
+            Ok(Observed::new(needle.is_match(&actual), actual))
+        })
+        .await
+    }
+}
+
+impl Expect<ElementQuery> {
+    /// Assert that the query matches exactly the specified number of elements (across all
+    /// of its selectors, like `ElementQuery::any()`).
+    pub async fn to_have_count(self, count: usize) -> WebDriverResult<()> {
+        let query = &self.target;
+        let subject = format!("query {}", query.selector_summary());
+        self.retry(&subject, &format!("to have count {count}"), || async move {
+            let actual = query.any().await?.len();
+            Ok(Observed::new(actual == count, actual))
+        })
+        .await
+    }
+}
+
+/// Show the differences between the expected and actual text.
+///
+/// Single-line values are shown one above the other, with a marker under the first
+/// difference. Multi-line values are compared line by line.
+fn diff(expected: &str, actual: &str) -> String {
+    let lines_expected: Vec<&str> = expected.lines().collect();
+    let lines_actual: Vec<&str> = actual.lines().collect();
+    if lines_expected.len() <= 1 && lines_actual.len() <= 1 {
+        let index = expected
+            .char_indices()
+            .zip(actual.chars())
+            .find(|((_, a), b)| a != b)
+            .map(|((i, _), _)| i)
+            .unwrap_or_else(|| expected.len().min(actual.len()));
+        let offset = format!("{:?}", &expected[..index]).chars().count() - 1;
+        return format!(
+            "expected: {expected:?}\nobserved: {actual:?}\n{}^",
+            " ".repeat("expected: ".len() + offset)
+        );
+    }
+
+    if lines_expected.len() > MAX_DIFF_LINES || lines_actual.len() > MAX_DIFF_LINES {
+        return format!("expected:\n{expected}\nobserved:\n{actual}");
+    }
+
+    // Longest common subsequence of lines.
+    let (n, m) = (lines_expected.len(), lines_actual.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if lines_expected[i] == lines_actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::from("diff (- expected, + observed):");
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && lines_expected[i] == lines_actual[j] {
+            let _ = write!(out, "\n  {}", lines_expected[i]);
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            let _ = write!(out, "\n+ {}", lines_actual[j]);
+            j += 1;
+        } else {
+            let _ = write!(out, "\n- {}", lines_expected[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_single_line() {
+        assert_eq!(
+            diff("Hello world", "Hello World"),
+            "expected: \"Hello world\"\nobserved: \"Hello World\"\n                 ^"
+        );
+        assert_eq!(diff("ab", "abc"), "expected: \"ab\"\nobserved: \"abc\"\n             ^");
+    }
+
+    #[test]
+    fn test_diff_multi_line() {
+        assert_eq!(
+            diff("one\ntwo\nthree", "one\n2\nthree\nfour"),
+            "diff (- expected, + observed):\n  one\n+ 2\n- two\n  three\n+ four"
+        );
+    }
+}
@@ -78,6 +78,26 @@
 //! The `By::Css` and `By::XPath` selectors may be required for more complex
 //! filters.
 //!
+//! Elements can also be matched by their position relative to other elements, via
+//! `above()`, `below()`, `left_of()`, `right_of()` and `near()`.
+//! All of these are checked together in a single request per query branch.
+//!
+//! ```no_run
+//! # use thirtyfour::prelude::*;
+//! # use thirtyfour::support::block_on;
+//! #
+//! # fn main() -> WebDriverResult<()> {
+//! #     block_on(async {
+//! #         let caps = DesiredCapabilities::chrome();
+//! #         let mut driver = WebDriver::new("http://localhost:4444", caps).await?;
+//! let label = driver.query(By::Tag("label")).with_text("Email").first().await?;
+//! let input = driver.query(By::Tag("input")).right_of(&label).near(&label, 50).first().await?;
+//! #         driver.quit().await?;
+//! #         Ok(())
+//! #     })
+//! # }
+//! ```
+//!
 //! To fetch all matching elements instead of just the first one, change `first()` to `all()`
 //! and you'll get a Vec instead.
 //! Note that `all()` will return only the elements from the query
@@ -132,22 +152,80 @@
 //!
 //! These predicates (or your own) can also be supplied as filters to `ElementQuery`.
 //!
+//! Predicates can be combined using `conditions::and()`, `or()`, `not()`, `all_of()` and
+//! `any_of()`, and custom predicates can be given a description for error messages via
+//! `conditions::describe()`:
+//!
+//! ```ignore
+//! use thirtyfour::extensions::query::conditions::{self, or};
+//!
+//! elem.wait_until().condition(or(
+//!     conditions::element_has_class("active", true),
+//!     conditions::element_has_text("Selected", true),
+//! )).await?;
+//! ```
+//!
+//! ### DriverWaiter
+//!
+//! To wait for the state of the browser rather than a single element, use `wait_until()`
+//! on the driver instead:
+//! ```ignore
+//! driver.wait_until().title_contains("Dashboard").await?;
+//! driver.wait_until().number_of_windows(2).await?;
+//! driver.wait_until().js_returns_truthy("return window.appReady;").await?;
+//! ```
+//!
+//! See the [`DriverWaiter`] docs for the full list of conditions available.
+//!
+//! [`DriverWaiter`]: DriverWaiter
+//!
+//! ### Assertions
+//!
+//! The [`expect()`] function provides assertions that are retried until they pass or time
+//! out, and report the last observed value (with a diff) when they fail:
+//! ```ignore
+//! use thirtyfour::extensions::query::expect;
+//!
+//! expect(&elem).to_have_text("Saved").await?;
+//! expect(driver.query(By::Css("li"))).to_have_count(3).await?;
+//! expect(&driver).to_have_url("https://example.com/done").await?;
+//! ```
+//!
+//! [`expect()`]: expect()
+//!
 //! ### ElementPoller
 //!
 //! The polling strategy can be customized by implementing both [`ElementPoller`]
 //! and [`IntoElementPoller`].
 //!
 //! See [`ElementPollerWithTimeout`] for more details about the default polling behaviour.
+//! To poll again as soon as the DOM changes instead of on a fixed interval, use
+//! [`ElementPollerOnMutation`] (or `ElementQuery::wait_on_mutation()`).
 //!
 //! [`ElementPoller`]: ElementPoller
 //! [`IntoElementPoller`]: IntoElementPoller
 //! [`ElementPollerWithTimeout`]: ElementPollerWithTimeout
+//! [`ElementPollerOnMutation`]: ElementPollerOnMutation
 
+mod batch;
 /// Predicates to use for element conditions.
 pub mod conditions;
+mod diagnostics;
+mod driver_waiter;
 mod element_query;
 mod element_waiter;
+mod expect;
+mod order;
 mod poller;
+mod relative;
+mod stream;
+pub use batch::{filter_elements_batched, BatchFilter};
+pub use diagnostics::{FilterDiagnostics, QueryDiagnostics, SelectorDiagnostics};
+pub use driver_waiter::*;
 pub use element_query::*;
 pub use element_waiter::*;
+pub use expect::*;
+pub use order::*;
 pub use poller::*;
+pub use relative::*;
+pub use stream::*;
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use crate::error::WebDriverResult;
+use crate::js::SORT_ELEMENTS;
+use crate::session::handle::SessionHandle;
+use crate::{ElementId, WebElement};
+
+/// The order of the elements returned by an `ElementQuery`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ElementOrder {
+    /// The elements matched by each selector, in the order the selectors were added to the
+    /// query (the first one, followed by each `or()` branch). The elements matched by a
+    /// single selector are in the order returned by WebDriver, which is usually document
+    /// order.
+    #[default]
+    Selector,
+    /// Document order, i.e. the order in which the elements start in the page source.
+    Document,
+    /// On-screen position, from top to bottom and then from left to right, as reported by
+    /// `getBoundingClientRect()`. Elements at the same position are in document order.
+    Position,
+}
+
+impl ElementOrder {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ElementOrder::Selector => "selector",
+            ElementOrder::Document => "document",
+            ElementOrder::Position => "position",
+        }
+    }
+}
+
+/// Sort the specified elements in the specified order, using a single script execution per
+/// frame.
+///
+/// Elements in different frames cannot be compared, so the elements are grouped by frame,
+/// in the order in which each frame first appears, and each group is sorted separately.
+pub async fn sort_elements(
+    handle: &Arc<SessionHandle>,
+    elements: Vec<WebElement>,
+    order: ElementOrder,
+) -> WebDriverResult<Vec<WebElement>> {
+    if order == ElementOrder::Selector || elements.len() < 2 {
+        return Ok(elements);
+    }
+
+    let mut groups: IndexMap<Vec<ElementId>, Vec<WebElement>> = IndexMap::new();
+    for element in elements {
+        groups.entry(element.frame_path().to_vec()).or_default().push(element);
+    }
+
+    let mut sorted = Vec::new();
+    for (frames, group) in groups {
+        if group.len() < 2 {
+            sorted.extend(group);
+            continue;
+        }
+
+        let candidates = group.iter().map(|e| e.to_json()).collect::<WebDriverResult<Vec<_>>>()?;
+        let args = vec![Value::Array(candidates), json!(order.as_str())];
+        let ret = if frames.is_empty() {
+            handle.execute(SORT_ELEMENTS, args).await?
+        } else {
+            handle.in_frame_path(&frames, handle.execute(SORT_ELEMENTS, args)).await?
+        };
+        let indices: Vec<usize> = ret.convert()?;
+
+        let mut group: Vec<Option<WebElement>> = group.into_iter().map(Some).collect();
+        sorted.extend(indices.into_iter().filter_map(|i| group.get_mut(i).and_then(Option::take)));
+    }
+    Ok(sorted)
+}
@@ -1,5 +1,9 @@
+use crate::js::WAIT_FOR_MUTATION;
+use crate::session::handle::SessionHandle;
 use crate::support::sleep;
+use serde_json::json;
 use std::fmt::Debug;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// Trait for implementing the element polling strategy.
@@ -98,6 +102,97 @@ impl IntoElementPoller for ElementPollerNoWait {
     }
 }
 
+/// The time to wait before polling again if the `MutationObserver` script fails, e.g.
+/// because the page is navigating.
+const MUTATION_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Poll up to the specified timeout, waiting for the DOM to change between each poll attempt
+/// rather than sleeping for a fixed interval.
+///
+/// Each tick runs a script via `execute_async()` that resumes as soon as a
+/// `MutationObserver` on the document reports a change (or one was already reported since
+/// the previous tick). This polls again almost immediately after the page changes, without
+/// sending commands while it is idle.
+///
+/// Changes that are not DOM mutations (e.g. CSS transitions, scrolling or changes inside
+/// frames) are not observed, so each tick also waits for at most `max_interval`. This
+/// should be shorter than the session's script timeout.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use thirtyfour::extensions::query::ElementPollerOnMutation;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let poller = ElementPollerOnMutation::new(
+///     driver.handle.clone(),
+///     Duration::from_secs(20),
+///     Duration::from_secs(2),
+/// );
+/// let elem = driver.query(By::Id("button1")).with_poller(Arc::new(poller)).first().await?;
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ElementPollerOnMutation {
+    handle: Arc<SessionHandle>,
+    timeout: Duration,
+    max_interval: Duration,
+    start: Instant,
+    seen: Option<u64>,
+}
+
+impl ElementPollerOnMutation {
+    /// Create a new `ElementPollerOnMutation`.
+    pub fn new(handle: Arc<SessionHandle>, timeout: Duration, max_interval: Duration) -> Self {
+        Self {
+            handle,
+            timeout,
+            max_interval,
+            start: Instant::now(),
+            seen: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ElementPoller for ElementPollerOnMutation {
+    async fn tick(&mut self) -> bool {
+        let elapsed = self.start.elapsed();
+        if elapsed >= self.timeout {
+            return false;
+        }
+
+        let wait = self.max_interval.min(self.timeout - elapsed);
+        let args = vec![json!(self.seen), json!(wait.as_millis() as u64)];
+        let ret = self.handle.execute_async(WAIT_FOR_MUTATION, args).await;
+        match ret.and_then(|ret| ret.convert::<u64>()) {
+            Ok(seen) => self.seen = Some(seen),
+            Err(e) => {
+                tracing::debug!("waiting for DOM changes failed: {e}");
+                self.seen = None;
+                sleep(MUTATION_RETRY_INTERVAL.min(wait)).await;
+            }
+        }
+
+        true
+    }
+}
+
+impl IntoElementPoller for ElementPollerOnMutation {
+    fn start(&self) -> Box<dyn ElementPoller + Send + Sync> {
+        Box::new(Self::new(self.handle.clone(), self.timeout, self.max_interval))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
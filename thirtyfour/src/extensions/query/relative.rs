@@ -0,0 +1,110 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use crate::error::WebDriverResult;
+use crate::js::FILTER_RELATIVE;
+use crate::session::handle::SessionHandle;
+use crate::WebElement;
+
+/// The position of an element relative to another (anchor) element, similar to the
+/// relative locators in Selenium 4.
+///
+/// Positions are compared using the bounding rectangle of each element, as reported by the
+/// browser. An element is never considered to be relative to itself, or to any element
+/// it contains.
+#[derive(Debug, Clone)]
+pub enum Relation {
+    /// The element is entirely above the anchor.
+    Above(WebElement),
+    /// The element is entirely below the anchor.
+    Below(WebElement),
+    /// The element is entirely to the left of the anchor.
+    LeftOf(WebElement),
+    /// The element is entirely to the right of the anchor.
+    RightOf(WebElement),
+    /// The gap between the element and the anchor is at most the specified number of pixels.
+    Near(WebElement, u32),
+}
+
+impl Relation {
+    /// The anchor element.
+    pub fn anchor(&self) -> &WebElement {
+        match self {
+            Relation::Above(elem)
+            | Relation::Below(elem)
+            | Relation::LeftOf(elem)
+            | Relation::RightOf(elem)
+            | Relation::Near(elem, _) => elem,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Relation::Above(_) => "above",
+            Relation::Below(_) => "below",
+            Relation::LeftOf(_) => "left_of",
+            Relation::RightOf(_) => "right_of",
+            Relation::Near(..) => "near",
+        }
+    }
+
+    fn to_json(&self) -> WebDriverResult<Value> {
+        let distance = match self {
+            Relation::Near(_, distance) => *distance,
+            _ => 0,
+        };
+        Ok(json!({
+            "kind": self.kind(),
+            "anchor": self.anchor().to_json()?,
+            "distance": distance,
+        }))
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relation::Near(elem, distance) => write!(f, "near({elem}, {distance}px)"),
+            _ => write!(f, "{}({})", self.kind(), self.anchor()),
+        }
+    }
+}
+
+/// Filter the specified elements so that only those matching all of the specified relations
+/// remain, using a single script execution.
+///
+/// The elements are sorted by the distance between their center and the center of the
+/// first anchor, nearest first. Elements in a different frame to the anchors never match.
+pub async fn filter_relative(
+    handle: &Arc<SessionHandle>,
+    elements: Vec<WebElement>,
+    relations: &[Relation],
+) -> WebDriverResult<Vec<WebElement>> {
+    let Some(first) = relations.first() else {
+        return Ok(elements);
+    };
+    let frames = first.anchor().frame_path();
+    if relations.iter().any(|r| r.anchor().frame_path() != frames) {
+        return Ok(Vec::new());
+    }
+    let elements: Vec<WebElement> =
+        elements.into_iter().filter(|e| e.frame_path() == frames).collect();
+    if elements.is_empty() {
+        return Ok(elements);
+    }
+
+    let candidates = elements.iter().map(|e| e.to_json()).collect::<WebDriverResult<Vec<_>>>()?;
+    let relations = relations.iter().map(|r| r.to_json()).collect::<WebDriverResult<Vec<_>>>()?;
+    let args = vec![Value::Array(candidates), Value::Array(relations)];
+    let ret = if frames.is_empty() {
+        handle.execute(FILTER_RELATIVE, args).await?
+    } else {
+        handle.in_frame_path(frames, handle.execute(FILTER_RELATIVE, args)).await?
+    };
+    let indices: Vec<usize> = ret.convert()?;
+
+    let mut elements: Vec<Option<WebElement>> = elements.into_iter().map(Some).collect();
+    Ok(indices.into_iter().filter_map(|i| elements.get_mut(i).and_then(Option::take)).collect())
+}
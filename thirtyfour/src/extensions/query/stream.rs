@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use futures_util::stream::{self, Stream, StreamExt};
+use serde_json::Value;
+
+use super::ElementQuery;
+use crate::error::WebDriverResult;
+use crate::js::SCROLL_FOR_MORE;
+use crate::support::sleep;
+use crate::{ElementId, WebElement};
+
+/// Options for `ElementQuery::stream_with()`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ElementStreamOptions {
+    container: Option<WebElement>,
+    scroll: bool,
+    quiet_period: Duration,
+    interval: Duration,
+}
+
+impl Default for ElementStreamOptions {
+    fn default() -> Self {
+        Self {
+            container: None,
+            scroll: true,
+            quiet_period: Duration::from_secs(2),
+            interval: Duration::from_millis(250),
+        }
+    }
+}
+
+impl ElementStreamOptions {
+    /// Scroll the specified element to load more items, rather than the window.
+    pub fn container(mut self, container: WebElement) -> Self {
+        self.container = Some(container);
+        self
+    }
+
+    /// Set the element to scroll to load more items. If `None`, the window is scrolled.
+    pub fn set_container(mut self, container: Option<WebElement>) -> Self {
+        self.container = container;
+        self
+    }
+
+    /// Set whether to scroll to load more items (the default is true). If false, the
+    /// stream only waits for new items to appear.
+    pub fn scroll(mut self, scroll: bool) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    /// End the stream once no new items have appeared for this long (the default is
+    /// 2 seconds).
+    pub fn quiet_period(mut self, quiet_period: Duration) -> Self {
+        self.quiet_period = quiet_period;
+        self
+    }
+
+    /// Set the minimum time between attempts when no new items were found (the default is
+    /// 250 milliseconds).
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// The state of a stream returned by `ElementQuery::stream_with()`.
+struct ElementStreamState<'a> {
+    query: &'a ElementQuery,
+    options: ElementStreamOptions,
+    seen: HashSet<ElementId>,
+    last_new: Instant,
+    started: bool,
+    done: bool,
+}
+
+impl ElementStreamState<'_> {
+    /// Find the next batch of new elements, or `None` once the stream has ended.
+    async fn next_batch(&mut self) -> Option<Vec<WebDriverResult<WebElement>>> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            if self.started && self.options.scroll {
+                if let Err(e) = self.scroll().await {
+                    self.done = true;
+                    return Some(vec![Err(e)]);
+                }
+            }
+            self.started = true;
+
+            let elements = match self.query.select_all_once().await {
+                Ok(elements) => elements,
+                Err(e) => {
+                    self.done = true;
+                    return Some(vec![Err(e)]);
+                }
+            };
+            let new: Vec<_> =
+                elements.into_iter().filter(|e| self.seen.insert(e.element_id())).map(Ok).collect();
+            if !new.is_empty() {
+                self.last_new = Instant::now();
+                return Some(new);
+            }
+
+            if self.last_new.elapsed() >= self.options.quiet_period {
+                return None;
+            }
+            sleep(self.options.interval).await;
+        }
+    }
+
+    /// Scroll the container (or the window) down to load more items.
+    async fn scroll(&self) -> WebDriverResult<()> {
+        let handle = self.query.handle();
+        match &self.options.container {
+            Some(container) => {
+                let args = vec![container.to_json()?];
+                let frames = container.frame_path();
+                if frames.is_empty() {
+                    handle.execute(SCROLL_FOR_MORE, args).await?;
+                } else {
+                    handle.in_frame_path(frames, handle.execute(SCROLL_FOR_MORE, args)).await?;
+                }
+            }
+            None => {
+                handle.execute(SCROLL_FOR_MORE, vec![Value::Null]).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ElementQuery {
+    /// Stream the elements matching any selector (including filters) as they appear,
+    /// scrolling the window to load more, using the default `ElementStreamOptions`.
+    ///
+    /// See `stream_with()` for details.
+    pub fn stream(&self) -> impl Stream<Item = WebDriverResult<WebElement>> + Send + '_ {
+        self.stream_with(ElementStreamOptions::default())
+    }
+
+    /// Stream the elements matching any selector (including filters) as they appear.
+    ///
+    /// This is useful for long or virtualised lists, where only the items currently in
+    /// view are rendered, so the other retrievers never see all of them.
+    ///
+    /// Each attempt runs all selectors once and yields any elements that were not yielded
+    /// before, in the order set via `order_by()`. After each attempt, the container (or the
+    /// window) is scrolled down by most of its visible height, to load or render more
+    /// items. The stream ends once no new elements have been found for the quiet period.
+    /// The poller set for the query (e.g. via `wait()` or `nowait()`) is not used.
+    ///
+    /// Elements are identified by their WebDriver element reference, so lists that reuse
+    /// the same DOM element for different items will not yield it again. Items that are
+    /// scrolled out of view may also be removed from the page, so any values needed from
+    /// an element should be read as soon as it is yielded.
+    ///
+    /// The stream ends after the first error.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// use futures_util::StreamExt;
+    /// use thirtyfour::extensions::query::ElementStreamOptions;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// let list = driver.find(By::Id("results")).await?;
+    /// let query = list.query(By::Css("li.result"));
+    /// let options = ElementStreamOptions::default().container(list);
+    /// let mut results = Box::pin(query.stream_with(options));
+    /// while let Some(elem) = results.next().await {
+    ///     println!("{}", elem?.text().await?);
+    /// }
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn stream_with(
+        &self,
+        options: ElementStreamOptions,
+    ) -> impl Stream<Item = WebDriverResult<WebElement>> + Send + '_ {
+        let state = ElementStreamState {
+            query: self,
+            options,
+            seen: HashSet::new(),
+            last_new: Instant::now(),
+            started: false,
+            done: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            let batch = state.next_batch().await?;
+            Some((batch, state))
+        })
+        .flat_map(stream::iter)
+    }
+}
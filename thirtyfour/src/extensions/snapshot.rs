@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::{invalid_selector, no_such_element, WebDriverError, WebDriverResult};
+use crate::js::{RESOLVE_SNAPSHOT_ELEMENT, SNAPSHOT_DOM};
+use crate::session::handle::SessionHandle;
+use crate::{ElementId, WebElement};
+
+/// A read-only copy of the DOM, parsed locally so that it can be queried without sending
+/// any WebDriver commands.
+///
+/// Reading the text or attributes of many elements via `WebElement` sends a command per
+/// element and value, which is slow for large pages (such as tables with thousands of
+/// cells). A snapshot serializes the page once, via `WebDriver::dom_snapshot()` or
+/// `WebElement::dom_snapshot()`, and parses it with [`scraper`]. Elements can then be
+/// found with CSS selectors and read locally, and mapped back to a live `WebElement`
+/// (via `SnapshotElement::live()`) when they need to be interacted with.
+///
+/// The snapshot does not change when the page does. It only contains the HTML of the
+/// document, so it does not include the contents of shadow roots or frames, the current
+/// values of form controls (only their `value` attributes), or anything that depends on
+/// layout or styles, such as visibility.
+///
+/// A `DomSnapshot` can be sent between threads, but cannot be shared between them, so a
+/// `SnapshotElement` (which borrows the snapshot) cannot be held across an `.await` in a
+/// future that must be `Send`. The future returned by `SnapshotElement::live()` does not
+/// borrow the snapshot, so it can be created before the element is dropped and awaited
+/// afterwards, as below.
+///
+/// This requires the `dom-snapshot` feature.
+///
+/// # Example:
+/// ```no_run
+/// # use thirtyfour::prelude::*;
+/// # use thirtyfour::support::block_on;
+/// #
+/// # fn main() -> WebDriverResult<()> {
+/// #     block_on(async {
+/// #         let caps = DesiredCapabilities::chrome();
+/// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+/// let table = driver.find(By::Id("results")).await?;
+/// let snapshot = table.dom_snapshot().await?;
+///
+/// let mut failed = None;
+/// for row in snapshot.find_all("tbody tr")? {
+///     let cells: Vec<String> = row.find_all("td")?.iter().map(|cell| cell.text()).collect();
+///     if cells.iter().any(|text| text == "FAILED") {
+///         failed = Some(row.find("button.retry")?.live());
+///     }
+/// }
+///
+/// // Only the button is looked up in the live page.
+/// if let Some(button) = failed {
+///     button.await?.click().await?;
+/// }
+/// #         driver.quit().await?;
+/// #         Ok(())
+/// #     })
+/// # }
+/// ```
+pub struct DomSnapshot {
+    html: Html,
+    handle: Arc<SessionHandle>,
+    /// The frame that the snapshot was taken in.
+    frames: Arc<[ElementId]>,
+    /// The id of the snapshot in the page, used to find the live elements.
+    id: String,
+    /// The root element of the snapshot.
+    root: NodeId,
+    /// The number of each element in the page. See `take_element_indices()`.
+    indices: HashMap<NodeId, usize>,
+}
+
+impl Debug for DomSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DomSnapshot")
+            .field("id", &self.id)
+            .field("root", &self.root().tag_name())
+            .field("frames", &self.frames)
+            .finish()
+    }
+}
+
+/// The value returned by the `SNAPSHOT_DOM` script.
+#[derive(Debug, Deserialize)]
+struct SnapshotData {
+    id: String,
+    html: String,
+    root: Option<usize>,
+}
+
+/// The attribute containing the number of each element in the HTML returned by
+/// `SNAPSHOT_DOM`.
+const INDEX_ATTRIBUTE: &str = "data-thirtyfour-snapshot";
+
+impl DomSnapshot {
+    /// Take a snapshot of the document containing the specified element (or the current
+    /// document), rooted at that element.
+    pub(crate) async fn capture(
+        handle: &Arc<SessionHandle>,
+        root: Option<&WebElement>,
+    ) -> WebDriverResult<Self> {
+        let frames: Arc<[ElementId]> = root.map(|e| e.frame_path().into()).unwrap_or_default();
+        let args = vec![root.map(|e| e.to_json()).transpose()?.unwrap_or(Value::Null)];
+        let ret = if frames.is_empty() {
+            handle.execute(SNAPSHOT_DOM, args).await?
+        } else {
+            handle.in_frame_path(&frames, handle.execute(SNAPSHOT_DOM, args)).await?
+        };
+        let data: SnapshotData = ret.convert()?;
+        let Some(root_index) = data.root else {
+            return Err(no_such_element(
+                "no such element: cannot take a DOM snapshot of an element that is not in \
+                 the document (e.g. an element inside a shadow root)"
+                    .to_string(),
+            ));
+        };
+
+        let mut html = Html::parse_document(&data.html);
+        let indices = take_element_indices(&mut html);
+        let Some(root) = indices.iter().find(|(_, &i)| i == root_index).map(|(&id, _)| id) else {
+            return Err(WebDriverError::ParseError(
+                "the parsed DOM snapshot does not contain the root element".to_string(),
+            ));
+        };
+        Ok(Self {
+            html,
+            handle: handle.clone(),
+            frames,
+            id: data.id,
+            root,
+            indices,
+        })
+    }
+
+    /// The root element of this snapshot, i.e. the `<html>` element for a snapshot of the
+    /// document, or the element the snapshot was taken from.
+    pub fn root(&self) -> SnapshotElement<'_> {
+        let elem = self
+            .html
+            .tree
+            .get(self.root)
+            .and_then(ElementRef::wrap)
+            .unwrap_or_else(|| self.html.root_element());
+        SnapshotElement {
+            snapshot: self,
+            elem,
+        }
+    }
+
+    /// Find the first element matching the specified CSS selector.
+    ///
+    /// Returns `WebDriverError::NoSuchElement` if no elements match, or
+    /// `WebDriverError::InvalidSelector` if the selector cannot be parsed.
+    pub fn find(&self, css: &str) -> WebDriverResult<SnapshotElement<'_>> {
+        first_match(self.find_all(css)?, css)
+    }
+
+    /// Find all elements matching the specified CSS selector, in document order.
+    ///
+    /// For a snapshot of an element, only its descendants are searched, as they are for
+    /// `WebElement::find_all()`.
+    ///
+    /// Returns `WebDriverError::InvalidSelector` if the selector cannot be parsed.
+    pub fn find_all(&self, css: &str) -> WebDriverResult<Vec<SnapshotElement<'_>>> {
+        if self.root == self.html.root_element().id() {
+            let selector = parse_selector(css)?;
+            Ok(self.html.select(&selector).map(|elem| self.wrap(elem)).collect())
+        } else {
+            self.root().find_all(css)
+        }
+    }
+
+    /// The parsed document, for anything not covered by `DomSnapshot` and `SnapshotElement`.
+    pub fn html(&self) -> &Html {
+        &self.html
+    }
+
+    fn wrap<'a>(&'a self, elem: ElementRef<'a>) -> SnapshotElement<'a> {
+        SnapshotElement {
+            snapshot: self,
+            elem,
+        }
+    }
+}
+
+/// Remove the `INDEX_ATTRIBUTE` added by `SNAPSHOT_DOM` from each element, and return the
+/// number of each element in the page.
+///
+/// Elements added by the parser (such as a `<tbody>` when the page has a `<tr>` directly
+/// inside a `<table>`) are not in the page, so they have no number.
+fn take_element_indices(html: &mut Html) -> HashMap<NodeId, usize> {
+    let ids: Vec<NodeId> =
+        html.tree.nodes().filter(|node| node.value().is_element()).map(|node| node.id()).collect();
+    let mut indices = HashMap::new();
+    for id in ids {
+        let Some(mut node) = html.tree.get_mut(id) else {
+            continue;
+        };
+        let Node::Element(elem) = node.value() else {
+            continue;
+        };
+        let attrs = std::mem::take(&mut elem.attrs);
+        elem.attrs = attrs
+            .into_iter()
+            .filter(|(name, value)| {
+                if &*name.local != INDEX_ATTRIBUTE {
+                    return true;
+                }
+                if let Ok(index) = value.parse() {
+                    indices.insert(id, index);
+                }
+                false
+            })
+            .collect();
+    }
+    indices
+}
+
+/// Parse the specified CSS selector.
+fn parse_selector(css: &str) -> WebDriverResult<Selector> {
+    Selector::parse(css).map_err(|e| invalid_selector(format!("invalid selector CSS({css}): {e}")))
+}
+
+/// Return the first of the specified elements, or a `NoSuchElement` error.
+fn first_match<'a>(
+    elements: Vec<SnapshotElement<'a>>,
+    css: &str,
+) -> WebDriverResult<SnapshotElement<'a>> {
+    elements.into_iter().next().ok_or_else(|| {
+        no_such_element(format!(
+            "no such element: element not found in DOM snapshot using selector: CSS({css})"
+        ))
+    })
+}
+
+/// An element in a `DomSnapshot`.
+#[derive(Clone, Copy)]
+pub struct SnapshotElement<'a> {
+    snapshot: &'a DomSnapshot,
+    elem: ElementRef<'a>,
+}
+
+impl Debug for SnapshotElement<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapshotElement")
+            .field("element", self.elem.value())
+            .field("index", &self.index())
+            .finish()
+    }
+}
+
+impl<'a> SnapshotElement<'a> {
+    /// The tag name of this element, e.g. `div`.
+    pub fn tag_name(&self) -> &'a str {
+        self.elem.value().name()
+    }
+
+    /// The id of this element, if any.
+    pub fn id(&self) -> Option<&'a str> {
+        self.elem.value().id()
+    }
+
+    /// The value of the specified attribute, if present.
+    pub fn attr(&self, name: &str) -> Option<&'a str> {
+        self.elem.value().attr(name)
+    }
+
+    /// The names and values of all attributes of this element.
+    pub fn attrs(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.elem.value().attrs()
+    }
+
+    /// The classes of this element.
+    pub fn class_names(&self) -> impl Iterator<Item = &'a str> {
+        self.elem.value().classes()
+    }
+
+    /// The text of this element and its descendants, with leading, trailing and repeated
+    /// whitespace removed.
+    ///
+    /// Unlike `WebElement::text()`, this does not depend on styles, so it includes the text
+    /// of hidden elements and does not add line breaks between blocks.
+    pub fn text(&self) -> String {
+        self.elem.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+    }
+
+    /// The text of this element and its descendants, exactly as it appears in the HTML,
+    /// like the `textContent` property.
+    pub fn text_content(&self) -> String {
+        self.elem.text().collect()
+    }
+
+    /// The HTML of this element's descendants.
+    pub fn inner_html(&self) -> String {
+        self.elem.inner_html()
+    }
+
+    /// The HTML of this element, including its descendants.
+    pub fn outer_html(&self) -> String {
+        self.elem.html()
+    }
+
+    /// Find the first descendant of this element matching the specified CSS selector.
+    ///
+    /// Returns `WebDriverError::NoSuchElement` if no elements match, or
+    /// `WebDriverError::InvalidSelector` if the selector cannot be parsed.
+    pub fn find(&self, css: &str) -> WebDriverResult<SnapshotElement<'a>> {
+        first_match(self.find_all(css)?, css)
+    }
+
+    /// Find all descendants of this element matching the specified CSS selector, in
+    /// document order.
+    ///
+    /// Returns `WebDriverError::InvalidSelector` if the selector cannot be parsed.
+    pub fn find_all(&self, css: &str) -> WebDriverResult<Vec<SnapshotElement<'a>>> {
+        let selector = parse_selector(css)?;
+        Ok(self.elem.select(&selector).map(|elem| self.snapshot.wrap(elem)).collect())
+    }
+
+    /// The child elements of this element.
+    pub fn children(&self) -> impl Iterator<Item = SnapshotElement<'a>> + 'a {
+        let snapshot = self.snapshot;
+        self.elem.child_elements().map(move |elem| snapshot.wrap(elem))
+    }
+
+    /// The parent element of this element, or `None` for the root of the snapshot.
+    pub fn parent(&self) -> Option<SnapshotElement<'a>> {
+        if self.elem == self.snapshot.root().elem {
+            return None;
+        }
+        self.elem.parent().and_then(ElementRef::wrap).map(|elem| self.snapshot.wrap(elem))
+    }
+
+    /// The underlying [`scraper`] element.
+    pub fn element(&self) -> ElementRef<'a> {
+        self.elem
+    }
+
+    /// The number of this element in the page (see `take_element_indices()`).
+    fn index(&self) -> Option<usize> {
+        self.snapshot.indices.get(&self.elem.id()).copied()
+    }
+
+    /// Find the live `WebElement` for this element.
+    ///
+    /// When the snapshot is taken, the page records which element each snapshot element was
+    /// copied from, so this finds the same element even if the page has changed since,
+    /// as long as the element is still in the page. The page only keeps this record for
+    /// its 16 most recent snapshots, and loses it when it navigates.
+    ///
+    /// Returns `WebDriverError::NoSuchElement` if the element is no longer in the page, if
+    /// its tag name or id have changed, or if it was added by the HTML parser (such as a
+    /// `<tbody>` that is not in the page).
+    ///
+    /// The returned future does not borrow the snapshot.
+    pub fn live(&self) -> impl Future<Output = WebDriverResult<WebElement>> + Send + 'static {
+        let handle = self.snapshot.handle.clone();
+        let frames = self.snapshot.frames.clone();
+        let snapshot_id = self.snapshot.id.clone();
+        let index = self.index();
+        let tag_name = self.tag_name().to_string();
+        let id = self.id().map(String::from);
+        async move {
+            let Some(index) = index else {
+                return Err(no_such_element(format!(
+                    "no such element: the <{tag_name}> element in the DOM snapshot was added \
+                     by the HTML parser, and is not in the page"
+                )));
+            };
+            let args = vec![json!(snapshot_id), json!(index), json!(tag_name), json!(id)];
+            let ret = if frames.is_empty() {
+                handle.execute(RESOLVE_SNAPSHOT_ELEMENT, args).await?
+            } else {
+                handle
+                    .in_frame_path(&frames, handle.execute(RESOLVE_SNAPSHOT_ELEMENT, args))
+                    .await?
+            };
+            if ret.json().is_null() {
+                return Err(no_such_element(format!(
+                    "no such element: the <{tag_name}> element in the DOM snapshot is no longer \
+                     in the page"
+                )));
+            }
+            Ok(ret.element()?.in_frames(&frames))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_send() {
+        fn is_send<T: Send>() {}
+        is_send::<DomSnapshot>();
+    }
+
+    #[test]
+    fn test_take_element_indices() {
+        let mut html = Html::parse_document(
+            "<!DOCTYPE html><html data-thirtyfour-snapshot='0'><head data-thirtyfour-snapshot='1'>\
+             </head><body data-thirtyfour-snapshot='2'><table data-thirtyfour-snapshot='3'>\
+             <tr data-thirtyfour-snapshot='4'><td id='b' data-thirtyfour-snapshot='5'>b</td>\
+             </tr></table></body></html>",
+        );
+        let indices = take_element_indices(&mut html);
+        assert!(!html.html().contains(INDEX_ATTRIBUTE));
+
+        let index = |css: &str| {
+            let selector = Selector::parse(css).unwrap();
+            indices.get(&html.select(&selector).next().unwrap().id()).copied()
+        };
+        assert_eq!(index("html"), Some(0));
+        assert_eq!(index("tr"), Some(4));
+        assert_eq!(index("#b"), Some(5));
+        // The parser adds a <tbody>, which is not in the page.
+        assert_eq!(index("tbody"), None);
+
+        let selector = Selector::parse("#b").unwrap();
+        let elem = html.select(&selector).next().unwrap();
+        assert_eq!(elem.value().attrs().count(), 1);
+    }
+}
@@ -47,3 +47,823 @@ function simulateDragDrop(sourceNode, destinationNode) {
 }
 
 simulateDragDrop(arguments[0], arguments[1]);"#;
+
+/// A javascript function for filtering elements by their position relative to other elements.
+///
+/// Returns the indices of the matching candidates, sorted by distance from the first anchor.
+pub const FILTER_RELATIVE: &str = r#"
+function filterRelative(candidates, relations) {
+    function rect(elem) {
+        return elem.getBoundingClientRect();
+    }
+
+    function center(r) {
+        return { x: r.left + r.width / 2, y: r.top + r.height / 2 };
+    }
+
+    function gap(a, b) {
+        var dx = Math.max(0, a.left - b.right, b.left - a.right);
+        var dy = Math.max(0, a.top - b.bottom, b.top - a.bottom);
+        return Math.sqrt(dx * dx + dy * dy);
+    }
+
+    function matches(elem, r, relation) {
+        if (elem === relation.anchor || elem.contains(relation.anchor)) {
+            return false;
+        }
+        var a = rect(relation.anchor);
+        switch (relation.kind) {
+            case "above": return r.bottom <= a.top;
+            case "below": return r.top >= a.bottom;
+            case "left_of": return r.right <= a.left;
+            case "right_of": return r.left >= a.right;
+            case "near": return gap(r, a) <= relation.distance;
+        }
+        return false;
+    }
+
+    var origin = relations.length > 0 ? center(rect(relations[0].anchor)) : null;
+    var results = [];
+    for (var i = 0; i < candidates.length; i++) {
+        var r = rect(candidates[i]);
+        var ok = relations.every(function (relation) {
+            return matches(candidates[i], r, relation);
+        });
+        if (ok) {
+            var c = center(r);
+            var distance = origin ? Math.hypot(c.x - origin.x, c.y - origin.y) : 0;
+            results.push({ index: i, distance: distance });
+        }
+    }
+    results.sort(function (a, b) { return a.distance - b.distance; });
+    return results.map(function (result) { return result.index; });
+}
+
+return filterRelative(arguments[0], arguments[1]);"#;
+
+/// A javascript function for finding elements the way a user would, i.e. by their role,
+/// label, placeholder, visible text or alt text.
+///
+/// Takes the root element (or null for the whole document) and a query object, and returns
+/// the matching elements in document order.
+pub const FIND_ACCESSIBLE: &str = r#"
+function findAccessible(root, query) {
+    var IMPLICIT_ROLES = {
+        ARTICLE: "article", ASIDE: "complementary", BUTTON: "button", DATALIST: "listbox",
+        DD: "definition", DETAILS: "group", DIALOG: "dialog", DT: "term", FIELDSET: "group",
+        FIGURE: "figure", FOOTER: "contentinfo", FORM: "form", H1: "heading", H2: "heading",
+        H3: "heading", H4: "heading", H5: "heading", H6: "heading", HEADER: "banner",
+        HR: "separator", LI: "listitem", MAIN: "main", MENU: "list", METER: "meter",
+        NAV: "navigation", OL: "list", OPTGROUP: "group", OPTION: "option", OUTPUT: "status",
+        PROGRESS: "progressbar", SECTION: "region", SUMMARY: "button", TABLE: "table",
+        TBODY: "rowgroup", TD: "cell", TEXTAREA: "textbox", TFOOT: "rowgroup", THEAD: "rowgroup",
+        TR: "row", UL: "list"
+    };
+    var INPUT_ROLES = {
+        button: "button", checkbox: "checkbox", email: "textbox", image: "button",
+        number: "spinbutton", radio: "radio", range: "slider", reset: "button",
+        search: "searchbox", submit: "button", tel: "textbox", text: "textbox", url: "textbox"
+    };
+    var NAME_FROM_CONTENT = [
+        "button", "cell", "checkbox", "columnheader", "gridcell", "heading", "link", "menuitem",
+        "menuitemcheckbox", "menuitemradio", "option", "radio", "row", "rowheader", "switch",
+        "tab", "tooltip", "treeitem"
+    ];
+
+    function normalize(text) {
+        return (text || "").replace(/\s+/g, " ").trim();
+    }
+
+    function matches(actual, expected, exact) {
+        actual = normalize(actual);
+        expected = normalize(expected);
+        if (exact) {
+            return actual === expected;
+        }
+        return actual.toLowerCase().indexOf(expected.toLowerCase()) !== -1;
+    }
+
+    // Computed styles, visibility and text are cached per element, since they are needed
+    // for the same elements (and their ancestors) many times.
+    var styles = new Map();
+    var hiddenTrees = new Map();
+    var texts = [new Map(), new Map()];
+
+    function styleOf(elem) {
+        var style = styles.get(elem);
+        if (!style) {
+            style = window.getComputedStyle(elem);
+            styles.set(elem, style);
+        }
+        return style;
+    }
+
+    // Whether the element, and therefore everything inside it, is hidden.
+    function isHiddenTree(elem) {
+        if (!elem || elem.nodeType !== 1) {
+            return false;
+        }
+        var hidden = hiddenTrees.get(elem);
+        if (hidden === undefined) {
+            hidden = elem.hidden || elem.getAttribute("aria-hidden") === "true"
+                || styleOf(elem).display === "none" || isHiddenTree(elem.parentElement);
+            hiddenTrees.set(elem, hidden);
+        }
+        return hidden;
+    }
+
+    function isHidden(elem) {
+        return isHiddenTree(elem) || styleOf(elem).visibility === "hidden";
+    }
+
+    function roleOf(elem) {
+        var explicit = normalize(elem.getAttribute("role")).split(" ")[0];
+        if (explicit) {
+            return explicit;
+        }
+        var tag = elem.tagName.toUpperCase();
+        switch (tag) {
+            case "A":
+            case "AREA":
+                return elem.hasAttribute("href") ? "link" : null;
+            case "IMG":
+                return elem.getAttribute("alt") === "" ? "presentation" : "img";
+            case "INPUT":
+                var type = (elem.getAttribute("type") || "text").toLowerCase();
+                if (elem.hasAttribute("list") && INPUT_ROLES[type] === "textbox") {
+                    return "combobox";
+                }
+                return INPUT_ROLES[type] || null;
+            case "SELECT":
+                return elem.multiple || elem.size > 1 ? "listbox" : "combobox";
+            case "TH":
+                return elem.closest("thead") ? "columnheader" : "rowheader";
+        }
+        return IMPLICIT_ROLES[tag] || null;
+    }
+
+    // The visible text of an element. If `forName` is true, this includes alt text and
+    // aria-label, as used when computing the accessible name.
+    function textOf(elem, forName) {
+        if (elem.nodeType === 3) {
+            return elem.textContent;
+        }
+        if (elem.nodeType !== 1) {
+            return "";
+        }
+        var cache = texts[forName ? 1 : 0];
+        var text = cache.get(elem);
+        if (text === undefined) {
+            text = isHidden(elem) ? "" : computeText(elem, forName);
+            cache.set(elem, text);
+        }
+        return text;
+    }
+
+    // The text of a visible element, built from the (cached) text of its children.
+    function computeText(elem, forName) {
+        var tag = elem.tagName.toUpperCase();
+        if (tag === "SCRIPT" || tag === "STYLE" || tag === "TEMPLATE" || tag === "NOSCRIPT") {
+            return "";
+        }
+        if (tag === "INPUT" && ["button", "submit", "reset"].indexOf(elem.type) !== -1) {
+            return elem.value;
+        }
+        if (forName) {
+            if (tag === "IMG") {
+                return elem.getAttribute("alt") || "";
+            }
+            var label = elem.getAttribute("aria-label");
+            if (normalize(label)) {
+                return label;
+            }
+        }
+        var parts = [];
+        for (var child = elem.firstChild; child; child = child.nextSibling) {
+            parts.push(textOf(child, forName));
+        }
+        return parts.join(styleOf(elem).display === "inline" ? "" : " ");
+    }
+
+    function labelsOf(elem) {
+        var texts = [];
+        var labelledBy = normalize(elem.getAttribute("aria-labelledby"));
+        if (labelledBy) {
+            labelledBy.split(" ").forEach(function (id) {
+                var label = document.getElementById(id);
+                if (label) {
+                    texts.push(textOf(label, true));
+                }
+            });
+        }
+        if (elem.hasAttribute("aria-label")) {
+            texts.push(elem.getAttribute("aria-label"));
+        }
+        Array.prototype.forEach.call(elem.labels || [], function (label) {
+            texts.push(textOf(label, true));
+        });
+        return texts.map(normalize).filter(function (text) { return text; });
+    }
+
+    function nameOf(elem, role) {
+        var labels = labelsOf(elem);
+        if (labels.length > 0) {
+            return labels[0];
+        }
+        var tag = elem.tagName.toUpperCase();
+        if (tag === "IMG" || (tag === "INPUT" && elem.type === "image")) {
+            if (elem.getAttribute("alt")) {
+                return elem.getAttribute("alt");
+            }
+        }
+        if (tag === "INPUT" && ["button", "submit", "reset"].indexOf(elem.type) !== -1) {
+            return elem.value || { submit: "Submit", reset: "Reset" }[elem.type] || "";
+        }
+        if (tag === "FIELDSET" || tag === "TABLE" || tag === "FIGURE") {
+            var caption = elem.querySelector("legend, caption, figcaption");
+            if (caption) {
+                return textOf(caption, true);
+            }
+        }
+        if (NAME_FROM_CONTENT.indexOf(role) !== -1) {
+            var text = normalize(textOf(elem, true));
+            if (text) {
+                return text;
+            }
+        }
+        return elem.getAttribute("title") || elem.getAttribute("placeholder") || "";
+    }
+
+    function ownText(elem) {
+        var tag = elem.tagName.toUpperCase();
+        if (tag === "INPUT") {
+            return ["button", "submit", "reset"].indexOf(elem.type) !== -1 ? elem.value : null;
+        }
+        if (tag === "HEAD" || tag === "TITLE") {
+            return null;
+        }
+        return textOf(elem, false);
+    }
+
+    function isMatch(elem) {
+        switch (query.kind) {
+            case "role":
+                if (roleOf(elem) !== query.role || isHidden(elem)) {
+                    return false;
+                }
+                return !query.name || matches(nameOf(elem, query.role), query.name, true);
+            case "label":
+                return labelsOf(elem).some(function (text) {
+                    return matches(text, query.text, true);
+                });
+            case "placeholder":
+                return elem.hasAttribute("placeholder")
+                    && matches(elem.getAttribute("placeholder"), query.text, true);
+            case "alt_text":
+                return elem.hasAttribute("alt") && matches(elem.getAttribute("alt"), query.text, true);
+            case "text":
+                var text = ownText(elem);
+                if (text === null || !matches(text, query.text, query.exact)) {
+                    return false;
+                }
+                // Only match the innermost elements containing the text.
+                for (var child = elem.firstElementChild; child; child = child.nextElementSibling) {
+                    var childText = ownText(child);
+                    if (childText !== null && matches(childText, query.text, query.exact)) {
+                        return false;
+                    }
+                }
+                return true;
+        }
+        return false;
+    }
+
+    // Roles and text are only matched for visible elements, so skip hidden subtrees.
+    var visibleOnly = query.kind === "role" || query.kind === "text";
+    var walker = document.createTreeWalker(root || document, NodeFilter.SHOW_ELEMENT, {
+        acceptNode: function (node) {
+            if (visibleOnly && isHiddenTree(node)) {
+                return NodeFilter.FILTER_REJECT;
+            }
+            return isMatch(node) ? NodeFilter.FILTER_ACCEPT : NodeFilter.FILTER_SKIP;
+        }
+    });
+    var found = [];
+    while (walker.nextNode()) {
+        found.push(walker.currentNode);
+    }
+    return found;
+}
+
+return findAccessible(arguments[0], arguments[1]);"#;
+
+/// A javascript function for finding elements using CSS selectors separated by `>>>`,
+/// where each `>>>` descends into the open shadow root of the elements matched so far.
+///
+/// Takes the root element (or null for the whole document) and the list of CSS selectors,
+/// and returns the matching elements.
+pub const FIND_DEEP_CSS: &str = r#"
+function findDeepCss(root, selectors) {
+    var scopes = [root || document];
+    for (var i = 0; i < selectors.length; i++) {
+        var found = [];
+        scopes.forEach(function (scope) {
+            if (i > 0) {
+                scope = scope.shadowRoot;
+                if (!scope) {
+                    return;
+                }
+            }
+            Array.prototype.forEach.call(scope.querySelectorAll(selectors[i]), function (elem) {
+                if (found.indexOf(elem) === -1) {
+                    found.push(elem);
+                }
+            });
+        });
+        scopes = found;
+    }
+    return scopes;
+}
+
+return findDeepCss(arguments[0], arguments[1]);"#;
+
+/// A javascript function for sorting elements in document order or by on-screen position.
+///
+/// Takes the elements (which must all be in the same frame) and the order (`"document"` or
+/// `"position"`), and returns the indices of the elements in sorted order.
+pub const SORT_ELEMENTS: &str = r#"
+function sortElements(elements, order) {
+    function documentOrder(a, b) {
+        if (a === b) {
+            return 0;
+        }
+        var pos = a.compareDocumentPosition(b);
+        if (pos & Node.DOCUMENT_POSITION_DISCONNECTED) {
+            return 0;
+        }
+        if (pos & Node.DOCUMENT_POSITION_FOLLOWING) {
+            return -1;
+        }
+        if (pos & Node.DOCUMENT_POSITION_PRECEDING) {
+            return 1;
+        }
+        return 0;
+    }
+    var rects = order === "position" ? elements.map(function (elem) {
+        return elem.getBoundingClientRect();
+    }) : null;
+    var indices = elements.map(function (_, i) {
+        return i;
+    });
+    indices.sort(function (i, j) {
+        if (rects) {
+            var dy = Math.round(rects[i].top) - Math.round(rects[j].top);
+            if (dy !== 0) {
+                return dy;
+            }
+            var dx = Math.round(rects[i].left) - Math.round(rects[j].left);
+            if (dx !== 0) {
+                return dx;
+            }
+        }
+        return documentOrder(elements[i], elements[j]) || i - j;
+    });
+    return indices;
+}
+
+return sortElements(arguments[0], arguments[1]);"#;
+
+/// A javascript function for scrolling a container (or the window) down by most of its
+/// visible height, used to load more items in `ElementQuery::stream()`.
+///
+/// Takes the container element (or null for the window), and returns whether the scroll
+/// position changed.
+pub const SCROLL_FOR_MORE: &str = r#"
+function scrollForMore(container) {
+    var scroller = container || document.scrollingElement || document.documentElement;
+    var before = scroller.scrollTop;
+    var height = container ? container.clientHeight : window.innerHeight;
+    scroller.scrollTo({ top: before + Math.max(Math.floor(height * 0.9), 1), behavior: "instant" });
+    return scroller.scrollTop !== before;
+}
+
+return scrollForMore(arguments[0]);"#;
+
+/// A javascript function for serializing the document for a `DomSnapshot`.
+///
+/// Takes the root element of the snapshot (or null for the whole document). Each element
+/// in the document is numbered in document order, starting with 0 for the `<html>` element,
+/// and the number is recorded in a `WeakMap` kept on the window, so that the element can be
+/// found again by `RESOLVE_SNAPSHOT_ELEMENT`. Only the most recent snapshots are kept.
+///
+/// Returns the id of the snapshot, the HTML of an inert copy of the document in which each
+/// element has its number in the `data-thirtyfour-snapshot` attribute, and the number of
+/// the root element. The root is null if the root element is not in the document (e.g. in
+/// a shadow root).
+#[cfg(feature = "dom-snapshot")]
+pub const SNAPSHOT_DOM: &str = r#"
+function snapshotDom(root) {
+    var store = window.__thirtyfourSnapshots;
+    if (!store) {
+        store = { ids: [], indices: {} };
+        Object.defineProperty(window, "__thirtyfourSnapshots", { value: store });
+    }
+
+    // Copy the document into a document without a browsing context, so that the copy does
+    // not load images or run scripts.
+    var source = document.documentElement;
+    var copy = document.implementation.createHTMLDocument("").importNode(source, true);
+    var elements = [source].concat(Array.prototype.slice.call(source.querySelectorAll("*")));
+    var copies = [copy].concat(Array.prototype.slice.call(copy.querySelectorAll("*")));
+    var indices = new WeakMap();
+    for (var i = 0; i < elements.length && i < copies.length; i++) {
+        indices.set(elements[i], i);
+        copies[i].setAttribute("data-thirtyfour-snapshot", String(i));
+    }
+
+    var id = Date.now().toString(36) + "-" + Math.random().toString(36).slice(2);
+    store.ids.push(id);
+    store.indices[id] = indices;
+    while (store.ids.length > 16) {
+        delete store.indices[store.ids.shift()];
+    }
+
+    var doctype = document.doctype ? "<!DOCTYPE " + document.doctype.name + ">" : "";
+    var rootIndex = root ? indices.get(root) : 0;
+    return {
+        id: id,
+        html: doctype + copy.outerHTML,
+        root: rootIndex === undefined ? null : rootIndex
+    };
+}
+
+return snapshotDom(arguments[0]);"#;
+
+/// A javascript function for finding the live element for an element in a `DomSnapshot`.
+///
+/// Takes the id of the snapshot and the number of the element (see `SNAPSHOT_DOM`), and its
+/// expected local name and id. Returns the element, or null if it is no longer in the
+/// document, if its local name or id have changed, or if the snapshot is no longer kept.
+#[cfg(feature = "dom-snapshot")]
+pub const RESOLVE_SNAPSHOT_ELEMENT: &str = r#"
+function resolveSnapshotElement(snapshotId, index, localName, id) {
+    var store = window.__thirtyfourSnapshots;
+    var indices = store && store.indices[snapshotId];
+    if (!indices) {
+        return null;
+    }
+    var source = document.documentElement;
+    var elements = [source].concat(Array.prototype.slice.call(source.querySelectorAll("*")));
+    for (var i = 0; i < elements.length; i++) {
+        var elem = elements[i];
+        if (indices.get(elem) === index) {
+            return elem.localName === localName && elem.getAttribute("id") === id ? elem : null;
+        }
+    }
+    return null;
+}
+
+return resolveSnapshotElement(arguments[0], arguments[1], arguments[2], arguments[3]);"#;
+
+/// A javascript function wrapping the script of a `By::Js()` selector, where the script is
+/// inserted in place of `/*SCRIPT*/`.
+///
+/// Takes the root element (or null for the whole document) and the script arguments, calls
+/// the script with the root element as `this`, and returns the resulting elements as an array.
+const FIND_BY_SCRIPT: &str = r#"
+function findByScript(root, args) {
+    var result = (function () {
+/*SCRIPT*/
+    }).apply(root || document, args);
+    if (result === null || result === undefined) {
+        return [];
+    }
+    if (result.nodeType === 1) {
+        return [result];
+    }
+    if (typeof result.length !== "number") {
+        throw new Error("By::Js() script must return an element or an array of elements");
+    }
+    var elements = Array.prototype.slice.call(result);
+    elements.forEach(function (elem) {
+        if (!elem || elem.nodeType !== 1) {
+            throw new Error("By::Js() script returned something other than an element");
+        }
+    });
+    return elements;
+}
+
+return findByScript(arguments[0], arguments[1]);"#;
+
+/// Get the script used to find elements via the specified `By::Js()` script.
+pub fn find_by_script(script: &str) -> String {
+    FIND_BY_SCRIPT.replace("/*SCRIPT*/", script)
+}
+
+/// A javascript function for reading values from many elements at once, used to check the
+/// built-in `ElementQuery` filters with a single script execution.
+///
+/// Takes the elements and a list of probes, and returns an array of values for each element
+/// (one per probe). Throws if a value cannot be read the same way as via WebDriver.
+pub const READ_ELEMENT_VALUES: &str = r#"
+function readElementValues(elements, probes) {
+    function isHiddenByStyle(elem) {
+        if (typeof elem.checkVisibility === "function") {
+            return !elem.checkVisibility({ visibilityProperty: true });
+        }
+        for (var e = elem; e && e.nodeType === 1; e = e.parentElement) {
+            if (window.getComputedStyle(e).display === "none") {
+                return true;
+            }
+        }
+        return window.getComputedStyle(elem).visibility !== "visible";
+    }
+
+    function hasSize(elem) {
+        var rect = elem.getBoundingClientRect();
+        if (rect.width > 0 && rect.height > 0) {
+            return true;
+        }
+        for (var child = elem.firstElementChild; child; child = child.nextElementSibling) {
+            if (hasSize(child)) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    function isDisplayed(elem) {
+        var tag = elem.tagName.toUpperCase();
+        if (tag === "OPTION" || tag === "OPTGROUP") {
+            var select = elem.closest("select, datalist");
+            return select ? isDisplayed(select) : false;
+        }
+        if (tag === "INPUT" && elem.type === "hidden") {
+            return false;
+        }
+        return !isHiddenByStyle(elem) && hasSize(elem);
+    }
+
+    function isSelected(elem) {
+        if (elem.tagName.toUpperCase() === "OPTION") {
+            return elem.selected;
+        }
+        if (elem.type === "checkbox" || elem.type === "radio") {
+            return elem.checked;
+        }
+        return false;
+    }
+
+    function read(elem, probe) {
+        switch (probe.kind) {
+            case "text":
+                var text = typeof elem.innerText === "string" ? elem.innerText : elem.textContent;
+                return text.trim();
+            case "tag_name":
+                return elem.tagName.toLowerCase();
+            case "attribute":
+                return elem.getAttribute(probe.name);
+            case "property":
+                var value = elem[probe.name];
+                if (value === undefined) {
+                    return null;
+                }
+                if (typeof value === "object" && value !== null) {
+                    throw new Error("unsupported value for property " + probe.name);
+                }
+                return value;
+            case "css_value":
+                return window.getComputedStyle(elem).getPropertyValue(probe.name);
+            case "displayed":
+                return isDisplayed(elem);
+            case "enabled":
+                return !elem.matches(":disabled");
+            case "selected":
+                return isSelected(elem);
+        }
+        throw new Error("unknown probe " + probe.kind);
+    }
+
+    return elements.map(function (elem) {
+        return probes.map(function (probe) {
+            return read(elem, probe);
+        });
+    });
+}
+
+return readElementValues(arguments[0], arguments[1]);"#;
+
+/// A javascript function (for use with `execute_async`) that waits for the DOM to change.
+///
+/// A MutationObserver is installed on the document the first time this runs, and counts
+/// all changes. Takes the count seen by the previous call (or null) and the maximum time to
+/// wait in milliseconds, and returns the current count as soon as it differs from the
+/// previous one, or the wait expires. Returns immediately if the observer was not installed
+/// yet, since any changes before that point were missed.
+pub const WAIT_FOR_MUTATION: &str = r#"
+function waitForMutation(seen, wait, done) {
+    var state = window.__thirtyfourMutations;
+    if (!state) {
+        state = { count: 0, waiters: [] };
+        var observer = new MutationObserver(function () {
+            state.count++;
+            var waiters = state.waiters;
+            state.waiters = [];
+            waiters.forEach(function (waiter) {
+                waiter();
+            });
+        });
+        observer.observe(document, {
+            subtree: true,
+            childList: true,
+            attributes: true,
+            characterData: true
+        });
+        Object.defineProperty(window, "__thirtyfourMutations", { value: state });
+        done(state.count);
+        return;
+    }
+    if (seen === null || seen !== state.count) {
+        done(state.count);
+        return;
+    }
+
+    var finished = false;
+    function finish() {
+        if (!finished) {
+            finished = true;
+            done(state.count);
+        }
+    }
+    var timer = setTimeout(function () {
+        state.waiters = state.waiters.filter(function (waiter) {
+            return waiter !== onMutation;
+        });
+        finish();
+    }, wait);
+    function onMutation() {
+        clearTimeout(timer);
+        finish();
+    }
+    state.waiters.push(onMutation);
+}
+
+waitForMutation(arguments[0], arguments[1], arguments[arguments.length - 1]);"#;
+
+/// A javascript function that reports the in-flight activity on the page, used to wait for
+/// the network to be idle or the app to be stable.
+///
+/// The first time this runs in a document, it wraps `fetch()`, `XMLHttpRequest`,
+/// `setTimeout()` and `clearTimeout()` to count pending requests and short timers, and
+/// installs a MutationObserver to record when the DOM last changed. Activity that started
+/// before that point is not counted, so the idle times start from the installation.
+///
+/// Returns the current counts, and the number of milliseconds since the last network
+/// activity and DOM change.
+pub const READ_PAGE_ACTIVITY: &str = r#"
+function readPageActivity() {
+    // Timers longer than this are assumed to be unrelated to the current activity
+    // (e.g. session expiry).
+    var MAX_TIMER_DELAY = 1000;
+
+    var state = window.__thirtyfourActivity;
+    if (!state) {
+        state = {
+            requests: 0,
+            timers: new Set(),
+            lastNetwork: performance.now(),
+            lastMutation: performance.now(),
+            lastFramework: performance.now()
+        };
+        Object.defineProperty(window, "__thirtyfourActivity", { value: state });
+
+        function requestStarted() {
+            state.requests++;
+            state.lastNetwork = performance.now();
+        }
+        function requestFinished() {
+            state.requests = Math.max(0, state.requests - 1);
+            state.lastNetwork = performance.now();
+        }
+
+        if (typeof window.fetch === "function") {
+            var originalFetch = window.fetch;
+            window.fetch = function () {
+                requestStarted();
+                var promise;
+                try {
+                    promise = originalFetch.apply(this, arguments);
+                } catch (e) {
+                    requestFinished();
+                    throw e;
+                }
+                promise.then(requestFinished, requestFinished);
+                return promise;
+            };
+        }
+
+        var originalSend = XMLHttpRequest.prototype.send;
+        XMLHttpRequest.prototype.send = function () {
+            requestStarted();
+            this.addEventListener("loadend", requestFinished);
+            try {
+                return originalSend.apply(this, arguments);
+            } catch (e) {
+                this.removeEventListener("loadend", requestFinished);
+                requestFinished();
+                throw e;
+            }
+        };
+
+        // Recurring timers (e.g. polling, clocks or carousels) would never let the page
+        // settle, so only the first run of each timer is tracked. A timer is treated as
+        // recurring if its callback has run before, or if it is scheduled by another timer.
+        var firedCallbacks = new WeakSet();
+        var inTimer = false;
+        var originalSetTimeout = window.setTimeout;
+        var originalClearTimeout = window.clearTimeout;
+        window.setTimeout = function (callback, delay) {
+            if (typeof callback !== "function" || (delay || 0) > MAX_TIMER_DELAY
+                    || inTimer || firedCallbacks.has(callback)) {
+                return originalSetTimeout.apply(this, arguments);
+            }
+            var args = Array.prototype.slice.call(arguments, 2);
+            var id = originalSetTimeout.call(this, function () {
+                state.timers.delete(id);
+                firedCallbacks.add(callback);
+                inTimer = true;
+                try {
+                    callback.apply(this, args);
+                } finally {
+                    inTimer = false;
+                }
+            }, delay);
+            state.timers.add(id);
+            return id;
+        };
+        window.clearTimeout = function (id) {
+            state.timers.delete(id);
+            return originalClearTimeout.apply(this, arguments);
+        };
+
+        new MutationObserver(function () {
+            state.lastMutation = performance.now();
+        }).observe(document, { subtree: true, childList: true, attributes: true, characterData: true });
+
+        // React reports every commit to the DevTools hook, if the page had one before React
+        // loaded (e.g. via the React DevTools extension).
+        var hook = window.__REACT_DEVTOOLS_GLOBAL_HOOK__;
+        if (hook && typeof hook.onCommitFiberRoot === "function") {
+            var originalCommit = hook.onCommitFiberRoot;
+            hook.onCommitFiberRoot = function () {
+                state.lastFramework = performance.now();
+                return originalCommit.apply(this, arguments);
+            };
+        }
+    }
+
+    function runningAnimations() {
+        if (typeof document.getAnimations !== "function") {
+            return 0;
+        }
+        return document.getAnimations().filter(function (animation) {
+            // Infinite animations (e.g. spinners) never finish.
+            return animation.playState === "running"
+                && animation.effect
+                && animation.effect.getComputedTiming().endTime !== Infinity;
+        }).length;
+    }
+
+    function frameworkBusy() {
+        if (typeof window.getAllAngularTestabilities === "function") {
+            return window.getAllAngularTestabilities().some(function (testability) {
+                return !testability.isStable();
+            });
+        }
+        if (window.angular && typeof window.angular.element === "function") {
+            try {
+                var injector = window.angular.element(document.body).injector();
+                if (injector) {
+                    return injector.get("$http").pendingRequests.length > 0;
+                }
+            } catch (e) {
+                // Not an AngularJS app after all.
+            }
+        }
+        return false;
+    }
+
+    var now = performance.now();
+    return {
+        loaded: document.readyState === "complete",
+        requests: state.requests,
+        timers: state.timers.size,
+        animations: runningAnimations(),
+        framework_busy: frameworkBusy(),
+        network_idle_ms: Math.floor(now - state.lastNetwork),
+        dom_idle_ms: Math.floor(now - state.lastMutation),
+        framework_idle_ms: Math.floor(now - state.lastFramework)
+    };
+}
+
+return readPageActivity();"#;
@@ -32,6 +32,7 @@
 //! * `rustls-tls`: (Default) Use rustls to provide TLS support (via reqwest).
 //! * `native-tls`: Use native TLS (via reqwest).
 //! * `component`: (Default) Enable the `Component` derive macro (via thirtyfour-macros).
+//! * `metrics`: Report command counts, latencies and errors via the `metrics` crate facade.
 //!
 //! ## Example
 //!
@@ -147,6 +148,8 @@
 
 // Re-export StringMatch if needed.
 pub use stringmatch;
+// Re-export CancellationToken for use with `WebDriver::with_cancellation()`.
+pub use tokio_util::sync::CancellationToken;
 
 // Export types at root level.
 pub use alert::Alert;
@@ -165,9 +168,11 @@ pub use common::{
     command::By,
     cookie::*,
     keys::*,
+    locators::LocatorRepository,
     requestdata::*,
     types::*,
 };
+pub use common::{css, xpath};
 pub use switch_to::SwitchTo;
 pub use web_driver::WebDriver;
 pub use web_element::WebElement;
@@ -176,7 +181,9 @@ pub use web_element::WebElement;
 pub mod prelude {
     pub use crate::alert::Alert;
     pub use crate::error::{WebDriverError, WebDriverResult};
-    pub use crate::extensions::query::{ElementPoller, ElementQueryable, ElementWaitable};
+    pub use crate::extensions::query::{
+        DriverWaitable, ElementPoller, ElementQueryable, ElementWaitable,
+    };
     pub use crate::session::scriptret::ScriptRet;
     pub use crate::switch_to::SwitchTo;
     pub use crate::WebDriver;
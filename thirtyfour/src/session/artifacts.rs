@@ -0,0 +1,285 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::future::BoxFuture;
+
+use crate::error::{WebDriverError, WebDriverErrorInner, WebDriverResult};
+use crate::session::handle::SessionHandle;
+use crate::session::http::CmdResponse;
+use crate::{support, RequestData};
+
+/// The number of recent commands included in the artifacts.
+const HISTORY_LEN: usize = 50;
+
+/// Shared state used to save failure artifacts for a session.
+#[derive(Debug)]
+pub(crate) struct FailureArtifacts {
+    dir: PathBuf,
+    started: Instant,
+    history: Mutex<VecDeque<String>>,
+    test_name: Mutex<Option<String>>,
+    /// The tests whose artifacts are currently being saved.
+    capturing: Mutex<HashSet<String>>,
+    count: AtomicUsize,
+}
+
+impl FailureArtifacts {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            started: Instant::now(),
+            history: Mutex::new(VecDeque::with_capacity(HISTORY_LEN)),
+            test_name: Mutex::new(None),
+            capturing: Mutex::new(HashSet::new()),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add the command to the history of recent commands.
+    pub(crate) fn record(
+        &self,
+        command: &str,
+        request: &RequestData,
+        elapsed: Duration,
+        result: &WebDriverResult<CmdResponse>,
+    ) {
+        let outcome = match result {
+            Ok(_) => "ok".to_string(),
+            Err(e) => format!("error: {}", e.kind()),
+        };
+        let entry = format!(
+            "[+{:.3?}] {command} {} {} -> {outcome} ({elapsed:.3?})",
+            self.started.elapsed(),
+            request.method,
+            request.uri
+        );
+
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        if history.len() == HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(entry);
+    }
+
+    /// Returns true if artifacts should be saved automatically for the specified error.
+    ///
+    /// Errors that are routinely handled (e.g. while polling for an element, or retrying
+    /// an interaction) are skipped, as are errors where the browser is unlikely to respond.
+    /// If they cause a test to fail, the artifacts are still saved by the
+    /// `FailureArtifactsGuard`.
+    pub(crate) fn should_capture(&self, command: &str, error: &WebDriverError) -> bool {
+        !matches!(
+            **error,
+            WebDriverErrorInner::NoSuchElement(_)
+                | WebDriverErrorInner::StaleElementReference(_)
+                | WebDriverErrorInner::NoSuchAlert(_)
+                | WebDriverErrorInner::ElementClickIntercepted(_)
+                | WebDriverErrorInner::ElementNotInteractable(_)
+                | WebDriverErrorInner::JavascriptError(_)
+                | WebDriverErrorInner::CommandTimeout(_)
+                | WebDriverErrorInner::CommandCancelled(_)
+                | WebDriverErrorInner::RequestFailed(_)
+        ) && command != "DeleteSession"
+    }
+
+    /// Save the artifacts into a new directory under the directory for the specified test.
+    ///
+    /// Failures while the artifacts for the same test are already being saved (including
+    /// failures of the commands used to save them) are skipped.
+    pub(crate) fn capture<'a>(
+        &'a self,
+        handle: &'a SessionHandle,
+        test_name: String,
+        label: &'a str,
+        reason: &'a str,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let Some(_capturing) = CapturingGuard::new(&self.capturing, &test_name) else {
+                tracing::info!(
+                    "skipped failure artifacts for {label} ({reason}): artifacts for \
+                     {test_name} are already being saved"
+                );
+                return;
+            };
+
+            let n = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+            let dir = self.dir.join(&test_name).join(format!("{n:02}-{label}"));
+            let history = {
+                let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+                history.iter().map(|entry| format!("{entry}\n")).collect::<String>()
+            };
+            match handle.save_artifacts(&dir, reason, &history).await {
+                Ok(()) => tracing::info!("failure artifacts saved to {}", dir.display()),
+                Err(e) => {
+                    tracing::warn!("failed to save failure artifacts to {}: {e}", dir.display())
+                }
+            }
+        })
+    }
+
+    /// The name of the directory for the current test.
+    ///
+    /// This is the name given to the `FailureArtifactsGuard`, if any, otherwise the name of
+    /// the current thread (the default test harness names each thread after the test).
+    pub(crate) fn test_name(&self, handle: &SessionHandle) -> String {
+        let name = self.test_name.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let name = name
+            .or_else(|| std::thread::current().name().map(String::from))
+            .filter(|name| name != "main" && !name.starts_with("tokio-runtime"))
+            .unwrap_or_else(|| format!("session-{}", handle.session_id()));
+        name.chars()
+            .map(|c| {
+                if c.is_alphanumeric() || "-_.".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+}
+
+/// Marks the artifacts for a test as being saved, until it is dropped.
+struct CapturingGuard<'a> {
+    capturing: &'a Mutex<HashSet<String>>,
+    test_name: &'a str,
+}
+
+impl<'a> CapturingGuard<'a> {
+    /// Returns `None` if the artifacts for the test are already being saved.
+    fn new(capturing: &'a Mutex<HashSet<String>>, test_name: &'a str) -> Option<Self> {
+        let mut tests = capturing.lock().unwrap_or_else(|e| e.into_inner());
+        // Only create the guard if it was inserted, since dropping it takes the lock.
+        tests.insert(test_name.to_string()).then(|| Self {
+            capturing,
+            test_name,
+        })
+    }
+}
+
+impl Drop for CapturingGuard<'_> {
+    fn drop(&mut self) {
+        self.capturing.lock().unwrap_or_else(|e| e.into_inner()).remove(self.test_name);
+    }
+}
+
+/// Guard that saves failure artifacts if it is dropped while the current thread is panicking.
+///
+/// Create this at the start of a test via `SessionHandle::failure_artifacts_guard()`.
+/// Artifacts saved while the guard is alive are stored in a directory named after the test.
+///
+/// Does nothing unless `WebDriverConfigBuilder::failure_artifacts_dir()` was set.
+#[derive(Debug)]
+#[must_use = "the artifacts are only saved when the guard is dropped"]
+pub struct FailureArtifactsGuard {
+    pub(crate) handle: Arc<SessionHandle>,
+}
+
+impl Drop for FailureArtifactsGuard {
+    fn drop(&mut self) {
+        let Some(artifacts) = self.handle.artifacts.clone() else {
+            return;
+        };
+
+        let test_name = artifacts.test_name(&self.handle);
+        *artifacts.test_name.lock().unwrap_or_else(|e| e.into_inner()) = None;
+
+        if std::thread::panicking() {
+            let handle = Arc::clone(&self.handle);
+            support::spawn_blocked_future(|spawned| async move {
+                let handle = if spawned {
+                    // Old I/O drivers may be destroyed at this point
+                    let client = handle.client.new().await;
+                    Arc::new(handle.clone_with_client(client))
+                } else {
+                    handle
+                };
+                artifacts.capture(&handle, test_name, "panic", "the test panicked").await;
+            });
+        }
+    }
+}
+
+impl SessionHandle {
+    /// Create a guard that saves failure artifacts if the test panics.
+    ///
+    /// Any artifacts saved while the guard is alive (including those saved automatically
+    /// when a command fails) are stored in a directory named after `test_name`.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// use thirtyfour::common::config::WebDriverConfig;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// let caps = DesiredCapabilities::chrome();
+    /// let config = WebDriverConfig::builder().failure_artifacts_dir("target/artifacts").build()?;
+    /// let driver = WebDriver::new_with_config("http://localhost:4444", caps, config).await?;
+    /// let _guard = driver.failure_artifacts_guard("login_works");
+    /// driver.goto("https://www.rust-lang.org").await?;
+    /// assert_eq!(driver.title().await?, "Rust Programming Language");
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn failure_artifacts_guard(
+        self: &Arc<Self>,
+        test_name: impl Into<String>,
+    ) -> FailureArtifactsGuard {
+        if let Some(artifacts) = &self.artifacts {
+            *artifacts.test_name.lock().unwrap_or_else(|e| e.into_inner()) = Some(test_name.into());
+        }
+        FailureArtifactsGuard {
+            handle: Arc::clone(self),
+        }
+    }
+
+    /// Save a screenshot, the page source, the current URL, the specified error and the
+    /// specified command history into the specified directory.
+    ///
+    /// This is called automatically if `WebDriverConfigBuilder::failure_artifacts_dir()`
+    /// was set, but can also be used directly.
+    pub async fn save_artifacts(
+        &self,
+        dir: &Path,
+        error: &str,
+        history: &str,
+    ) -> WebDriverResult<()> {
+        let create_dir = dir.to_owned();
+        tokio::task::spawn_blocking(move || std::fs::create_dir_all(create_dir))
+            .await
+            .map_err(std::io::Error::from)??;
+
+        support::write_file(dir.join("error.txt"), error).await?;
+        support::write_file(dir.join("history.txt"), history).await?;
+        // Save as much as possible, even if the browser is in a bad state.
+        let url = self.current_url().await.map(String::from);
+        let source = self.source().await;
+        let screenshot = self.screenshot(&dir.join("screenshot.png")).await;
+        support::write_file(dir.join("url.txt"), url?).await?;
+        support::write_file(dir.join("page_source.html"), source?).await?;
+        screenshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capturing_guard() {
+        let capturing = Mutex::new(HashSet::new());
+        let guard = CapturingGuard::new(&capturing, "a").unwrap();
+        assert!(CapturingGuard::new(&capturing, "a").is_none());
+        // Failures in other tests are still captured.
+        assert!(CapturingGuard::new(&capturing, "b").is_some());
+        drop(guard);
+        assert!(CapturingGuard::new(&capturing, "a").is_some());
+    }
+}
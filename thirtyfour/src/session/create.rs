@@ -9,7 +9,7 @@ use crate::{
         config::WebDriverConfig,
     },
     prelude::WebDriverResult,
-    session::http::run_webdriver_cmd,
+    session::http::{command_span, run_traced, run_webdriver_cmd},
     Capabilities, SessionId, TimeoutConfiguration,
 };
 
@@ -21,26 +21,30 @@ pub async fn start_session(
     config: &WebDriverConfig,
     capabilities: Capabilities,
 ) -> WebDriverResult<SessionId> {
-    let request_data = Command::NewSession(serde_json::Value::Object(capabilities))
-        .format_request(&SessionId::null());
+    let command = Command::NewSession(serde_json::Value::Object(capabilities));
+    let request_data = command.format_request(&SessionId::null());
+    let span = command_span(&command, &SessionId::null());
 
-    let v = match run_webdriver_cmd(http_client, &request_data, server_url, config).await {
-        Ok(x) => Ok(x),
-        Err(e) => {
-            // Selenium sometimes gives a bogus 500 error "Chrome failed to start".
-            // Retry if we get a 500. If it happens twice in a row, then the second error
-            // will be returned.
-            if let WebDriverErrorInner::UnknownError(x) = &*e {
-                if x.status == 500 {
-                    run_webdriver_cmd(http_client, &request_data, server_url, config).await
+    let v = run_traced(span, async {
+        match run_webdriver_cmd(http_client, &request_data, server_url, config).await {
+            Ok(x) => Ok(x),
+            Err(e) => {
+                // Selenium sometimes gives a bogus 500 error "Chrome failed to start".
+                // Retry if we get a 500. If it happens twice in a row, then the second error
+                // will be returned.
+                if let WebDriverErrorInner::UnknownError(x) = &*e {
+                    if x.status == 500 {
+                        run_webdriver_cmd(http_client, &request_data, server_url, config).await
+                    } else {
+                        Err(e)
+                    }
                 } else {
                     Err(e)
                 }
-            } else {
-                Err(e)
             }
         }
-    }?;
+    })
+    .await?;
 
     #[derive(Debug, Deserialize)]
     struct ConnectionData {
@@ -66,9 +70,10 @@ pub async fn start_session(
     });
 
     // Set default timeouts.
-    let request_data =
-        Command::SetTimeouts(TimeoutConfiguration::default()).format_request(&session_id);
-    run_webdriver_cmd(http_client, &request_data, server_url, config).await?;
+    let command = Command::SetTimeouts(TimeoutConfiguration::default());
+    let request_data = command.format_request(&session_id);
+    let span = command_span(&command, &session_id);
+    run_traced(span, run_webdriver_cmd(http_client, &request_data, server_url, config)).await?;
 
     Ok(session_id)
 }
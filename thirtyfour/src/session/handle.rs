@@ -1,10 +1,9 @@
 use serde_json::Value;
 use std::fmt::{Debug, Display, Formatter};
 use std::future::Future;
-use std::ops::{Deref, DerefMut};
 use std::path::Path;
-use std::sync::{Arc, LazyLock};
-use std::time::Duration;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::sync::OnceCell;
 use url::{ParseError, Url};
 
@@ -13,16 +12,18 @@ use crate::common::command::{Command, FormatRequestData};
 use crate::common::config::WebDriverConfig;
 use crate::common::cookie::Cookie;
 use crate::common::print::PrintParameters;
-use crate::error::WebDriverResult;
+use crate::error::{ErrorContext, WebDriverErrorInner, WebDriverResult};
 use crate::prelude::WebDriverError;
 use crate::session::scriptret::ScriptRet;
 use crate::support::base64_decode;
 use crate::web_driver::AlreadyQuit;
 use crate::{support, By, OptionRect, Rect, SessionId, SwitchTo, WebDriverStatus, WebElement};
-use crate::{IntoArcStr, IntoUrl};
+use crate::{ElementId, IntoArcStr, IntoUrl, RequestData};
 use crate::{TimeoutConfiguration, WindowHandle};
 
-use super::http::{run_webdriver_cmd, CmdResponse, HttpClient};
+use super::artifacts::FailureArtifacts;
+use super::http::{command_span, run_traced, run_webdriver_cmd, CmdResponse, HttpClient};
+use super::metrics::{record_command, SessionMetrics, SessionReport};
 
 /// The SessionHandle contains a shared reference to the HTTP client
 /// to allow sending commands to the underlying WebDriver.
@@ -37,6 +38,17 @@ pub struct SessionHandle {
     config: WebDriverConfig,
     /// quit session flag
     quit: Arc<OnceCell<()>>,
+    /// Quits the session once every handle sharing it has been dropped. This is `None`
+    /// only for the handle used to quit the session on drop.
+    drop_guard: Option<Arc<QuitOnDrop>>,
+    /// Command metrics, shared by every handle for this session. This is initialized by the
+    /// first handle whose config enables `WebDriverConfig::collect_metrics`.
+    metrics: Arc<OnceLock<SessionMetrics>>,
+    /// Failure artifacts, if enabled via `WebDriverConfig::failure_artifacts_dir`.
+    pub(crate) artifacts: Option<Arc<FailureArtifacts>>,
+    /// The ids of the frame elements leading to the current frame, starting from the
+    /// top-level document, or `None` if this is not known.
+    current_frame: Arc<Mutex<Option<Arc<[ElementId]>>>>,
 }
 
 impl Debug for SessionHandle {
@@ -65,28 +77,73 @@ impl SessionHandle {
         session_id: SessionId,
         config: WebDriverConfig,
     ) -> WebDriverResult<Self> {
-        Ok(Self {
+        let metrics = Arc::new(OnceLock::new());
+        if config.collect_metrics {
+            metrics.get_or_init(SessionMetrics::default);
+        }
+        let mut handle = Self {
             client,
             server_url: Arc::new(server_url.into_url()?),
             session_id,
+            metrics,
+            artifacts: config
+                .failure_artifacts_dir
+                .clone()
+                .map(|d| Arc::new(FailureArtifacts::new(d))),
             config,
             quit: Arc::new(OnceCell::new()),
-        })
+            drop_guard: None,
+            current_frame: Arc::new(Mutex::new(Some(Arc::from([])))),
+        };
+        handle.drop_guard = Some(Arc::new(QuitOnDrop(handle.clone_without_guard())));
+        Ok(handle)
+    }
+
+    /// Clone this session handle, without sharing the guard that quits the session on
+    /// drop. See `QuitOnDrop`.
+    fn clone_without_guard(&self) -> Self {
+        Self {
+            client: Arc::clone(&self.client),
+            server_url: Arc::clone(&self.server_url),
+            session_id: self.session_id.clone(),
+            config: self.config.clone(),
+            quit: Arc::clone(&self.quit),
+            drop_guard: None,
+            metrics: Arc::clone(&self.metrics),
+            artifacts: self.artifacts.clone(),
+            current_frame: Arc::clone(&self.current_frame),
+        }
     }
 
     /// Clone this session handle but attach the specified `WebDriverConfig`.
     ///
     /// See `WebDriver::clone_with_config()`.
     pub(crate) fn clone_with_config(self: &SessionHandle, config: WebDriverConfig) -> Self {
+        if config.collect_metrics {
+            self.metrics.get_or_init(SessionMetrics::default);
+        }
         Self {
             client: Arc::clone(&self.client),
             server_url: Arc::clone(&self.server_url),
             session_id: self.session_id.clone(),
             quit: Arc::clone(&self.quit),
+            drop_guard: self.drop_guard.clone(),
+            metrics: Arc::clone(&self.metrics),
+            artifacts: self.artifacts.clone().or_else(|| {
+                config.failure_artifacts_dir.clone().map(|d| Arc::new(FailureArtifacts::new(d)))
+            }),
+            current_frame: Arc::clone(&self.current_frame),
             config,
         }
     }
 
+    /// Clone this session handle but use the specified `HttpClient`.
+    pub(crate) fn clone_with_client(&self, client: Arc<dyn HttpClient>) -> Self {
+        let mut handle = self.clone_with_config(self.config.clone());
+        handle.client = client;
+        handle
+    }
+
     /// The session id for this webdriver session.
     pub fn session_id(&self) -> &SessionId {
         &self.session_id
@@ -103,10 +160,158 @@ impl SessionHandle {
         &self.config
     }
 
+    /// Get a snapshot of the metrics recorded for this session so far.
+    ///
+    /// Returns `None` unless metrics were enabled via
+    /// `WebDriverConfigBuilder::collect_metrics()`.
+    pub fn metrics_report(&self) -> Option<SessionReport> {
+        self.metrics.get().map(|m| m.report(&self.session_id))
+    }
+
     /// Send the specified command to the webdriver server.
+    ///
+    /// If the config specifies a `command_timeout` or a `cancellation_token`, the
+    /// HTTP request is aborted once either of them fires.
     pub async fn cmd(&self, command: impl FormatRequestData) -> WebDriverResult<CmdResponse> {
         let request_data = command.format_request(&self.session_id);
-        run_webdriver_cmd(&*self.client, &request_data, &self.server_url, &self.config).await
+        let listeners = &self.config.event_listeners;
+        let name = command.command_name();
+        for listener in listeners {
+            listener.before_command(&name, &request_data).await;
+        }
+
+        let span = command_span(&command, &self.session_id);
+        let start = Instant::now();
+        let result = run_traced(span, self.run_cmd(&request_data)).await;
+        let elapsed = start.elapsed();
+        let result = match result {
+            Ok(response) => Ok(response),
+            Err(e) => Err(self.add_error_context(e, &command).await),
+        };
+        record_command(self.metrics.get(), &command, elapsed, &result);
+        if let Some(command) = command.command() {
+            self.track_frame(command, result.is_ok());
+        }
+        if let Some(artifacts) = &self.artifacts {
+            artifacts.record(&name, &request_data, elapsed, &result);
+            if let Err(e) = &result {
+                if artifacts.should_capture(&name, e) {
+                    let test_name = artifacts.test_name(self);
+                    artifacts.capture(self, test_name, &name, &e.to_string()).await;
+                }
+            }
+        }
+
+        for listener in listeners {
+            match &result {
+                Ok(response) => listener.after_command(&name, &request_data, response).await,
+                Err(e) => listener.on_error(&name, &request_data, e).await,
+            }
+        }
+        result
+    }
+
+    /// The ids of the frame elements leading to the current frame, starting from the
+    /// top-level document, or `None` if this is not known (e.g. after entering a frame by
+    /// its index).
+    pub(crate) fn current_frame(&self) -> Option<Arc<[ElementId]>> {
+        self.current_frame.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Update the current frame after the specified command.
+    fn track_frame(&self, command: &Command, succeeded: bool) {
+        let mut current = self.current_frame.lock().unwrap_or_else(|e| e.into_inner());
+        *current = match command {
+            Command::NavigateTo(_)
+            | Command::Refresh
+            | Command::SwitchToWindow(_)
+            | Command::SwitchToFrameDefault
+                if succeeded =>
+            {
+                Some(Arc::from([]))
+            }
+            Command::SwitchToFrameElement(id) if succeeded => {
+                current.take().map(|path| path.iter().cloned().chain([id.clone()]).collect())
+            }
+            Command::SwitchToParentFrame if succeeded => {
+                current.take().map(|path| path[..path.len().saturating_sub(1)].into())
+            }
+            Command::NavigateTo(_)
+            | Command::Refresh
+            | Command::Back
+            | Command::Forward
+            | Command::CloseWindow
+            | Command::SwitchToWindow(_)
+            | Command::SwitchToFrameDefault
+            | Command::SwitchToFrameNumber(_)
+            | Command::SwitchToFrameElement(_)
+            | Command::SwitchToParentFrame => None,
+            _ => return,
+        };
+    }
+
+    /// Attach the details of the failed command to the error, along with the current page URL
+    /// if `WebDriverConfig::error_url_context` is set.
+    async fn add_error_context(
+        &self,
+        error: WebDriverError,
+        command: &impl FormatRequestData,
+    ) -> WebDriverError {
+        let context = ErrorContext::new(command, &self.session_id);
+        // Don't bother asking for the URL if the browser can't respond.
+        let can_get_url = self.config.error_url_context
+            && !matches!(context.command(), "NewSession" | "DeleteSession" | "GetCurrentUrl")
+            && !matches!(
+                *error,
+                WebDriverErrorInner::CommandTimeout(_)
+                    | WebDriverErrorInner::CommandCancelled(_)
+                    | WebDriverErrorInner::RequestFailed(_)
+                    | WebDriverErrorInner::HttpError(_)
+                    | WebDriverErrorInner::UnknownResponse(..)
+                    | WebDriverErrorInner::InvalidSessionId(_)
+                    | WebDriverErrorInner::NoSuchWindow(_)
+                    | WebDriverErrorInner::UnexpectedAlertOpen(_)
+            );
+
+        let url = match can_get_url {
+            // Send this directly, so that it isn't reported as a separate command.
+            true => self
+                .run_cmd(&Command::GetCurrentUrl.format_request(&self.session_id))
+                .await
+                .and_then(|r| r.value::<String>())
+                .ok(),
+            false => None,
+        };
+        error.with_context(context.with_url(url))
+    }
+
+    /// Run the specified request, applying the command timeout and cancellation token.
+    async fn run_cmd(&self, request_data: &RequestData) -> WebDriverResult<CmdResponse> {
+        let request = async {
+            let request =
+                run_webdriver_cmd(&*self.client, request_data, &self.server_url, &self.config);
+            match self.config.command_timeout {
+                Some(timeout) => {
+                    tokio::time::timeout(timeout, request).await.unwrap_or_else(|_| {
+                        Err(WebDriverError::CommandTimeout(format!(
+                            "{} {} did not complete within {timeout:?}",
+                            request_data.method, request_data.uri
+                        )))
+                    })
+                }
+                None => request.await,
+            }
+        };
+
+        match &self.config.cancellation_token {
+            Some(token) => token.run_until_cancelled(request).await.unwrap_or_else(|| {
+                Err(WebDriverError::CommandCancelled(format!(
+                    "{} {}",
+                    request_data.method, request_data.uri
+                )))
+            }),
+            None => request.await,
+        }
     }
 
     /// Get the WebDriver status.
@@ -198,7 +403,13 @@ impl SessionHandle {
                 e => Err(e),
             })
             .map_err(WebDriverError::InvalidUrl)?;
-        self.cmd(Command::NavigateTo(url)).await?;
+        for listener in &self.config.event_listeners {
+            listener.before_navigate(self, &url).await;
+        }
+        self.cmd(Command::NavigateTo(url.clone())).await?;
+        for listener in &self.config.event_listeners {
+            listener.after_navigate(self, &url).await;
+        }
         Ok(())
     }
 
@@ -225,6 +436,35 @@ impl SessionHandle {
         self.source().await
     }
 
+    /// Take a `DomSnapshot` of the current document, which can be queried locally without
+    /// sending any further WebDriver commands.
+    ///
+    /// This requires the `dom-snapshot` feature.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// let snapshot = driver.dom_snapshot().await?;
+    /// let links: Vec<&str> =
+    ///     snapshot.find_all("a[href]")?.iter().filter_map(|a| a.attr("href")).collect();
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    #[cfg(feature = "dom-snapshot")]
+    pub async fn dom_snapshot(
+        self: &Arc<Self>,
+    ) -> WebDriverResult<crate::extensions::snapshot::DomSnapshot> {
+        crate::extensions::snapshot::DomSnapshot::capture(self, None).await
+    }
+
     /// Get the page title as a String.
     pub async fn title(&self) -> WebDriverResult<String> {
         self.cmd(Command::GetTitle).await?.value()
@@ -254,8 +494,27 @@ impl SessionHandle {
     /// # }
     /// ```
     pub async fn find(self: &Arc<Self>, by: By) -> WebDriverResult<WebElement> {
-        let r = self.cmd(Command::FindElement(by.into())).await?;
-        r.element(self.clone())
+        if self.config.validate_selectors {
+            by.validate()?;
+        }
+        let elem = match by.script() {
+            Some((script, query)) => {
+                let elems = self.execute(&*script, vec![Value::Null, query]).await?.elements()?;
+                elems.into_iter().next().ok_or_else(|| {
+                    crate::error::no_such_element(format!(
+                        "no such element: element not found using selector: {by}"
+                    ))
+                })?
+            }
+            None => {
+                self.cmd(Command::FindElement(by.clone().into())).await?.element(self.clone())?
+            }
+        };
+        let elem = elem.with_find_locator(None, &by);
+        for listener in &self.config.event_listeners {
+            listener.on_find(&by, std::slice::from_ref(&elem)).await;
+        }
+        Ok(elem)
     }
 
     /// Search for an element on the current page using the specified selector.
@@ -290,8 +549,21 @@ impl SessionHandle {
     /// # }
     /// ```
     pub async fn find_all(self: &Arc<Self>, by: By) -> WebDriverResult<Vec<WebElement>> {
-        let r = self.cmd(Command::FindElements(by.into())).await?;
-        r.elements(self.clone())
+        if self.config.validate_selectors {
+            by.validate()?;
+        }
+        let elems = match by.script() {
+            Some((script, query)) => {
+                self.execute(&*script, vec![Value::Null, query]).await?.elements()?
+            }
+            None => {
+                self.cmd(Command::FindElements(by.clone().into())).await?.elements(self.clone())?
+            }
+        };
+        for listener in &self.config.event_listeners {
+            listener.on_find(&by, &elems).await;
+        }
+        Ok(elems)
     }
 
     /// Search for all elements on the current page that match the specified selector.
@@ -1189,11 +1461,15 @@ impl SessionHandle {
     }
 }
 
+/// Quits the session when the last handle sharing it (including those created via
+/// `clone_with_config()`) is dropped, unless it was quit manually.
+struct QuitOnDrop(SessionHandle);
+
 // "SyncDrop" only runs if not manually quit
-impl Drop for SessionHandle {
+impl Drop for QuitOnDrop {
     #[track_caller]
     fn drop(&mut self) {
-        if self.quit.initialized() {
+        if self.0.quit.initialized() {
             return;
         }
 
@@ -1203,41 +1479,7 @@ impl Drop for SessionHandle {
             std::backtrace::Backtrace::capture()
         );
 
-        struct SessionDropGuard(SessionHandle);
-
-        impl Deref for SessionDropGuard {
-            type Target = SessionHandle;
-
-            fn deref(&self) -> &Self::Target {
-                &self.0
-            }
-        }
-
-        impl DerefMut for SessionDropGuard {
-            fn deref_mut(&mut self) -> &mut Self::Target {
-                &mut self.0
-            }
-        }
-
-        impl Drop for SessionDropGuard {
-            fn drop(&mut self) {
-                if !self.0.quit.initialized() {
-                    static ALWAYS_INIT: LazyLock<Arc<OnceCell<()>>> =
-                        LazyLock::new(|| Arc::new(OnceCell::new_with(Some(()))));
-                    self.0.quit = Arc::clone(&ALWAYS_INIT);
-                    debug_assert!(self.0.quit.initialized())
-                }
-            }
-        }
-
-        let mut this = SessionDropGuard(Self {
-            client: Arc::clone(&self.client),
-            server_url: Arc::clone(&self.server_url),
-            quit: Arc::clone(&self.quit),
-            session_id: self.session_id.clone(),
-            config: self.config.clone(),
-        });
-
+        let mut this = self.0.clone_without_guard();
         support::spawn_blocked_future(|spawned| async move {
             if spawned {
                 // Old I/O drivers may be destroyed at this point
@@ -1247,3 +1489,83 @@ impl Drop for SessionHandle {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::http::recording_client::RecordingHttpClient;
+
+    fn new_handle(client: &Arc<RecordingHttpClient>) -> SessionHandle {
+        let client: Arc<dyn HttpClient> = client.clone();
+        SessionHandle::new(client, "http://localhost:4444", SessionId::from("test")).unwrap()
+    }
+
+    #[test]
+    fn test_quit_on_drop() {
+        let client = Arc::new(RecordingHttpClient::default());
+        let handle = new_handle(&client);
+        let clone = handle.clone_with_config(WebDriverConfig::default());
+        drop(handle);
+        assert!(client.requests().is_empty());
+        drop(clone);
+        assert_eq!(client.requests(), ["DELETE /session/test"]);
+
+        // Sessions that were quit manually are not quit again.
+        let handle = new_handle(&client);
+        support::block_on(handle.quit()).unwrap();
+        drop(handle);
+        assert_eq!(client.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_quit_on_concurrent_drop() {
+        let client = Arc::new(RecordingHttpClient::default());
+        let handle = new_handle(&client);
+        let handles: Vec<SessionHandle> =
+            (0..8).map(|_| handle.clone_with_config(WebDriverConfig::default())).collect();
+        drop(handle);
+        std::thread::scope(|s| {
+            for handle in handles {
+                s.spawn(move || drop(handle));
+            }
+        });
+        assert_eq!(client.requests(), ["DELETE /session/test"]);
+    }
+
+    #[test]
+    fn test_track_frame() {
+        let client = Arc::new(RecordingHttpClient::default());
+        let handle = new_handle(&client);
+        let frame = |id: &str| {
+            let command = Command::SwitchToFrameElement(ElementId::from(id));
+            support::block_on(handle.cmd(command)).unwrap();
+            handle.current_frame().map(|path| path.iter().map(|id| id.to_string()).collect())
+        };
+        assert_eq!(frame("a"), Some(vec!["a".to_string()]));
+        assert_eq!(frame("b"), Some(vec!["a".to_string(), "b".to_string()]));
+
+        support::block_on(handle.cmd(Command::SwitchToParentFrame)).unwrap();
+        assert_eq!(handle.current_frame().unwrap().len(), 1);
+        support::block_on(handle.cmd(Command::SwitchToFrameNumber(0))).unwrap();
+        assert!(handle.current_frame().is_none());
+        support::block_on(handle.cmd(Command::SwitchToFrameDefault)).unwrap();
+        assert_eq!(handle.current_frame().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_metrics_shared_by_session() {
+        let client = Arc::new(RecordingHttpClient::default());
+        let handle = new_handle(&client);
+        assert!(handle.metrics_report().is_none());
+
+        // Enabling metrics on a clone also enables them for the original handle.
+        let config = WebDriverConfig::builder().collect_metrics(true).build().unwrap();
+        let clone = handle.clone_with_config(config);
+        support::block_on(handle.cmd(Command::GetTitle)).unwrap();
+        support::block_on(clone.cmd(Command::GetTitle)).unwrap();
+        for report in [handle.metrics_report().unwrap(), clone.metrics_report().unwrap()] {
+            assert_eq!(report.commands.len(), 1);
+            assert_eq!(report.commands[0].count, 2);
+        }
+    }
+}
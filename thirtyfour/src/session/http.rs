@@ -1,18 +1,22 @@
+use std::fmt::Debug;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Instant;
 
 use base64::Engine;
 use bytes::Bytes;
 use http::{
     header::{ACCEPT, AUTHORIZATION, CONNECTION, CONTENT_TYPE, USER_AGENT},
-    HeaderValue, Request, Response,
+    HeaderMap, HeaderValue, Request, Response,
 };
 use serde_json::Value;
+use tracing::{field, Instrument, Span};
 use url::Url;
 
 use crate::{
-    common::config::WebDriverConfig,
+    common::{command::FormatRequestData, config::WebDriverConfig},
     prelude::{WebDriverError, WebDriverResult},
-    ElementId, ElementRef, RequestData, WebElement,
+    ElementId, ElementRef, RequestData, SessionId, WebElement,
 };
 
 use super::handle::SessionHandle;
@@ -46,13 +50,39 @@ pub trait HttpClient: Send + Sync + 'static {
     /// or couldn't prove its availability
     /// this isn't a simple clone,
     /// this new client needs to be able to run in a new runtime even if the old runtime has been destroyed
-
     // needed for object safety
     #[allow(clippy::new_ret_no_self)]
     #[allow(clippy::wrong_self_convention)]
     async fn new(&self) -> Arc<dyn HttpClient>;
 }
 
+/// Trait used to propagate trace context (e.g. the W3C `traceparent` header) to the
+/// WebDriver server.
+///
+/// `inject()` is called for every request, from within the span of the command being sent,
+/// so `tracing::Span::current()` refers to that command.
+///
+/// # Example:
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_http::HeaderInjector;
+/// use tracing_opentelemetry::OpenTelemetrySpanExt;
+///
+/// #[derive(Debug)]
+/// struct OtelPropagator;
+///
+/// impl TracePropagator for OtelPropagator {
+///     fn inject(&self, headers: &mut http::HeaderMap) {
+///         let cx = tracing::Span::current().context();
+///         global::get_text_map_propagator(|p| p.inject_context(&cx, &mut HeaderInjector(headers)));
+///     }
+/// }
+/// ```
+pub trait TracePropagator: Debug + Send + Sync {
+    /// Add the trace context headers for the current span to the outgoing request.
+    fn inject(&self, headers: &mut HeaderMap);
+}
+
 #[cfg(feature = "reqwest")]
 #[async_trait::async_trait]
 impl HttpClient for reqwest::Client {
@@ -147,7 +177,82 @@ pub(crate) mod null_client {
     }
 }
 
-#[tracing::instrument(skip_all)]
+/// A client that records each request and responds with `{"value": null}`, for unit tests.
+#[cfg(test)]
+pub(crate) mod recording_client {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub(crate) struct RecordingHttpClient {
+        requests: Arc<Mutex<Vec<Request<()>>>>,
+    }
+
+    impl RecordingHttpClient {
+        /// The method and path of each request sent so far, e.g. `DELETE /session/x`.
+        pub(crate) fn requests(&self) -> Vec<String> {
+            let requests = self.requests.lock().unwrap();
+            requests.iter().map(|r| format!("{} {}", r.method(), r.uri().path())).collect()
+        }
+
+        /// The headers of the last request sent, if any.
+        pub(crate) fn last_headers(&self) -> Option<HeaderMap> {
+            let requests = self.requests.lock().unwrap();
+            requests.last().map(|r| r.headers().clone())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for RecordingHttpClient {
+        async fn send(&self, request: Request<Body<'_>>) -> WebDriverResult<Response<Bytes>> {
+            self.requests.lock().unwrap().push(request.map(|_| ()));
+            Ok(Response::new(Bytes::from_static(br#"{"value":null}"#)))
+        }
+
+        async fn new(&self) -> Arc<dyn HttpClient> {
+            Arc::new(RecordingHttpClient {
+                requests: Arc::clone(&self.requests),
+            })
+        }
+    }
+}
+
+/// Create the span for the specified command.
+///
+/// The outcome of the command is recorded on the span by [`run_traced`].
+pub(crate) fn command_span(command: &impl FormatRequestData, session_id: &SessionId) -> Span {
+    let name = command.command_name();
+    tracing::info_span!(
+        "webdriver_cmd",
+        otel.name = %name,
+        otel.kind = "client",
+        otel.status_code = field::Empty,
+        command = %name,
+        session.id = %session_id,
+        element.id = command.element_id().map(field::display),
+        selector = command.selector().map(field::display),
+        http.response.status_code = field::Empty,
+        duration_ms = field::Empty,
+        error.type = field::Empty,
+    )
+}
+
+/// Run the specified command future within its span, recording the duration and any error.
+pub(crate) async fn run_traced<F>(span: Span, f: F) -> WebDriverResult<CmdResponse>
+where
+    F: Future<Output = WebDriverResult<CmdResponse>>,
+{
+    let start = Instant::now();
+    let result = f.instrument(span.clone()).await;
+    span.record("duration_ms", start.elapsed().as_millis() as u64);
+    if let Err(e) = &result {
+        span.record("otel.status_code", "ERROR");
+        span.record("error.type", e.kind());
+    }
+    result
+}
+
 pub(crate) async fn run_webdriver_cmd(
     client: &dyn HttpClient,
     request_data: &RequestData,
@@ -181,6 +286,9 @@ pub(crate) async fn run_webdriver_cmd(
     if config.keep_alive {
         builder = builder.header(CONNECTION, "keep-alive");
     }
+    if let (Some(propagator), Some(headers)) = (&config.trace_propagator, builder.headers_mut()) {
+        propagator.inject(headers);
+    }
 
     let body = match &request_data.body {
         Some(body) => Body::from(body),
@@ -192,6 +300,7 @@ pub(crate) async fn run_webdriver_cmd(
         .map_err(|e| WebDriverError::RequestFailed(format!("invalid request body: {e}")))?;
     let response = client.send(request).await?;
     let status = response.status().as_u16();
+    Span::current().record("http.response.status_code", status);
     let lossy_response = String::from_utf8_lossy(response.body());
     tracing::debug!("webdriver response: {status} {lossy_response}");
     match status {
@@ -247,3 +356,93 @@ impl CmdResponse {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod trace_tests {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    use super::recording_client::RecordingHttpClient;
+    use super::*;
+    use crate::common::command::Command;
+    use crate::{support, By};
+
+    /// Collects the fields recorded on the `webdriver_cmd` span.
+    #[derive(Debug, Clone, Default)]
+    struct SpanFields(Arc<Mutex<BTreeMap<String, String>>>);
+
+    impl Visit for SpanFields {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.lock().unwrap().insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.lock().unwrap().insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for SpanFields {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            if attrs.metadata().name() == "webdriver_cmd" {
+                attrs.record(&mut self.clone());
+            }
+        }
+
+        fn on_record(&self, _: &Id, values: &Record<'_>, _: Context<'_, S>) {
+            values.record(&mut self.clone());
+        }
+    }
+
+    /// Injects a header naming the span that is current when the request is sent.
+    #[derive(Debug)]
+    struct SpanNamePropagator;
+
+    impl TracePropagator for SpanNamePropagator {
+        fn inject(&self, headers: &mut HeaderMap) {
+            let name = Span::current().metadata().map_or("none", |m| m.name());
+            headers.insert("traceparent", HeaderValue::from_static(name));
+        }
+    }
+
+    #[test]
+    fn test_command_span() {
+        let fields = SpanFields::default();
+        let subscriber = tracing_subscriber::registry().with(fields.clone());
+        let client = Arc::new(RecordingHttpClient::default());
+        let config = WebDriverConfig::builder()
+            .trace_propagator(Arc::new(SpanNamePropagator))
+            .build()
+            .unwrap();
+        let handle = SessionHandle::new_with_config(
+            client.clone(),
+            "http://localhost:4444",
+            SessionId::from("test"),
+            config,
+        )
+        .unwrap();
+
+        let command = Command::FindElementFromElement(ElementId::from("e1"), By::Css("a").into());
+        tracing::subscriber::with_default(subscriber, || {
+            support::block_on(handle.cmd(command)).unwrap();
+        });
+
+        let fields = fields.0.lock().unwrap();
+        assert_eq!(fields["otel.name"], "FindElementFromElement");
+        assert_eq!(fields["otel.kind"], "client");
+        assert_eq!(fields["command"], "FindElementFromElement");
+        assert_eq!(fields["session.id"], "test");
+        assert_eq!(fields["element.id"], "e1");
+        assert_eq!(fields["selector"], "css selector: a");
+        assert_eq!(fields["http.response.status_code"], "200");
+        assert!(fields.contains_key("duration_ms"));
+        assert!(!fields.contains_key("error.type"));
+
+        let headers = client.last_headers().unwrap();
+        assert_eq!(headers["traceparent"], "webdriver_cmd");
+    }
+}
@@ -0,0 +1,320 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::common::command::FormatRequestData;
+use crate::error::WebDriverResult;
+use crate::session::http::CmdResponse;
+use crate::SessionId;
+
+/// Upper bounds (in milliseconds) of the buckets used for latency histograms.
+///
+/// Anything slower than the last bound is counted in a final overflow bucket.
+const BUCKET_BOUNDS_MS: [u64; 12] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+/// Records the number, latency and errors of the commands sent by a session.
+///
+/// Enable it via `WebDriverConfigBuilder::collect_metrics()`. All `WebDriver` instances
+/// (and elements) sharing the same session also share the same recorder, so once it is
+/// enabled for any of them (e.g. via `WebDriver::clone_with_config()`), commands sent by
+/// all of them are recorded.
+#[derive(Debug)]
+pub struct SessionMetrics {
+    started: Instant,
+    commands: Mutex<HashMap<Cow<'static, str>, CommandStats>>,
+}
+
+impl Default for SessionMetrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            commands: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SessionMetrics {
+    /// Record a single command.
+    pub fn record(
+        &self,
+        command: Cow<'static, str>,
+        elapsed: Duration,
+        error: Option<&'static str>,
+    ) {
+        let mut commands = self.commands.lock().unwrap_or_else(|e| e.into_inner());
+        commands
+            .entry(command)
+            .or_insert_with_key(|name| CommandStats::new(name.to_string()))
+            .record(elapsed, error);
+    }
+
+    /// Get a snapshot of all metrics recorded so far.
+    pub fn report(&self, session_id: &SessionId) -> SessionReport {
+        let commands = self.commands.lock().unwrap_or_else(|e| e.into_inner());
+        let mut commands: Vec<CommandStats> = commands.values().cloned().collect();
+        commands.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.command.cmp(&b.command)));
+        SessionReport {
+            session_id: session_id.clone(),
+            elapsed: self.started.elapsed(),
+            commands,
+        }
+    }
+}
+
+/// Record the outcome of a command, both in the session recorder (if any) and via the
+/// `metrics` crate facade (if the `metrics` feature is enabled).
+pub(crate) fn record_command(
+    recorder: Option<&SessionMetrics>,
+    command: &impl FormatRequestData,
+    elapsed: Duration,
+    result: &WebDriverResult<CmdResponse>,
+) {
+    if recorder.is_none() && !cfg!(feature = "metrics") {
+        return;
+    }
+
+    let name = command.command_name();
+    let error = result.as_ref().err().map(|e| e.kind());
+    #[cfg(feature = "metrics")]
+    record_facade(name.clone(), elapsed, error);
+    if let Some(recorder) = recorder {
+        recorder.record(name, elapsed, error);
+    }
+}
+
+/// Report the command via the `metrics` crate facade.
+#[cfg(feature = "metrics")]
+fn record_facade(name: Cow<'static, str>, elapsed: Duration, error: Option<&'static str>) {
+    metrics::counter!("thirtyfour_commands_total", "command" => name.clone()).increment(1);
+    metrics::histogram!("thirtyfour_command_duration_seconds", "command" => name.clone())
+        .record(elapsed.as_secs_f64());
+    if let Some(error) = error {
+        metrics::counter!("thirtyfour_command_errors_total", "command" => name, "error" => error)
+            .increment(1);
+    }
+}
+
+/// Statistics for a single type of command.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CommandStats {
+    /// The command name, e.g. `FindElement`.
+    pub command: String,
+    /// The number of times the command was sent.
+    pub count: u64,
+    /// The number of times the command returned an error.
+    pub errors: u64,
+    /// The number of errors by error type, e.g. `NoSuchElement`.
+    pub errors_by_kind: BTreeMap<&'static str, u64>,
+    /// The total time spent waiting for this command.
+    pub total: Duration,
+    /// The fastest response.
+    pub min: Duration,
+    /// The slowest response.
+    pub max: Duration,
+    /// The latency distribution.
+    pub histogram: LatencyHistogram,
+}
+
+impl CommandStats {
+    fn new(command: String) -> Self {
+        Self {
+            command,
+            count: 0,
+            errors: 0,
+            errors_by_kind: BTreeMap::new(),
+            total: Duration::ZERO,
+            min: Duration::MAX,
+            max: Duration::ZERO,
+            histogram: LatencyHistogram::default(),
+        }
+    }
+
+    fn record(&mut self, elapsed: Duration, error: Option<&'static str>) {
+        self.count += 1;
+        self.total += elapsed;
+        self.min = self.min.min(elapsed);
+        self.max = self.max.max(elapsed);
+        self.histogram.record(elapsed);
+        if let Some(kind) = error {
+            self.errors += 1;
+            *self.errors_by_kind.entry(kind).or_default() += 1;
+        }
+    }
+
+    /// The mean response time.
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            n => self.total.div_f64(n as f64),
+        }
+    }
+}
+
+/// A histogram of command latencies, using fixed buckets from 1ms to 10s.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: [u64; BUCKET_BOUNDS_MS.len() + 1],
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; BUCKET_BOUNDS_MS.len() + 1],
+        }
+    }
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, elapsed: Duration) {
+        let index = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|&bound| elapsed <= Duration::from_millis(bound))
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.counts[index] += 1;
+    }
+
+    /// Iterate over each bucket as `(upper bound, count)`.
+    ///
+    /// The upper bound of the last bucket is `None`, meaning it has no upper limit.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        let bounds = BUCKET_BOUNDS_MS.iter().map(|&ms| Some(Duration::from_millis(ms)));
+        bounds.chain(std::iter::once(None)).zip(self.counts.iter().copied())
+    }
+
+    /// Get the upper bound of the bucket containing the specified percentile (0.0 - 1.0).
+    ///
+    /// Returns `None` if nothing was recorded, or if the percentile falls within the
+    /// overflow bucket.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        let total: u64 = self.counts.iter().sum();
+        if total == 0 {
+            return None;
+        }
+
+        let target = ((total as f64) * percentile.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bound, count) in self.buckets() {
+            seen += count;
+            if seen >= target {
+                return bound;
+            }
+        }
+        None
+    }
+}
+
+/// Summary of all commands sent during a session.
+///
+/// Obtain this via `WebDriver::quit_with_report()` or `SessionHandle::metrics_report()`.
+/// The `Display` implementation prints a table sorted by total time, with the commands
+/// that dominate the runtime at the top.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SessionReport {
+    /// The session id.
+    pub session_id: SessionId,
+    /// The time since metrics collection started.
+    pub elapsed: Duration,
+    /// Statistics for each command, sorted by total time (slowest first).
+    pub commands: Vec<CommandStats>,
+}
+
+impl SessionReport {
+    /// The total number of commands sent.
+    pub fn total_commands(&self) -> u64 {
+        self.commands.iter().map(|c| c.count).sum()
+    }
+
+    /// The total number of commands that returned an error.
+    pub fn total_errors(&self) -> u64 {
+        self.commands.iter().map(|c| c.errors).sum()
+    }
+
+    /// The total time spent waiting for commands.
+    pub fn total_time(&self) -> Duration {
+        self.commands.iter().map(|c| c.total).sum()
+    }
+
+    /// Get the statistics for the specified command, e.g. `FindElement`.
+    pub fn command(&self, name: &str) -> Option<&CommandStats> {
+        self.commands.iter().find(|c| c.command == name)
+    }
+}
+
+impl fmt::Display for SessionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Session {}: {} commands ({} errors) in {:.3?}, {:.3?} spent in commands",
+            self.session_id,
+            self.total_commands(),
+            self.total_errors(),
+            self.elapsed,
+            self.total_time()
+        )?;
+        writeln!(
+            f,
+            "{:<32} {:>8} {:>8} {:>12} {:>12} {:>12} {:>12}",
+            "command", "count", "errors", "total", "mean", "p95", "max"
+        )?;
+        for stats in &self.commands {
+            let p95 = match stats.histogram.percentile(0.95) {
+                Some(bound) => format!("<={bound:?}"),
+                None => format!(
+                    ">{:?}",
+                    Duration::from_millis(BUCKET_BOUNDS_MS[BUCKET_BOUNDS_MS.len() - 1])
+                ),
+            };
+            writeln!(
+                f,
+                "{:<32} {:>8} {:>8} {:>12} {:>12} {:>12} {:>12}",
+                stats.command,
+                stats.count,
+                stats.errors,
+                format!("{:.3?}", stats.total),
+                format!("{:.3?}", stats.mean()),
+                p95,
+                format!("{:.3?}", stats.max)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_report() {
+        let metrics = SessionMetrics::default();
+        metrics.record("FindElement".into(), Duration::from_millis(3), None);
+        metrics.record("FindElement".into(), Duration::from_millis(40), Some("NoSuchElement"));
+        metrics.record("GetTitle".into(), Duration::from_millis(2), None);
+
+        let report = metrics.report(&SessionId::null());
+        assert_eq!(report.total_commands(), 3);
+        assert_eq!(report.total_errors(), 1);
+        assert_eq!(report.commands[0].command, "FindElement");
+
+        let find = report.command("FindElement").unwrap();
+        assert_eq!(find.count, 2);
+        assert_eq!(find.min, Duration::from_millis(3));
+        assert_eq!(find.max, Duration::from_millis(40));
+        assert_eq!(find.errors_by_kind.get("NoSuchElement"), Some(&1));
+        assert_eq!(find.histogram.percentile(0.5), Some(Duration::from_millis(5)));
+        assert_eq!(find.histogram.percentile(1.0), Some(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_histogram_overflow() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentile(0.5), None);
+        histogram.record(Duration::from_secs(30));
+        assert_eq!(histogram.percentile(0.5), None);
+        assert_eq!(histogram.buckets().last(), Some((None, 1)));
+    }
+}
@@ -1,8 +1,12 @@
+/// Failure artifacts for triaging errors.
+pub mod artifacts;
 /// Code for starting a new session.
 pub mod create;
 /// The underlying session handle.
 pub mod handle;
 /// HTTP helpers for WebDriver commands.
 pub mod http;
+/// Command metrics and session reports.
+pub mod metrics;
 /// Helper for values returned from scripts.
 pub mod scriptret;
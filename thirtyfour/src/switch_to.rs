@@ -1,11 +1,12 @@
 use crate::common::command::Command;
 use crate::error::WebDriverErrorInfo;
 use crate::session::handle::SessionHandle;
-use crate::WindowHandle;
 use crate::{
     error::{WebDriverError, WebDriverResult},
     Alert, WebElement,
 };
+use crate::{ElementId, WindowHandle};
+use std::future::Future;
 use std::sync::Arc;
 
 /// Struct for switching between frames/windows/alerts.
@@ -236,6 +237,69 @@ impl SessionHandle {
         Ok(())
     }
 
+    /// Switch to the frame at the specified path, i.e. enter each of the specified frame
+    /// elements in turn, starting from the top-level document.
+    ///
+    /// If the current frame is known, this only switches up to the frame shared by both paths
+    /// (when that is quicker than starting from the top-level document), and then down from
+    /// there. Nothing is sent if the specified frame is already the current one.
+    pub(crate) async fn enter_frame_path(&self, frames: &[ElementId]) -> WebDriverResult<()> {
+        let shared = match self.current_frame() {
+            Some(current) => {
+                let shared = current.iter().zip(frames).take_while(|(a, b)| a == b).count();
+                let up = current.len() - shared;
+                if up <= shared {
+                    for _ in 0..up {
+                        self.cmd(Command::SwitchToParentFrame).await?;
+                    }
+                    shared
+                } else {
+                    self.cmd(Command::SwitchToFrameDefault).await?;
+                    0
+                }
+            }
+            None => {
+                self.cmd(Command::SwitchToFrameDefault).await?;
+                0
+            }
+        };
+        for id in &frames[shared..] {
+            self.cmd(Command::SwitchToFrameElement(id.clone())).await?;
+        }
+        Ok(())
+    }
+
+    /// Run the specified future inside the frame at the specified path, and then switch
+    /// back to the frame that was current before.
+    ///
+    /// If the previous frame is not known (e.g. after entering a frame by its index), or
+    /// no longer exists, this switches back to the top-level document instead.
+    pub(crate) async fn in_frame_path<T>(
+        &self,
+        frames: &[ElementId],
+        f: impl Future<Output = WebDriverResult<T>>,
+    ) -> WebDriverResult<T> {
+        let previous = self.current_frame();
+        if previous.as_deref() == Some(frames) {
+            return f.await;
+        }
+
+        self.enter_frame_path(frames).await?;
+        let result = f.await;
+        let restored = match &previous {
+            Some(previous) => match self.enter_frame_path(previous).await {
+                Err(_) if !previous.is_empty() => {
+                    self.cmd(Command::SwitchToFrameDefault).await.map(|_| ())
+                }
+                restored => restored,
+            },
+            None => self.cmd(Command::SwitchToFrameDefault).await.map(|_| ()),
+        };
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
     /// Switch to the specified window.
     ///
     /// # Example:
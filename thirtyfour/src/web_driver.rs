@@ -1,5 +1,7 @@
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 use crate::common::config::WebDriverConfig;
 use crate::error::WebDriverResult;
@@ -9,6 +11,7 @@ use crate::session::handle::SessionHandle;
 #[cfg(feature = "reqwest")]
 use crate::session::http::create_reqwest_client;
 use crate::session::http::HttpClient;
+use crate::session::metrics::SessionReport;
 use crate::Capabilities;
 
 /// The `WebDriver` struct encapsulates an async Selenium WebDriver browser
@@ -144,6 +147,66 @@ impl WebDriver {
         }
     }
 
+    /// Clone this `WebDriver`, aborting any command that takes longer than the specified
+    /// timeout with `WebDriverError::CommandTimeout`.
+    ///
+    /// Elements found via the returned instance use the same timeout.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// use std::time::Duration;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// driver.with_timeout(Duration::from_secs(5)).goto("https://www.rust-lang.org").await?;
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        let mut config = self.handle.config().clone();
+        config.command_timeout = Some(timeout);
+        self.clone_with_config(config)
+    }
+
+    /// Clone this `WebDriver`, aborting all of its in-flight and future commands with
+    /// `WebDriverError::CommandCancelled` once the specified token is cancelled.
+    ///
+    /// Elements found via the returned instance use the same token.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// use thirtyfour::CancellationToken;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// #         let caps = DesiredCapabilities::chrome();
+    /// #         let driver = WebDriver::new("http://localhost:4444", caps).await?;
+    /// let token = CancellationToken::new();
+    /// let cancellable = driver.with_cancellation(token.clone());
+    /// let task = tokio::spawn(async move {
+    ///     cancellable.execute_async("/* never calls back */", Vec::new()).await
+    /// });
+    /// token.cancel();
+    /// assert!(task.await.unwrap().is_err());
+    /// #         driver.quit().await?;
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub fn with_cancellation(&self, token: CancellationToken) -> Self {
+        let mut config = self.handle.config().clone();
+        config.cancellation_token = Some(token);
+        self.clone_with_config(config)
+    }
+
     /// End the webdriver session and close the browser.
     ///
     /// **NOTE:** Although `WebDriver` does close when all instances go out of scope.
@@ -156,6 +219,35 @@ impl WebDriver {
         self.handle.quit().await
     }
 
+    /// End the webdriver session and return the metrics recorded during the session.
+    ///
+    /// Returns `None` unless metrics were enabled via
+    /// `WebDriverConfigBuilder::collect_metrics()`.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// # use thirtyfour::prelude::*;
+    /// # use thirtyfour::support::block_on;
+    /// use thirtyfour::common::config::WebDriverConfig;
+    /// #
+    /// # fn main() -> WebDriverResult<()> {
+    /// #     block_on(async {
+    /// let caps = DesiredCapabilities::chrome();
+    /// let config = WebDriverConfig::builder().collect_metrics(true).build()?;
+    /// let driver = WebDriver::new_with_config("http://localhost:4444", caps, config).await?;
+    /// driver.goto("https://www.rust-lang.org").await?;
+    /// if let Some(report) = driver.quit_with_report().await? {
+    ///     println!("{report}");
+    /// }
+    /// #         Ok(())
+    /// #     })
+    /// # }
+    /// ```
+    pub async fn quit_with_report(self) -> WebDriverResult<Option<SessionReport>> {
+        self.handle.quit().await?;
+        Ok(self.handle.metrics_report())
+    }
+
     /// Leak the webdriver session and prevent it from being closed,
     /// use this if you don't want your driver to automatically close
     pub fn leak(self) -> Result<(), AlreadyQuit> {
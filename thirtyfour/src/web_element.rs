@@ -4,10 +4,18 @@ use std::fmt;
 use std::path::Path;
 use std::sync::Arc;
 
+use arc_swap::ArcSwapOption;
+use futures_util::future::BoxFuture;
+use std::future::Future;
+
 use crate::common::command::Command;
+use crate::common::config::StaleElementPolicy;
 use crate::error::{WebDriverError, WebDriverErrorInner};
+use crate::extensions::query::ElementQuery;
 use crate::js::SIMULATE_DRAG_AND_DROP;
 use crate::session::handle::SessionHandle;
+use crate::session::http::CmdResponse;
+use crate::session::scriptret::ScriptRet;
 use crate::support::base64_decode;
 use crate::{common::types::ElementRect, error::WebDriverResult, By, ElementRef};
 use crate::{support, IntoArcStr};
@@ -54,17 +62,61 @@ use crate::{ElementId, TypingData};
 /// Elements can be clicked using the `click()` method, and you can send
 /// input to an element using the `send_keys()` method.
 ///
-#[derive(Clone)]
 pub struct WebElement {
     /// The element id.
+    ///
+    /// NOTE: If the element was found again after becoming stale, this is the id it had
+    ///       when this `WebElement` was created or cloned.
+    ///       Use [`WebElement::element_id`] to get the current id.
     pub element_id: ElementId,
     /// The underlying session handle.
     pub handle: Arc<SessionHandle>,
+    /// How to find this element again, if it becomes stale.
+    locator: Option<Arc<ElementLocator>>,
+    /// The frames containing this element, if it was found via a frame search.
+    frames: Option<Arc<[ElementId]>>,
+}
+
+/// How to find an element again, if it becomes stale.
+///
+/// Used to find the element again when `StaleElementPolicy::Refind` is set.
+#[derive(Debug)]
+struct ElementLocator {
+    source: LocatorSource,
+    /// The id of the element after it was found again.
+    current: ArcSwapOption<ElementId>,
+}
+
+/// The search that originally found an element.
+#[derive(Debug)]
+enum LocatorSource {
+    /// Found via `find()`, from the specified element or from the document.
+    Find {
+        parent: Option<WebElement>,
+        by: By,
+    },
+    /// Found via `ElementQuery::first()`, or `ElementQuery::single()` if `single` is true.
+    Query {
+        query: ElementQuery,
+        single: bool,
+    },
+}
+
+impl Clone for WebElement {
+    /// Clone this element, using its current id if it was found again after becoming stale.
+    fn clone(&self) -> Self {
+        Self {
+            element_id: self.element_id(),
+            handle: self.handle.clone(),
+            locator: self.locator.clone(),
+            frames: self.frames.clone(),
+        }
+    }
 }
 
 impl fmt::Debug for WebElement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("WebElement").field("element", &self.element_id).finish()
+        f.debug_struct("WebElement").field("element", &self.element_id()).finish()
     }
 }
 
@@ -86,7 +138,74 @@ impl WebElement {
         Self {
             element_id,
             handle,
+            locator: None,
+            frames: None,
+        }
+    }
+
+    /// Bind this element to the frame at the specified path.
+    ///
+    /// Every command sent for a bound element first switches into its frame (unless it is
+    /// already the current frame), and then back to the previous frame afterwards.
+    pub(crate) fn in_frames(mut self, frames: &Arc<[ElementId]>) -> Self {
+        if !frames.is_empty() {
+            self.frames = Some(Arc::clone(frames));
+        }
+        self
+    }
+
+    /// The ids of the frame elements containing this element, starting from the top-level
+    /// document.
+    ///
+    /// This is empty unless the element was found via a query using `in_any_frame()` or
+    /// `in_frame_path()`, or from an element that was.
+    pub fn frame_path(&self) -> &[ElementId] {
+        self.frames.as_deref().unwrap_or_default()
+    }
+
+    /// Bind this element to the same frame as the specified parent element, if any.
+    fn in_frame_of(self, parent: &WebElement) -> Self {
+        match &parent.frames {
+            Some(frames) => self.in_frames(frames),
+            None => self,
+        }
+    }
+
+    /// Remember how this element was found, so that it can be found again if it
+    /// becomes stale.
+    ///
+    /// This does nothing unless `StaleElementPolicy::Refind` is set.
+    fn with_locator(mut self, source: LocatorSource) -> Self {
+        if self.handle.config().stale_element_policy == StaleElementPolicy::Refind {
+            self.locator = Some(Arc::new(ElementLocator {
+                source,
+                current: ArcSwapOption::empty(),
+            }));
         }
+        self
+    }
+
+    /// Remember the selector (and parent element) used to find this element via `find()`.
+    ///
+    /// Elements found from a parent element are also bound to the same frame as the parent.
+    pub(crate) fn with_find_locator(self, parent: Option<&WebElement>, by: &By) -> Self {
+        let element = match parent {
+            Some(parent) => self.in_frame_of(parent),
+            None => self,
+        };
+        element.with_locator(LocatorSource::Find {
+            parent: parent.cloned(),
+            by: by.clone(),
+        })
+    }
+
+    /// Remember the query used to find this element via `ElementQuery::first()`, or
+    /// `ElementQuery::single()` if `single` is true.
+    pub(crate) fn with_query_locator(self, query: &ElementQuery, single: bool) -> Self {
+        self.with_locator(LocatorSource::Query {
+            query: query.clone(),
+            single,
+        })
     }
 
     /// Construct a `WebElement` from a JSON response and a session handle.
@@ -104,10 +223,7 @@ impl WebElement {
     /// [`ScriptRet::element`]: crate::session::scriptret::ScriptRet::element
     pub fn from_json(value: Value, handle: Arc<SessionHandle>) -> WebDriverResult<Self> {
         let element_ref: ElementRef = serde_json::from_value(value)?;
-        Ok(Self {
-            element_id: ElementId::from(element_ref.id()),
-            handle,
-        })
+        Ok(Self::new(ElementId::from(element_ref.id()), handle))
     }
 
     /// Serialize this `WebElement` to JSON.
@@ -117,7 +233,7 @@ impl WebElement {
     /// See the documentation for [`SessionHandle::execute`] for more details.
     pub fn to_json(&self) -> WebDriverResult<Value> {
         Ok(serde_json::to_value(ElementRef::Element {
-            id: self.element_id.to_string(),
+            id: self.element_id().to_string(),
         })?)
     }
 
@@ -125,8 +241,109 @@ impl WebElement {
     ///
     /// NOTE: If you want the `id` property of an element,
     ///       use [`WebElement::id`] instead.
+    ///
+    /// If the element was found again after becoming stale, this returns the new id.
     pub fn element_id(&self) -> ElementId {
-        self.element_id.clone()
+        match self.locator.as_ref().and_then(|l| l.current.load_full()) {
+            Some(id) => ElementId::clone(&id),
+            None => self.element_id.clone(),
+        }
+    }
+
+    /// Run the specified operation using the id of this element.
+    ///
+    /// If the element is bound to a frame, the operation runs inside that frame.
+    /// If the operation fails because the element is stale, and `StaleElementPolicy::Refind`
+    /// is set, the element is found again and the operation is retried once.
+    async fn with_id<T, F, Fut>(&self, f: F) -> WebDriverResult<T>
+    where
+        F: Fn(ElementId) -> Fut,
+        Fut: Future<Output = WebDriverResult<T>>,
+    {
+        match self.in_own_frame(f(self.element_id())).await {
+            Err(e) if matches!(*e, WebDriverErrorInner::StaleElementReference(_)) => {
+                match self.refind().await {
+                    Some(id) => self.in_own_frame(f(id)).await,
+                    None => Err(e),
+                }
+            }
+            result => result,
+        }
+    }
+
+    /// Run the specified future inside the frame containing this element, if it is bound
+    /// to a frame.
+    async fn in_own_frame<T>(
+        &self,
+        f: impl Future<Output = WebDriverResult<T>>,
+    ) -> WebDriverResult<T> {
+        match &self.frames {
+            Some(frames) => self.handle.in_frame_path(frames, f).await,
+            None => f.await,
+        }
+    }
+
+    /// Send the command built by the specified function, using the id of this element.
+    async fn cmd(&self, command: impl Fn(ElementId) -> Command) -> WebDriverResult<CmdResponse> {
+        self.with_id(|id| self.handle.cmd(command(id))).await
+    }
+
+    /// Run the specified script with this element as the first argument, followed by
+    /// the specified arguments.
+    async fn execute_with_self(&self, script: &str, args: &[Value]) -> WebDriverResult<ScriptRet> {
+        self.with_id(|id| {
+            let arg = serde_json::to_value(ElementRef::Element {
+                id: id.to_string(),
+            });
+            async move {
+                let args: Vec<Value> = std::iter::once(arg?).chain(args.iter().cloned()).collect();
+                self.handle.execute(script, args).await
+            }
+        })
+        .await
+    }
+
+    /// Find this element again by repeating the search it was originally found with.
+    ///
+    /// Returns the new element id, or `None` if the element could not be found.
+    fn refind(&self) -> BoxFuture<'_, Option<ElementId>> {
+        Box::pin(async move {
+            let locator = self.locator.as_ref()?;
+            if self.handle.config().stale_element_policy != StaleElementPolicy::Refind {
+                return None;
+            }
+
+            let element = match &locator.source {
+                LocatorSource::Find {
+                    parent: Some(parent),
+                    by,
+                } => parent.find(by.clone()).await,
+                LocatorSource::Find {
+                    parent: None,
+                    by,
+                } => self.in_own_frame(self.handle.find(by.clone())).await,
+                LocatorSource::Query {
+                    query,
+                    single,
+                } => {
+                    let query = query.clone().nowait();
+                    match single {
+                        true => query.single().await,
+                        false => query.first().await,
+                    }
+                }
+            };
+            let element = element.ok()?;
+            // Commands for this element always switch into its original frame.
+            if element.frame_path() != self.frame_path() {
+                return None;
+            }
+
+            let id = element.element_id();
+            tracing::debug!("stale element {} found again as {id}", self.element_id());
+            locator.current.store(Some(Arc::new(id.clone())));
+            Some(id)
+        })
     }
 
     /// Get the bounding rectangle for this WebElement.
@@ -148,7 +365,7 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn rect(&self) -> WebDriverResult<ElementRect> {
-        let r = self.handle.cmd(Command::GetElementRect(self.element_id.clone())).await?;
+        let r = self.cmd(Command::GetElementRect).await?;
         r.value()
     }
 
@@ -177,7 +394,7 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn tag_name(&self) -> WebDriverResult<String> {
-        self.handle.cmd(Command::GetElementTagName(self.element_id.clone())).await?.value()
+        self.cmd(Command::GetElementTagName).await?.value()
     }
 
     /// Get the class name for this WebElement.
@@ -244,7 +461,7 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn text(&self) -> WebDriverResult<String> {
-        self.handle.cmd(Command::GetElementText(self.element_id.clone())).await?.value()
+        self.cmd(Command::GetElementText).await?.value()
     }
 
     /// Convenience method for getting the (optional) value property of this element.
@@ -271,7 +488,13 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn click(&self) -> WebDriverResult<()> {
-        self.handle.cmd(Command::ElementClick(self.element_id.clone())).await?;
+        for listener in &self.handle.config().event_listeners {
+            listener.before_click(self).await;
+        }
+        self.cmd(Command::ElementClick).await?;
+        for listener in &self.handle.config().event_listeners {
+            listener.after_click(self).await;
+        }
         Ok(())
     }
 
@@ -294,7 +517,13 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn clear(&self) -> WebDriverResult<()> {
-        self.handle.cmd(Command::ElementClear(self.element_id.clone())).await?;
+        for listener in &self.handle.config().event_listeners {
+            listener.before_clear(self).await;
+        }
+        self.cmd(Command::ElementClear).await?;
+        for listener in &self.handle.config().event_listeners {
+            listener.after_clear(self).await;
+        }
         Ok(())
     }
 
@@ -321,10 +550,8 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn prop(&self, name: impl IntoArcStr) -> WebDriverResult<Option<String>> {
-        let resp = self
-            .handle
-            .cmd(Command::GetElementProperty(self.element_id.clone(), name.into()))
-            .await?;
+        let name = name.into();
+        let resp = self.cmd(|id| Command::GetElementProperty(id, name.clone())).await?;
         match resp.value()? {
             Value::String(v) => Ok(Some(v)),
             Value::Bool(b) => Ok(Some(b.to_string())),
@@ -363,10 +590,8 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn attr(&self, name: impl IntoArcStr) -> WebDriverResult<Option<String>> {
-        self.handle
-            .cmd(Command::GetElementAttribute(self.element_id.clone(), name.into()))
-            .await?
-            .value()
+        let name = name.into();
+        self.cmd(|id| Command::GetElementAttribute(id, name.clone())).await?.value()
     }
 
     /// Get the specified attribute.
@@ -398,10 +623,8 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn css_value(&self, name: impl IntoArcStr) -> WebDriverResult<String> {
-        self.handle
-            .cmd(Command::GetElementCssValue(self.element_id.clone(), name.into()))
-            .await?
-            .value()
+        let name = name.into();
+        self.cmd(|id| Command::GetElementCssValue(id, name.clone())).await?.value()
     }
 
     /// Get the specified CSS property.
@@ -412,7 +635,7 @@ impl WebElement {
 
     /// Return true if the WebElement is currently selected, otherwise false.
     pub async fn is_selected(&self) -> WebDriverResult<bool> {
-        self.handle.cmd(Command::IsElementSelected(self.element_id.clone())).await?.value()
+        self.cmd(Command::IsElementSelected).await?.value()
     }
 
     /// Return true if the WebElement is currently displayed, otherwise false.
@@ -434,7 +657,7 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn is_displayed(&self) -> WebDriverResult<bool> {
-        self.handle.cmd(Command::IsElementDisplayed(self.element_id.clone())).await?.value()
+        self.cmd(Command::IsElementDisplayed).await?.value()
     }
 
     /// Return true if the WebElement is currently enabled, otherwise false.
@@ -456,7 +679,7 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn is_enabled(&self) -> WebDriverResult<bool> {
-        self.handle.cmd(Command::IsElementEnabled(self.element_id.clone())).await?.value()
+        self.cmd(Command::IsElementEnabled).await?.value()
     }
 
     /// Return true if the WebElement is currently clickable (visible and enabled),
@@ -544,11 +767,29 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn find(&self, by: By) -> WebDriverResult<WebElement> {
-        let r = self
-            .handle
-            .cmd(Command::FindElementFromElement(self.element_id.clone(), by.into()))
-            .await?;
-        r.element(self.handle.clone())
+        if self.handle.config().validate_selectors {
+            by.validate()?;
+        }
+        let elem = match by.script() {
+            Some((script, query)) => {
+                let elems = self.execute_with_self(&script, &[query]).await?.elements()?;
+                elems.into_iter().next().ok_or_else(|| {
+                    crate::error::no_such_element(format!(
+                        "no such element: element not found using selector: {by}"
+                    ))
+                })?
+            }
+            None => {
+                let r =
+                    self.cmd(|id| Command::FindElementFromElement(id, by.clone().into())).await?;
+                r.element(self.handle.clone())?
+            }
+        };
+        let elem = elem.with_find_locator(Some(self), &by);
+        for listener in &self.handle.config().event_listeners {
+            listener.on_find(&by, std::slice::from_ref(&elem)).await;
+        }
+        Ok(elem)
     }
 
     /// Search for a child element of this WebElement using the specified selector.
@@ -584,11 +825,22 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn find_all(&self, by: By) -> WebDriverResult<Vec<WebElement>> {
-        let r = self
-            .handle
-            .cmd(Command::FindElementsFromElement(self.element_id.clone(), by.into()))
-            .await?;
-        r.elements(self.handle.clone())
+        if self.handle.config().validate_selectors {
+            by.validate()?;
+        }
+        let elems = match by.script() {
+            Some((script, query)) => self.execute_with_self(&script, &[query]).await?.elements()?,
+            None => {
+                let r =
+                    self.cmd(|id| Command::FindElementsFromElement(id, by.clone().into())).await?;
+                r.elements(self.handle.clone())?
+            }
+        };
+        let elems: Vec<_> = elems.into_iter().map(|e| e.in_frame_of(self)).collect();
+        for listener in &self.handle.config().event_listeners {
+            listener.on_find(&by, &elems).await;
+        }
+        Ok(elems)
     }
 
     /// Search for all child elements of this WebElement that match the specified selector.
@@ -635,13 +887,20 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn send_keys(&self, key: impl Into<TypingData>) -> WebDriverResult<()> {
-        self.handle.cmd(Command::ElementSendKeys(self.element_id.clone(), key.into())).await?;
+        let keys = key.into();
+        for listener in &self.handle.config().event_listeners {
+            listener.before_send_keys(self, &keys).await;
+        }
+        self.cmd(|id| Command::ElementSendKeys(id, keys.clone())).await?;
+        for listener in &self.handle.config().event_listeners {
+            listener.after_send_keys(self, &keys).await;
+        }
         Ok(())
     }
 
     /// Take a screenshot of this WebElement and return it as PNG, base64 encoded.
     pub async fn screenshot_as_png_base64(&self) -> WebDriverResult<String> {
-        self.handle.cmd(Command::TakeElementScreenshot(self.element_id.clone())).await?.value()
+        self.cmd(Command::TakeElementScreenshot).await?.value()
     }
 
     /// Take a screenshot of this WebElement and return it as PNG bytes.
@@ -675,7 +934,7 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn focus(&self) -> WebDriverResult<()> {
-        self.handle.execute(r#"arguments[0].focus();"#, vec![self.to_json()?]).await?;
+        self.execute_with_self(r#"arguments[0].focus();"#, &[]).await?;
         Ok(())
     }
 
@@ -698,12 +957,11 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn scroll_into_view(&self) -> WebDriverResult<()> {
-        self.handle
-            .execute(
-                r#"arguments[0].scrollIntoView({block: "center", inline: "center"});"#,
-                vec![self.to_json()?],
-            )
-            .await?;
+        self.execute_with_self(
+            r#"arguments[0].scrollIntoView({block: "center", inline: "center"});"#,
+            &[],
+        )
+        .await?;
         Ok(())
     }
 
@@ -751,14 +1009,25 @@ impl WebElement {
         self.prop("outerHTML").await.map(|x| x.unwrap_or_default())
     }
 
+    /// Take a `DomSnapshot` of this element and its descendants, which can be queried
+    /// locally without sending any further WebDriver commands.
+    ///
+    /// The whole document is serialized and parsed, so that the element is parsed in the
+    /// same context as in the page (e.g. table rows within a table). The element must not be
+    /// inside a shadow root.
+    ///
+    /// This requires the `dom-snapshot` feature. See `DomSnapshot` for an example.
+    #[cfg(feature = "dom-snapshot")]
+    pub async fn dom_snapshot(&self) -> WebDriverResult<crate::extensions::snapshot::DomSnapshot> {
+        crate::extensions::snapshot::DomSnapshot::capture(&self.handle, Some(self)).await
+    }
+
     /// Get the shadowRoot property of the current element.
     ///
     /// Call this method on the element containing the `#shadowRoot` node.
     /// You can then use the returned `WebElement` to query elements within the shadowRoot node.
     pub async fn get_shadow_root(&self) -> WebDriverResult<WebElement> {
-        let ret =
-            self.handle.execute("return arguments[0].shadowRoot", vec![self.to_json()?]).await?;
-        ret.element()
+        self.execute_with_self("return arguments[0].shadowRoot", &[]).await?.element()
     }
 
     /// Switch to the specified iframe element.
@@ -783,7 +1052,16 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn enter_frame(self) -> WebDriverResult<()> {
-        self.handle.cmd(Command::SwitchToFrameElement(self.element_id.clone())).await?;
+        match &self.frames {
+            // Stay inside the frame, rather than switching back to the top-level document.
+            Some(frames) => {
+                self.handle.enter_frame_path(frames).await?;
+                self.handle.cmd(Command::SwitchToFrameElement(self.element_id())).await?;
+            }
+            None => {
+                self.cmd(Command::SwitchToFrameElement).await?;
+            }
+        }
         Ok(())
     }
 
@@ -807,9 +1085,7 @@ impl WebElement {
     /// # }
     /// ```
     pub async fn js_drag_to(&self, target: &Self) -> WebDriverResult<()> {
-        self.handle
-            .execute(SIMULATE_DRAG_AND_DROP, vec![self.to_json()?, target.to_json()?])
-            .await?;
+        self.execute_with_self(SIMULATE_DRAG_AND_DROP, &[target.to_json()?]).await?;
         Ok(())
     }
 
@@ -838,7 +1114,7 @@ impl WebElement {
 
 impl fmt::Display for WebElement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self.element_id)
+        write!(f, "{:?}", self.element_id())
     }
 }
 
@@ -847,6 +1123,6 @@ impl Serialize for WebElement {
     where
         S: Serializer,
     {
-        self.element_id.serialize(serializer)
+        self.element_id().serialize(serializer)
     }
 }
@@ -87,7 +87,7 @@ pub fn get_limiter() -> &'static Semaphore {
 /// Locks the Firefox browser for exclusive use.
 ///
 /// This ensures there is only ever one Firefox browser running at a time.
-pub async fn lock_firefox<'a>(browser: &str) -> Option<SemaphorePermit<'static>> {
+pub async fn lock_firefox(browser: &str) -> Option<SemaphorePermit<'static>> {
     if browser == "firefox" {
         Some(get_limiter().acquire().await.unwrap())
     } else {
@@ -215,4 +215,63 @@ mod feature_component {
             Ok(())
         })
     }
+
+    #[derive(Debug, Component, Clone)]
+    pub struct TestComponentLocator {
+        base: WebElement,
+        #[by(locator = "checkboxes.labels", not_empty)]
+        labels: ElementResolver<Vec<WebElement>>,
+        #[by(locator = "checkboxes.missing", nowait)]
+        missing: ElementResolver<WebElement>,
+    }
+
+    #[rstest]
+    fn component_attributes_locator(test_harness: TestHarness) -> WebDriverResult<()> {
+        use thirtyfour::LocatorRepository;
+
+        let c = test_harness.driver();
+        block_on(async {
+            let url = sample_page_url();
+            c.goto(&url).await?;
+
+            let locators =
+                LocatorRepository::from_json(r#"{"checkboxes": {"labels": "tag=label"}}"#)?;
+            LocatorRepository::set_global(locators);
+
+            let elem = c.query(By::Id("checkbox-section")).single().await?;
+            let tc = TestComponentLocator::new(elem);
+            assert!(!tc.labels.resolve().await?.is_empty());
+            assert_matches!(
+                tc.missing.resolve().await.map_err(WebDriverError::into_inner),
+                Err(WebDriverErrorInner::InvalidSelector(_))
+            );
+
+            Ok(())
+        })
+    }
+
+    #[derive(Debug, Component, Clone)]
+    pub struct TestComponentJs {
+        base: WebElement,
+        #[by(js = "return this.querySelectorAll('input[type=checkbox]');", not_empty)]
+        checkboxes: ElementResolver<Vec<WebElement>>,
+        #[by(js = "return this.querySelector('label');", first)]
+        label: ElementResolver<WebElement>,
+    }
+
+    #[rstest]
+    fn component_attributes_js(test_harness: TestHarness) -> WebDriverResult<()> {
+        let c = test_harness.driver();
+        block_on(async {
+            let url = sample_page_url();
+            c.goto(&url).await?;
+
+            let elem = c.query(By::Id("checkbox-section")).single().await?;
+            let tc = TestComponentJs::new(elem);
+            assert_eq!(tc.checkboxes.resolve().await?.len(), 3);
+            assert_eq!(tc.label.resolve().await?.tag_name().await?, "label");
+
+            Ok(())
+        })
+    }
 }
@@ -2,6 +2,8 @@
 use crate::common::sample_page_url;
 use common::*;
 use rstest::rstest;
+use thirtyfour::common::config::{StaleElementPolicy, WebDriverConfig};
+use thirtyfour::error::WebDriverErrorInner;
 use thirtyfour::{prelude::*, support::block_on};
 
 mod common;
@@ -260,3 +262,170 @@ fn element_get_parent(test_harness: TestHarness) -> WebDriverResult<()> {
         Ok(())
     })
 }
+
+#[rstest]
+fn element_refind_when_stale(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        c.goto(sample_page_url()).await?;
+        let rerender = r#"
+            const elem = document.getElementById("section-text");
+            elem.replaceWith(elem.cloneNode(true));
+        "#;
+
+        // By default, stale elements return an error.
+        let button = c.find(By::Id("section-text")).await?.find(By::Id("button-copy")).await?;
+        c.execute(rerender, Vec::new()).await?;
+        let err = button.text().await.expect_err("element should be stale");
+        assert!(matches!(err.as_inner(), WebDriverErrorInner::StaleElementReference(_)));
+
+        let config =
+            WebDriverConfig::builder().stale_element_policy(StaleElementPolicy::Refind).build()?;
+        let healing = c.clone_with_config(config);
+        let button =
+            healing.find(By::Id("section-text")).await?.find(By::Id("button-copy")).await?;
+        let original_id = button.element_id();
+        c.execute(rerender, Vec::new()).await?;
+        assert_eq!(button.text().await?, "Copy");
+        assert_ne!(button.element_id(), original_id);
+        assert_eq!(button.element_id, original_id);
+        assert_eq!(button.clone().element_id, button.element_id());
+
+        // Queries are run again in full, including filters, even if rows were inserted.
+        c.execute(
+            r#"document.body.insertAdjacentHTML("beforeend", "<ul id='rows'><li>one</li><li>two</li></ul>");"#,
+            Vec::new(),
+        )
+        .await?;
+        let row = healing.query(By::Css("#rows li")).with_text("two").first().await?;
+        let rows = healing.find_all(By::Css("#rows li")).await?;
+        c.execute(
+            r#"document.getElementById("rows").outerHTML = "<ul id='rows'><li>zero</li><li>one</li><li>two</li></ul>";"#,
+            Vec::new(),
+        )
+        .await?;
+        assert_eq!(row.text().await?, "two");
+
+        // Elements found along with other elements are never found again.
+        let err = rows[1].text().await.expect_err("element should be stale");
+        assert!(matches!(err.as_inner(), WebDriverErrorInner::StaleElementReference(_)));
+        Ok(())
+    })
+}
+
+#[rstest]
+fn find_deep_css(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+        c.execute(
+            r#"
+            const app = document.createElement("my-app");
+            document.getElementById("footer").appendChild(app);
+            const panel = document.createElement("settings-panel");
+            app.attachShadow({ mode: "open" }).appendChild(panel);
+            const button = document.createElement("button");
+            button.className = "save";
+            button.textContent = "Save";
+            panel.attachShadow({ mode: "open" }).appendChild(button);
+            "#,
+            Vec::new(),
+        )
+        .await?;
+
+        let elem = c.find(By::DeepCss("my-app >>> settings-panel >>> button.save")).await?;
+        assert_eq!(elem.text().await?, "Save");
+
+        let footer = c.find(By::Id("footer")).await?;
+        let elems = footer.find_all(By::DeepCss("my-app >>> button.save")).await?;
+        assert!(elems.is_empty());
+        let elems = footer.find_all(By::DeepCss("my-app >>> * >>> button")).await?;
+        assert_eq!(elems.len(), 1);
+        Ok(())
+    })
+}
+
+#[cfg(feature = "dom-snapshot")]
+#[rstest]
+fn dom_snapshot(test_harness: TestHarness) -> WebDriverResult<()> {
+    use assert_matches::assert_matches;
+
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        // Snapshot elements borrow the snapshot, which is not `Sync`, so they are not held
+        // across an await.
+        let snapshot = c.dom_snapshot().await?;
+        let alert_button = {
+            assert_eq!(snapshot.root().tag_name(), "html");
+            let labels: Vec<String> =
+                snapshot.find_all("button")?.iter().map(|b| b.text()).collect();
+            assert!(labels.contains(&"Show alert".to_string()), "{labels:?}");
+
+            let button = snapshot.find("#button-alert")?;
+            assert_eq!(button.attr("onclick"), Some("showAlert()"));
+            button.live()
+        };
+        assert_eq!(alert_button.await?.id().await?.unwrap(), "button-alert");
+
+        let section = c.find(By::Id("checkbox-section")).await?;
+        let snapshot = section.dom_snapshot().await?;
+        let (disabled, disabled_again) = {
+            assert_eq!(snapshot.root().id(), Some("checkbox-section"));
+            assert!(snapshot.root().parent().is_none());
+            assert_matches!(
+                snapshot.find("select").map_err(WebDriverError::into_inner),
+                Err(WebDriverErrorInner::NoSuchElement(_))
+            );
+            assert_matches!(
+                snapshot.find("input[").map_err(WebDriverError::into_inner),
+                Err(WebDriverErrorInner::InvalidSelector(_))
+            );
+
+            let checkboxes = snapshot.find_all("input[type=checkbox]")?;
+            assert_eq!(checkboxes.len(), 3);
+            (checkboxes[1].live(), checkboxes[1].live())
+        };
+        // Elements inserted before it do not affect which element is found.
+        c.execute(
+            r#"var section = document.getElementById("checkbox-section");
+            section.insertBefore(document.createElement("input"), section.firstChild);"#,
+            Vec::new(),
+        )
+        .await?;
+        assert_eq!(disabled.await?.id().await?.unwrap(), "checkbox-disabled");
+
+        // Once the page changes, the element can no longer be found.
+        c.execute(r#"document.getElementById("checkbox-section").innerHTML = "";"#, Vec::new())
+            .await?;
+        assert!(disabled_again.await.is_err());
+        Ok(())
+    })
+}
+
+#[rstest]
+fn element_validate_selectors(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        // Once enabled, selectors are checked locally before they are sent.
+        let config = WebDriverConfig::builder().validate_selectors(true).build()?;
+        let checked = c.clone_with_config(config);
+        let err = checked.find(By::Css("div[")).await.unwrap_err();
+        assert!(matches!(err.as_inner(), WebDriverErrorInner::InvalidSelector(_)));
+        assert!(err.to_string().contains("at position 4"), "{err}");
+        let err = checked.find(By::ClassName("foo bar")).await.unwrap_err();
+        assert!(matches!(err.as_inner(), WebDriverErrorInner::InvalidSelector(_)));
+
+        // By default, the browser reports the error instead.
+        let err = c.find(By::Css("div[")).await.unwrap_err();
+        assert!(matches!(err.as_inner(), WebDriverErrorInner::InvalidSelector(_)));
+        assert!(!err.to_string().contains("at position 4"), "{err}");
+        Ok(())
+    })
+}
@@ -1,8 +1,14 @@
 //! Tests that don't make use of external websites.
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use assert_matches::assert_matches;
 use rstest::rstest;
-use thirtyfour::{prelude::*, support::block_on, SameSite};
+use thirtyfour::common::config::WebDriverConfig;
+use thirtyfour::common::events::EventListener;
+use thirtyfour::error::WebDriverErrorInner;
+use thirtyfour::session::handle::SessionHandle;
+use thirtyfour::{prelude::*, support::block_on, CancellationToken, RequestData, SameSite};
 
 use crate::common::*;
 
@@ -35,6 +41,195 @@ fn resolve_execute_async_value(test_harness: TestHarness) -> WebDriverResult<()>
     })
 }
 
+#[rstest]
+fn command_timeout(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let r = c
+            .with_timeout(Duration::from_millis(500))
+            .execute_async("/* never calls back */", Vec::new())
+            .await;
+        assert_matches!(r.map_err(|e| e.into_inner()), Err(WebDriverErrorInner::CommandTimeout(_)));
+
+        // The original driver is unaffected, and still usable.
+        assert_eq!(c.current_url().await?.as_str(), url);
+        Ok(())
+    })
+}
+
+#[rstest]
+fn command_cancellation(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let token = CancellationToken::new();
+        let cancellable = c.with_cancellation(token.clone());
+        let task = tokio::spawn(async move {
+            cancellable.execute_async("/* never calls back */", Vec::new()).await
+        });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        token.cancel();
+
+        let r = task.await.expect("task should not panic");
+        assert_matches!(
+            r.map_err(|e| e.into_inner()),
+            Err(WebDriverErrorInner::CommandCancelled(_))
+        );
+        assert_eq!(c.current_url().await?.as_str(), url);
+        Ok(())
+    })
+}
+
+#[rstest]
+fn command_metrics(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let config = WebDriverConfig::builder().collect_metrics(true).build()?;
+        let metered = c.clone_with_config(config);
+        assert!(c.metrics_report().is_none());
+
+        metered.goto(sample_page_url()).await?;
+        metered.title().await?;
+        metered.title().await?;
+        assert!(metered.find(By::Id("doesnotexist")).await.is_err());
+
+        let report = metered.metrics_report().expect("metrics should be enabled");
+        assert_eq!(report.total_commands(), 4);
+        assert_eq!(report.total_errors(), 1);
+        assert_eq!(report.command("GetTitle").map(|s| s.count), Some(2));
+        let find = report.command("FindElement").expect("FindElement should be recorded");
+        assert_eq!(find.errors_by_kind.get("NoSuchElement"), Some(&1));
+        Ok(())
+    })
+}
+
+#[derive(Debug, Default)]
+struct RecordingListener {
+    events: Mutex<Vec<String>>,
+}
+
+impl RecordingListener {
+    fn push(&self, event: impl Into<String>) {
+        self.events.lock().unwrap().push(event.into());
+    }
+}
+
+#[async_trait::async_trait]
+impl EventListener for RecordingListener {
+    async fn on_error(&self, command: &str, _request: &RequestData, _error: &WebDriverError) {
+        self.push(format!("error {command}"));
+    }
+
+    async fn before_navigate(&self, _handle: &SessionHandle, _url: &str) {
+        self.push("before_navigate");
+    }
+
+    async fn after_navigate(&self, _handle: &SessionHandle, _url: &str) {
+        self.push("after_navigate");
+    }
+
+    async fn on_find(&self, _by: &By, elements: &[WebElement]) {
+        self.push(format!("found {}", elements.len()));
+    }
+
+    async fn before_click(&self, _elem: &WebElement) {
+        self.push("before_click");
+    }
+
+    async fn after_click(&self, _elem: &WebElement) {
+        self.push("after_click");
+    }
+}
+
+#[rstest]
+fn event_listener(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let listener = Arc::new(RecordingListener::default());
+        let config = WebDriverConfig::builder().event_listener(listener.clone()).build()?;
+        let observed = c.clone_with_config(config);
+
+        observed.goto(sample_page_url()).await?;
+        observed.find(By::Id("button-copy")).await?.click().await?;
+        assert!(observed.find(By::Id("doesnotexist")).await.is_err());
+
+        let events = listener.events.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            [
+                "before_navigate",
+                "after_navigate",
+                "found 1",
+                "before_click",
+                "after_click",
+                "error FindElement"
+            ]
+        );
+        Ok(())
+    })
+}
+
+#[rstest]
+fn failure_artifacts(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let dir = std::env::temp_dir().join(format!("thirtyfour-artifacts-{}", c.session_id()));
+        let config = WebDriverConfig::builder().failure_artifacts_dir(&dir).build()?;
+        let observed = c.clone_with_config(config);
+        let _guard = observed.failure_artifacts_guard("failure_artifacts");
+
+        observed.goto(sample_page_url()).await?;
+        // Script errors are routinely retried, so they should not save artifacts.
+        assert!(observed.execute("throw new Error('oops');", Vec::new()).await.is_err());
+        assert!(!dir.join("failure_artifacts").exists());
+        assert!(observed.get_named_cookie("missing").await.is_err());
+
+        let saved = dir.join("failure_artifacts").join("01-GetNamedCookie");
+        for file in ["screenshot.png", "page_source.html", "url.txt", "error.txt", "history.txt"] {
+            assert!(saved.join(file).is_file(), "{file} should be saved");
+        }
+        let history = std::fs::read_to_string(saved.join("history.txt"))?;
+        assert!(history.contains("NavigateTo"));
+        assert!(history.contains("ExecuteScript"));
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    })
+}
+
+#[rstest]
+fn error_context(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let err = c.find(By::Css(".doesnotexist")).await.expect_err("element should not exist");
+        assert_eq!(err.context().and_then(|context| context.url()), None);
+
+        let config = WebDriverConfig::builder().error_url_context(true).build()?;
+        let c = c.clone_with_config(config);
+        let err = c.find(By::Css(".doesnotexist")).await.expect_err("element should not exist");
+        assert_matches!(err.as_inner(), WebDriverErrorInner::NoSuchElement(_));
+        let context = err.context().expect("error should have context");
+        assert_eq!(context.command(), "FindElement");
+        assert_eq!(
+            context.selector().map(|s| s.to_string()).as_deref(),
+            Some("css selector: .doesnotexist")
+        );
+        assert_eq!(context.url(), Some(url.as_str()));
+        assert_eq!(context.session_id(), c.session_id());
+        assert!(err
+            .to_string()
+            .contains(&format!("FindElement(css selector: .doesnotexist) on {url}")));
+        Ok(())
+    })
+}
+
 #[rstest]
 fn status(test_harness: TestHarness) -> WebDriverResult<()> {
     let c = test_harness.driver();
@@ -1,9 +1,13 @@
 use crate::common::*;
 use assert_matches::assert_matches;
 use rstest::rstest;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use thirtyfour::components::{ElementResolverMulti, ElementResolverSingle};
 use thirtyfour::error::WebDriverErrorInner;
+use thirtyfour::extensions::query::ElementOrder;
+use thirtyfour::stringmatch::StringMatch;
 use thirtyfour::support::block_on;
 use thirtyfour::{components::SelectElement, prelude::*};
 
@@ -334,3 +338,553 @@ fn find_element_from_element(test_harness: TestHarness) -> WebDriverResult<()> {
         Ok(())
     })
 }
+
+#[rstest]
+fn query_relative(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+        let copy = c.find(By::Id("button-copy")).await?;
+
+        let elem = c.query(By::Tag("input")).left_of(&copy).near(&copy, 50).single().await?;
+        assert_eq!(elem.id().await?.unwrap(), "text-input2");
+
+        // Matches are sorted by distance from the anchor.
+        let elem = c.query(By::Tag("select")).below(&copy).first().await?;
+        assert_eq!(elem.id().await?.unwrap(), "select1");
+
+        let alert = c.find(By::Id("button-alert")).await?;
+        let elem = c.query(By::Tag("button")).right_of(&alert).first().await?;
+        assert_eq!(elem.id().await?.unwrap(), "button-confirm");
+
+        let elem = c.query(By::Tag("button")).above(&alert).first().await?;
+        assert_eq!(elem.id().await?.unwrap(), "button-copy");
+
+        // Elements in the same row are neither above nor below each other.
+        assert!(c.query(By::Id("text-input2")).above(&copy).nowait().not_exists().await?);
+        Ok(())
+    })
+}
+
+#[rstest]
+fn find_accessible(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let elem = c.find(By::Role("button", "Show alert")).await?;
+        assert_eq!(elem.id().await?.unwrap(), "button-alert");
+        let elems = c.find_all(By::Role("checkbox", "")).await?;
+        // The hidden checkbox is excluded.
+        assert_eq!(elems.len(), 2);
+        let elem = c.find(By::Role("checkbox", "Option 1")).await?;
+        assert_eq!(elem.id().await?.unwrap(), "checkbox-option-1");
+
+        let elem = c.find(By::Label("Text:")).await?;
+        assert_eq!(elem.id().await?.unwrap(), "text-input");
+        let elem = c.find(By::Placeholder("Text to copy")).await?;
+        assert_eq!(elem.id().await?.unwrap(), "text-input2");
+
+        let elem = c.find(By::Text("Copy", true)).await?;
+        assert_eq!(elem.id().await?.unwrap(), "button-copy");
+        let elems = c.find_all(By::Text("show", false)).await?;
+        assert_eq!(elems.len(), 3);
+
+        c.execute(
+            r#"const img = document.createElement("img"); img.alt = "Logo"; img.id = "logo";
+            document.getElementById("footer").appendChild(img);"#,
+            Vec::new(),
+        )
+        .await?;
+        let footer = c.find(By::Id("footer")).await?;
+        let elem = footer.find(By::AltText("Logo")).await?;
+        assert_eq!(elem.id().await?.unwrap(), "logo");
+
+        let elem = c.query(By::Text("nothing like this", true)).nowait().first().await;
+        assert_matches!(
+            elem.map_err(WebDriverError::into_inner),
+            Err(WebDriverErrorInner::NoSuchElement(_))
+        );
+        Ok(())
+    })
+}
+
+#[rstest]
+fn find_js(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let by = By::Js(
+            "return Array.from(this.querySelectorAll('input')).filter((e) => e.id.startsWith(arguments[0]));",
+            vec![serde_json::json!("checkbox-")],
+        );
+        let elems = c.find_all(by.clone()).await?;
+        assert_eq!(elems.len(), 3);
+
+        let section = c.find(By::Id("checkbox-section")).await?;
+        let elem = section.find(By::Js("return this.querySelector('label');", Vec::new())).await?;
+        assert_eq!(elem.tag_name().await?, "label");
+
+        let elems = c.query(by).and_enabled().and_displayed().all_from_selector_required().await?;
+        assert_eq!(elems.len(), 1);
+
+        let elem = c.query(By::Js("return null;", Vec::new())).nowait().first().await;
+        assert_matches!(
+            elem.map_err(WebDriverError::into_inner),
+            Err(WebDriverErrorInner::NoSuchElement(_))
+        );
+        Ok(())
+    })
+}
+
+#[rstest]
+fn query_count_and_order(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let elems = c.query(By::Tag("select")).exactly(3).await?;
+        assert_eq!(elems.len(), 3);
+        let elems = c.query(By::Tag("select")).nowait().exactly(2).await;
+        assert_matches!(
+            elems.map_err(WebDriverError::into_inner),
+            Err(WebDriverErrorInner::NoSuchElement(_))
+        );
+        assert_eq!(c.query(By::Tag("select")).at_least(2).await?.len(), 3);
+        assert!(c.query(By::Tag("select")).nowait().at_least(4).await.is_err());
+
+        let elem = c.query(By::Tag("select")).nth(1).await?;
+        assert_eq!(elem.id().await?.unwrap(), "select2");
+        assert!(c.query(By::Tag("select")).nowait().nth(3).await.is_err());
+        let elem = c.query(By::Tag("select")).last().await?;
+        assert_eq!(elem.id().await?.unwrap(), "select3");
+
+        // The button comes before the selects in the page.
+        let query = || c.query(By::Tag("select")).or(By::Id("button-copy"));
+        let elem = query().last().await?;
+        assert_eq!(elem.id().await?.unwrap(), "button-copy");
+        let elem = query().order_by(ElementOrder::Document).first().await?;
+        assert_eq!(elem.id().await?.unwrap(), "button-copy");
+        let elems = query().order_by(ElementOrder::Document).any_required().await?;
+        assert_eq!(elems.len(), 4);
+        assert_eq!(elems[3].id().await?.unwrap(), "select3");
+
+        let elems = c
+            .query(By::Id("button-prompt"))
+            .or(By::Id("button-alert"))
+            .order_by(ElementOrder::Position)
+            .any_required()
+            .await?;
+        assert_eq!(elems[0].id().await?.unwrap(), "button-alert");
+        assert_eq!(elems[1].id().await?.unwrap(), "button-prompt");
+        Ok(())
+    })
+}
+
+#[rstest]
+fn query_count_changing_list(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        // Re-render a list of 7 rows a few times, then shrink it to 5 rows.
+        c.execute(
+            r#"const list = document.createElement("ul");
+            list.id = "changing";
+            document.getElementById("footer").appendChild(list);
+            let renders = 0;
+            function render() {
+                const count = renders < 5 ? 7 : 5;
+                list.innerHTML = Array.from({ length: count }, (_, i) => "<li>" + (i + 1) + "</li>").join("");
+                renders++;
+            }
+            render();
+            const timer = setInterval(function () {
+                render();
+                if (renders > 5) clearInterval(timer);
+            }, 100);"#,
+            Vec::new(),
+        )
+        .await?;
+
+        // Rows removed or replaced by a re-render are not counted.
+        let elems = c.query(By::Css("#changing li")).exactly(5).await?;
+        assert_eq!(elems.len(), 5);
+        let elem = c.query(By::Css("#changing li")).last().await?;
+        assert_eq!(elem.text().await?, "5");
+        assert!(c.query(By::Css("#changing li")).nowait().nth(5).await.is_err());
+        Ok(())
+    })
+}
+
+#[rstest]
+fn query_stream(test_harness: TestHarness) -> WebDriverResult<()> {
+    use futures_util::TryStreamExt;
+    use thirtyfour::extensions::query::ElementStreamOptions;
+
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        // A list that loads 10 more items whenever it is scrolled to the bottom.
+        c.execute(
+            r#"const list = document.createElement("ul");
+            list.id = "infinite-list";
+            list.style = "height: 100px; overflow-y: auto;";
+            function load() {
+                for (let i = 0; i < 10 && list.children.length < 40; i++) {
+                    const item = document.createElement("li");
+                    item.textContent = "Item " + list.children.length;
+                    item.style = "height: 20px;";
+                    list.appendChild(item);
+                }
+            }
+            list.addEventListener("scroll", () => {
+                if (list.scrollTop + list.clientHeight >= list.scrollHeight - 1) {
+                    setTimeout(load, 50);
+                }
+            });
+            load();
+            document.body.appendChild(list);"#,
+            Vec::new(),
+        )
+        .await?;
+
+        let list = c.find(By::Id("infinite-list")).await?;
+        let query = list.query(By::Tag("li"));
+        let options = ElementStreamOptions::default()
+            .container(list.clone())
+            .quiet_period(Duration::from_secs(1));
+        let items: Vec<WebElement> = query.stream_with(options).try_collect().await?;
+        assert_eq!(items.len(), 40);
+        assert_eq!(items[39].text().await?, "Item 39");
+
+        // Without scrolling, only the items that are already loaded are found.
+        let options = ElementStreamOptions::default().scroll(false).quiet_period(Duration::ZERO);
+        let items: Vec<WebElement> =
+            c.query(By::Tag("li")).stream_with(options).try_collect().await?;
+        assert_eq!(items.len(), 40);
+        Ok(())
+    })
+}
+
+#[rstest]
+fn query_batch_filters(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let elems = c
+            .query(By::Tag("button"))
+            .batch_filters(true)
+            .with_text(StringMatch::new("Show").partial())
+            .and_displayed()
+            .and_enabled()
+            .all_from_selector_required()
+            .await?;
+        assert_eq!(elems.len(), 3);
+        assert_eq!(elems[0].id().await?.unwrap(), "button-alert");
+
+        let elem = c
+            .query(By::Tag("button"))
+            .batch_filters(true)
+            .with_id("button-copy")
+            .with_tag("button")
+            .single()
+            .await?;
+        assert_eq!(elem.text().await?, "Copy");
+
+        let batched = c
+            .query(By::Tag("input"))
+            .batch_filters(true)
+            .and_not_displayed()
+            .nowait()
+            .all_from_selector()
+            .await?;
+        let individual = c
+            .query(By::Tag("input"))
+            .batch_filters(false)
+            .and_not_displayed()
+            .nowait()
+            .all_from_selector()
+            .await?;
+        assert_eq!(batched, individual);
+
+        // Filters run in the order they were added, even when batched.
+        let before = Arc::new(AtomicUsize::new(0));
+        let after = Arc::new(AtomicUsize::new(0));
+        let counter = |count: &Arc<AtomicUsize>| {
+            let count = count.clone();
+            move |_: WebElement| {
+                count.fetch_add(1, Ordering::SeqCst);
+                async move { Ok(true) }
+            }
+        };
+        let elem = c
+            .query(By::Tag("button"))
+            .batch_filters(true)
+            .with_filter(counter(&before))
+            .with_id("button-copy")
+            .and_displayed()
+            .with_filter(counter(&after))
+            .nowait()
+            .single()
+            .await?;
+        assert_eq!(elem.text().await?, "Copy");
+        assert!(before.load(Ordering::SeqCst) > 1);
+        assert_eq!(after.load(Ordering::SeqCst), 1);
+        Ok(())
+    })
+}
+
+#[rstest]
+fn query_wait_on_mutation(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        c.execute(
+            r#"setTimeout(function () {
+                const div = document.createElement("div");
+                div.id = "added-later";
+                document.getElementById("footer").appendChild(div);
+            }, 500);"#,
+            Vec::new(),
+        )
+        .await?;
+        // The poller should resume when the DOM changes, well before the interval expires.
+        let start = std::time::Instant::now();
+        let elem = c
+            .query(By::Id("added-later"))
+            .wait_on_mutation(Duration::from_secs(20), Duration::from_secs(10))
+            .first()
+            .await?;
+        assert_eq!(elem.id().await?.unwrap(), "added-later");
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let elem = c.find(By::Id("footer")).await?;
+        c.execute(
+            r#"const elem = arguments[0];
+            setTimeout(function () { elem.classList.add("done"); }, 500);"#,
+            vec![elem.to_json()?],
+        )
+        .await?;
+        elem.wait_until()
+            .wait_on_mutation(Duration::from_secs(5), Duration::from_secs(10))
+            .has_class("done")
+            .await?;
+        Ok(())
+    })
+}
+
+#[rstest]
+fn driver_wait_until(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        c.wait_until().url_matches(StringMatch::new("sample_page.html").partial()).await?;
+        c.wait_until().title_contains("Sample").await?;
+        c.wait_until().number_of_windows(1).await?;
+        c.wait_until().element_count(By::Id("button-alert"), 1).await?;
+
+        c.execute(
+            r#"setTimeout(function () { document.title = "Changed"; window.ready = 1; }, 500);"#,
+            Vec::new(),
+        )
+        .await?;
+        c.wait_until().js_returns_truthy("return window.ready;").await?;
+        c.wait_until().title_matches("Changed").await?;
+
+        let result = c
+            .wait_until()
+            .wait(Duration::from_secs(1), Duration::from_millis(100))
+            .alert_present()
+            .await;
+        assert_matches!(
+            result.map_err(WebDriverError::into_inner),
+            Err(WebDriverErrorInner::Timeout(_))
+        );
+
+        c.find(By::Id("button-alert")).await?.click().await?;
+        c.wait_until().alert_present().await?;
+        c.accept_alert().await?;
+        Ok(())
+    })
+}
+
+#[rstest]
+fn wait_for_stability(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+        c.wait_for_network_idle(Duration::from_millis(200), Duration::from_secs(10)).await?;
+
+        // Install the instrumentation, then start a request that updates the page when done.
+        c.wait_for_app_stable().await?;
+        c.execute(
+            r#"fetch(arguments[0]).then(function () {
+                setTimeout(function () {
+                    const div = document.createElement("div");
+                    div.id = "fetched";
+                    document.getElementById("footer").appendChild(div);
+                }, 200);
+            });"#,
+            vec![serde_json::json!(other_page_url())],
+        )
+        .await?;
+        c.wait_for_app_stable().await?;
+        assert!(c.query(By::Id("fetched")).nowait().exists().await?);
+        Ok(())
+    })
+}
+
+#[rstest]
+fn query_combinators(test_harness: TestHarness) -> WebDriverResult<()> {
+    use thirtyfour::extensions::query::conditions::{self, and, any_of, not, or};
+    use thirtyfour::DynElementPredicate;
+
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let elems = c
+            .query(By::Tag("button"))
+            .with_filter(and(
+                conditions::element_is_displayed(true),
+                or(
+                    conditions::element_has_text("Copy", true),
+                    conditions::element_text_satisfies(|text| text.ends_with("confirm"), true),
+                ),
+            ))
+            .all_from_selector_required()
+            .await?;
+        assert_eq!(elems.len(), 2);
+
+        let others = c
+            .query(By::Tag("button"))
+            .with_filter(not(any_of([
+                DynElementPredicate::boxed(conditions::element_has_text(
+                    StringMatch::new("alert").partial(),
+                    true,
+                )),
+                DynElementPredicate::boxed(conditions::element_has_attribute(
+                    "id".into(),
+                    "button-copy",
+                    true,
+                )),
+            ])))
+            .all_from_selector()
+            .await?;
+        assert_eq!(others.len(), 2);
+        assert_eq!(others[0].id().await?.unwrap(), "button-confirm");
+
+        let elem = c.find(By::Id("button-copy")).await?;
+        let result = elem
+            .wait_until()
+            .wait(Duration::from_millis(500), Duration::from_millis(100))
+            .condition(conditions::element_has_class("missing", true))
+            .await;
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains(r#"has class "missing""#), "{err}");
+
+        let result = c
+            .query(By::Tag("button"))
+            .nowait()
+            .with_filter(conditions::describe("never", |_: WebElement| async move { Ok(false) }))
+            .first()
+            .await;
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("where never"), "{err}");
+        Ok(())
+    })
+}
+
+#[rstest]
+fn expect_assertions(test_harness: TestHarness) -> WebDriverResult<()> {
+    use thirtyfour::extensions::query::expect;
+
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        expect(c).to_have_url(url.as_str()).await?;
+        expect(c).to_have_title("Sample Page").await?;
+        expect(c.query(By::Tag("button"))).to_have_count(4).await?;
+
+        let elem = c.find(By::Id("button-copy")).await?;
+        expect(&elem).to_have_text("Copy").await?;
+        expect(&elem).to_be_visible().await?;
+        expect(&elem).to_be_enabled().await?;
+        expect(&elem).not().to_have_text("Paste").await?;
+
+        c.execute(
+            r#"const elem = arguments[0];
+            setTimeout(function () { elem.textContent = "Copied"; }, 500);"#,
+            vec![elem.to_json()?],
+        )
+        .await?;
+        expect(&elem).to_have_text("Copied").await?;
+
+        let result = expect(&elem)
+            .wait(Duration::from_millis(500), Duration::from_millis(100))
+            .to_have_text("Copies")
+            .await;
+        assert_matches!(
+            result.map_err(WebDriverError::into_inner),
+            Err(WebDriverErrorInner::ExpectationFailed(message))
+                if message.contains("observed: \"Copied\"")
+        );
+        Ok(())
+    })
+}
+
+#[rstest]
+fn query_diagnostics(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+
+        let err = c
+            .query(By::Tag("button"))
+            .nowait()
+            .and_displayed()
+            .with_text("Save")
+            .or(By::Id("save"))
+            .diagnostics(true)
+            .first()
+            .await
+            .unwrap_err();
+        let diagnostics = err.query_diagnostics().expect("diagnostics should be attached");
+        assert_eq!(diagnostics.selectors.len(), 2);
+
+        let buttons = &diagnostics.selectors[0];
+        assert_eq!(buttons.matched, 4);
+        assert_eq!(buttons.filters.len(), 2);
+        assert_eq!(buttons.filters[0].rejected, 0);
+        assert_eq!(buttons.filters[1].candidates, 4);
+        assert_eq!(buttons.filters[1].rejected, 4);
+        assert!(buttons.filters[1].observed.contains(&r#""Copy""#.to_string()));
+        assert_eq!(diagnostics.selectors[1].matched, 0);
+        assert!(err.to_string().contains("rejected 4 of 4"), "{err}");
+
+        // Diagnostics are disabled by default.
+        let err = c.query(By::Id("save")).nowait().single().await.unwrap_err();
+        assert!(err.query_diagnostics().is_none());
+        Ok(())
+    })
+}
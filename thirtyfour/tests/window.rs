@@ -36,6 +36,44 @@ fn iframe_switch(test_harness: TestHarness) -> WebDriverResult<()> {
     })
 }
 
+#[rstest]
+fn iframe_query(test_harness: TestHarness) -> WebDriverResult<()> {
+    let c = test_harness.driver();
+    block_on(async {
+        let url = sample_page_url();
+        c.goto(&url).await?;
+        c.find(By::Css("#iframe_page_id")).await?.click().await?;
+
+        let button = c.query(By::Id("iframe_button")).in_any_frame().first().await?;
+        let iframe = c.find(By::Id("iframe")).await?;
+        assert_eq!(button.frame_path(), [iframe.element_id()]);
+        // Commands for the button switch into the iframe and back again.
+        assert_eq!(button.text().await?, "Just A Button");
+        button.click().await?;
+        c.find(By::Id("root_button")).await?;
+
+        let button = c.query(By::Tag("button")).in_frame_path([By::Id("iframe")]).single().await?;
+        assert_eq!(button.id().await?.unwrap(), "iframe_button");
+        // Elements found from a bound element are bound to the same frame.
+        let div = button.parent().await?;
+        assert_eq!(div.frame_path(), button.frame_path());
+        assert_eq!(div.tag_name().await?, "div");
+
+        // The root document is searched first.
+        let buttons = c.query(By::Tag("button")).in_any_frame().any().await?;
+        assert_eq!(buttons.len(), 2);
+        assert_eq!(buttons[0].id().await?.unwrap(), "root_button");
+        assert!(buttons[0].frame_path().is_empty());
+
+        // Frame searches switch back to the frame that was current before.
+        iframe.enter_frame().await?;
+        assert_eq!(c.query(By::Tag("button")).in_any_frame().any().await?.len(), 2);
+        c.find(By::Id("iframe_button")).await?;
+        c.enter_default_frame().await?;
+        Ok(())
+    })
+}
+
 #[rstest]
 fn new_window(test_harness: TestHarness) -> WebDriverResult<()> {
     let c = test_harness.driver();